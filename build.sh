@@ -11,5 +11,5 @@ mkdir -p dist
 cp -r pkg/ dist/
 cp -r static/ dist/
 cp -r index.html dist/
-cp -r sidebar.html hmm.html faq.html fna2faa.html deltavis.html deltavis.js dist/
+cp -r sidebar.html hmm.html faq.html fna2faa.html backtranslate.html codonusage.html composition.html genecall.html deltavis.html deltavis.js dist/
 cp -r demo-data/ dist/
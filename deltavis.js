@@ -466,7 +466,10 @@ function renderDotPlot(delta, rotation, plotSize) {
 // App UI – drag-and-drop zone, file reading, error display
 // ---------------------------------------------------------------------------
 
-function initDeltaVis(container) {
+// `options.decodeInput`, if given, turns the bytes of a dropped file into text
+// (the page passes the WebAssembly reader, which also gunzips .gz files).
+function initDeltaVis(container, options = {}) {
+    const decodeInput = options.decodeInput || ((bytes) => new TextDecoder().decode(bytes));
     let state = "waiting"; // "waiting" | "error" | "displaying"
 
     render();
@@ -511,9 +514,18 @@ function initDeltaVis(container) {
 
     function readFile(file) {
         const reader = new FileReader();
-        reader.onload = () => handleFileContent(reader.result);
+        reader.onload = () => {
+            let content;
+            try {
+                content = decodeInput(new Uint8Array(reader.result));
+            } catch (err) {
+                showError(err instanceof Error ? err.message : String(err));
+                return;
+            }
+            handleFileContent(content);
+        };
         reader.onerror = () => showError("Failed to read file");
-        reader.readAsText(file);
+        reader.readAsArrayBuffer(file);
     }
 
     // --- Drop zone ---
@@ -532,7 +544,7 @@ function initDeltaVis(container) {
 
         const prompt = document.createElement("p");
         Object.assign(prompt.style, { fontSize: "1.2rem", color: "#666", marginBottom: "0.5rem" });
-        prompt.textContent = "Drop a .delta file here";
+        prompt.textContent = "Drop a .delta or .delta.gz file here";
 
         const sub = document.createElement("p");
         Object.assign(sub.style, { fontSize: "0.9rem", color: "#999" });
@@ -543,7 +555,7 @@ function initDeltaVis(container) {
         function setHover(hovering) {
             zone.style.border = hovering ? "3px solid #00BFFF" : "3px dashed #aaa";
             zone.style.background = hovering ? "#f0faff" : "#fafafa";
-            prompt.textContent = hovering ? "Drop it!" : "Drop a .delta file here";
+            prompt.textContent = hovering ? "Drop it!" : "Drop a .delta or .delta.gz file here";
         }
 
         zone.addEventListener("dragenter", (e) => { e.preventDefault(); setHover(true); });
@@ -1,62 +1,210 @@
 use serde::{Serialize, Deserialize};
+use std::fmt;
 
-const BLOSUM62_MATRIX: [[i32; 20]; 20] = [
-    [4,-1,-2,-2,0,-1,-1,0,-2,-1,-1,-1,-1,-2,-1,1,0,-3,-2,0],
-    [-1,5,0,-2,-3,1,0,-2,0,-3,-2,2,-1,-3,-2,-1,-1,-3,-2,-3],
-    [-2,0,6,1,-3,0,0,0,1,-3,-3,0,-2,-3,-2,1,0,-4,-2,-3],
-    [-2,-2,1,6,-3,0,2,-1,-1,-3,-4,-1,-3,-3,-1,0,-1,-4,-3,-3],
-    [0,-3,-3,-3,9,-3,-4,-3,-3,-1,-1,-3,-1,-2,-3,-1,-1,-2,-2,-1],
-    [-1,1,0,0,-3,5,2,-2,0,-3,-2,1,0,-3,-1,0,-1,-2,-1,-2],
-    [-1,0,0,2,-4,2,5,-2,0,-3,-3,1,-2,-3,-1,0,-1,-3,-2,-2],
-    [0,-2,0,-1,-3,-2,-2,6,-2,-4,-4,-2,-3,-3,-2,0,-2,-2,-3,-3],
-    [-2,0,1,-1,-3,0,0,-2,8,-3,-3,-1,-2,-1,-2,-1,-2,-2,2,-3],
-    [-1,-3,-3,-3,-1,-3,-3,-4,-3,4,2,-3,1,0,-3,-2,-1,-3,-1,3],
-    [-1,-2,-3,-4,-1,-2,-3,-4,-3,2,4,-2,2,0,-3,-2,-1,-2,-1,1],
-    [-1,2,0,-1,-3,1,1,-2,-1,-3,-2,5,-1,-3,-1,0,-1,-3,-2,-2],
-    [-1,-1,-2,-3,-1,0,-2,-3,-2,1,2,-1,5,0,-2,-1,-1,-1,-1,1],
-    [-2,-3,-3,-3,-2,-3,-3,-3,-1,0,0,-3,0,6,-4,-2,-2,1,3,-1],
-    [-1,-2,-2,-1,-3,-1,-1,-2,-2,-3,-3,-1,-2,-4,7,-1,-1,-4,-3,-2],
-    [1,-1,1,0,-1,0,0,0,-1,-2,-2,0,-1,-2,-1,4,1,-3,-2,-2],
-    [0,-1,0,-1,-1,-1,-1,-2,-2,-1,-1,-1,-1,-2,-1,1,5,-2,-2,0],
-    [-3,-3,-4,-4,-2,-2,-3,-2,-2,-3,-2,-3,-1,1,-4,-3,-2,11,2,-3],
-    [-2,-2,-2,-3,-2,-1,-2,-3,2,-1,-1,-2,-1,3,-3,-2,-2,2,7,-1],
-    [0,-3,-3,-3,-1,-2,-2,-3,-3,3,1,-2,1,-1,-2,-2,0,-3,-1,4],
+use crate::alphabet::{substitution_matrix_index, Alphabet};
+
+/// NCBI BLOSUM62, rows and columns in the order ARNDCQEGHILKMFPSTWYVBZX*.
+const BLOSUM62_MATRIX: [[i32; 24]; 24] = [
+    [4,-1,-2,-2,0,-1,-1,0,-2,-1,-1,-1,-1,-2,-1,1,0,-3,-2,0,-2,-1,0,-4],
+    [-1,5,0,-2,-3,1,0,-2,0,-3,-2,2,-1,-3,-2,-1,-1,-3,-2,-3,-1,0,-1,-4],
+    [-2,0,6,1,-3,0,0,0,1,-3,-3,0,-2,-3,-2,1,0,-4,-2,-3,3,0,-1,-4],
+    [-2,-2,1,6,-3,0,2,-1,-1,-3,-4,-1,-3,-3,-1,0,-1,-4,-3,-3,4,1,-1,-4],
+    [0,-3,-3,-3,9,-3,-4,-3,-3,-1,-1,-3,-1,-2,-3,-1,-1,-2,-2,-1,-3,-3,-2,-4],
+    [-1,1,0,0,-3,5,2,-2,0,-3,-2,1,0,-3,-1,0,-1,-2,-1,-2,0,3,-1,-4],
+    [-1,0,0,2,-4,2,5,-2,0,-3,-3,1,-2,-3,-1,0,-1,-3,-2,-2,1,4,-1,-4],
+    [0,-2,0,-1,-3,-2,-2,6,-2,-4,-4,-2,-3,-3,-2,0,-2,-2,-3,-3,-1,-2,-1,-4],
+    [-2,0,1,-1,-3,0,0,-2,8,-3,-3,-1,-2,-1,-2,-1,-2,-2,2,-3,0,0,-1,-4],
+    [-1,-3,-3,-3,-1,-3,-3,-4,-3,4,2,-3,1,0,-3,-2,-1,-3,-1,3,-3,-3,-1,-4],
+    [-1,-2,-3,-4,-1,-2,-3,-4,-3,2,4,-2,2,0,-3,-2,-1,-2,-1,1,-4,-3,-1,-4],
+    [-1,2,0,-1,-3,1,1,-2,-1,-3,-2,5,-1,-3,-1,0,-1,-3,-2,-2,0,1,-1,-4],
+    [-1,-1,-2,-3,-1,0,-2,-3,-2,1,2,-1,5,0,-2,-1,-1,-1,-1,1,-3,-1,-1,-4],
+    [-2,-3,-3,-3,-2,-3,-3,-3,-1,0,0,-3,0,6,-4,-2,-2,1,3,-1,-3,-3,-1,-4],
+    [-1,-2,-2,-1,-3,-1,-1,-2,-2,-3,-3,-1,-2,-4,7,-1,-1,-4,-3,-2,-2,-1,-2,-4],
+    [1,-1,1,0,-1,0,0,0,-1,-2,-2,0,-1,-2,-1,4,1,-3,-2,-2,0,0,0,-4],
+    [0,-1,0,-1,-1,-1,-1,-2,-2,-1,-1,-1,-1,-2,-1,1,5,-2,-2,0,-1,-1,0,-4],
+    [-3,-3,-4,-4,-2,-2,-3,-2,-2,-3,-2,-3,-1,1,-4,-3,-2,11,2,-3,-4,-3,-2,-4],
+    [-2,-2,-2,-3,-2,-1,-2,-3,2,-1,-1,-2,-1,3,-3,-2,-2,2,7,-1,-3,-2,-1,-4],
+    [0,-3,-3,-3,-1,-2,-2,-3,-3,3,1,-2,1,-1,-2,-2,0,-3,-1,4,-3,-2,-1,-4],
+    [-2,-1,3,4,-3,0,1,-1,0,-3,-4,0,-3,-3,-2,0,-1,-4,-3,-3,4,1,-1,-4],
+    [-1,0,0,1,-3,3,4,-2,0,-3,-3,1,-1,-3,-1,0,-1,-3,-2,-2,1,4,-1,-4],
+    [0,-1,-1,-1,-2,-1,-1,-1,-1,-1,-1,-1,-1,-1,-2,0,0,-2,-1,-1,-1,-1,-1,-4],
+    [-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,-4,1],
 ];
 
-fn aa_index(b: u8) -> Option<usize> {
-    match b.to_ascii_uppercase() {
-        b'A' => Some(0),
-        b'R' => Some(1),
-        b'N' => Some(2),
-        b'D' => Some(3),
-        b'C' => Some(4),
-        b'Q' => Some(5),
-        b'E' => Some(6),
-        b'G' => Some(7),
-        b'H' => Some(8),
-        b'I' => Some(9),
-        b'L' => Some(10),
-        b'K' => Some(11),
-        b'M' => Some(12),
-        b'F' => Some(13),
-        b'P' => Some(14),
-        b'S' => Some(15),
-        b'T' => Some(16),
-        b'W' => Some(17),
-        b'Y' => Some(18),
-        b'V' => Some(19),
+/// Index of a residue in [`BLOSUM62_MATRIX`]: the 20 amino acids, the
+/// ambiguity codes `B`, `Z` and `X`, and the stop `*`. `J`, `U` and `O` have
+/// no row in the matrix.
+fn blosum62_index(b: u8) -> Option<usize> {
+    substitution_matrix_index(b).or(match b.to_ascii_uppercase() {
+        b'B' => Some(20),
+        b'Z' => Some(21),
+        b'X' => Some(22),
+        b'*' => Some(23),
         _ => None,
-    }
+    })
 }
 
-fn blosum62_score(a: u8, b: u8) -> f64 {
-    match (aa_index(a), aa_index(b)) {
+/// Residues outside the matrix are rejected by [`check_blosum62`] before
+/// alignment; the fallback is the matrix minimum.
+pub(crate) fn blosum62_score(a: u8, b: u8) -> f64 {
+    match (blosum62_index(a), blosum62_index(b)) {
         (Some(i), Some(j)) => BLOSUM62_MATRIX[i][j] as f64,
         _ => -4.0,
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// Largest dynamic programming matrix (in cells) the aligners will allocate.
+/// The three `f64` matrices of a Gotoh alignment then take 240 MB, which a
+/// browser tab can still allocate.
+pub const MAX_DP_CELLS: usize = 10_000_000;
+
+/// Largest matrix (in cells) that can be exported with
+/// [`GotohTask::finish_with_matrices`]; enough for ~100 residue sequences.
+pub const MAX_EXPORT_CELLS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlignmentError {
+    /// A residue that the scoring scheme cannot handle; `sequence` is 1 or 2
+    /// and `position` is 1-based.
+    InvalidResidue {
+        sequence: usize,
+        position: usize,
+        residue: char,
+    },
+    EmptySequence {
+        sequence: usize,
+    },
+    InvalidGapParameters(String),
+    InvalidScoringParameters(String),
+    SizeLimitExceeded {
+        cells: usize,
+        limit: usize,
+    },
+    UnknownPreset(String),
+    InvalidAnchor(String),
+}
+
+impl fmt::Display for AlignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlignmentError::InvalidResidue {
+                sequence,
+                position,
+                residue,
+            } => write!(
+                f,
+                "Invalid residue '{}' at position {} of sequence {}",
+                residue.escape_default(),
+                position,
+                sequence
+            ),
+            AlignmentError::EmptySequence { sequence } => {
+                write!(f, "Sequence {} is empty", sequence)
+            }
+            AlignmentError::InvalidGapParameters(msg) => {
+                write!(f, "Invalid gap parameters: {}", msg)
+            }
+            AlignmentError::InvalidScoringParameters(msg) => {
+                write!(f, "Invalid scoring parameters: {}", msg)
+            }
+            AlignmentError::SizeLimitExceeded { cells, limit } => write!(
+                f,
+                "Alignment needs {} matrix cells, more than the limit of {}",
+                cells, limit
+            ),
+            AlignmentError::UnknownPreset(name) => {
+                write!(f, "Unknown alignment preset '{}'", name)
+            }
+            AlignmentError::InvalidAnchor(msg) => write!(f, "Invalid anchor: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AlignmentError {}
+
+/// Check the residues of both sequences with `is_valid`.
+///
+/// Gap characters are never accepted as input residues.
+pub(crate) fn check_residues<P>(seq1: &str, seq2: &str, is_valid: P) -> Result<(), AlignmentError>
+where
+    P: Fn(u8) -> bool,
+{
+    for (sequence, seq) in [(1, seq1), (2, seq2)] {
+        for (position, ch) in seq.chars().enumerate() {
+            let ok = ch.is_ascii() && ch != '-' && is_valid(ch as u8);
+            if !ok {
+                return Err(AlignmentError::InvalidResidue {
+                    sequence,
+                    position: position + 1,
+                    residue: ch,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that both sequences only use residues scored by BLOSUM62.
+pub(crate) fn check_blosum62(seq1: &str, seq2: &str) -> Result<(), AlignmentError> {
+    check_residues(seq1, seq2, |b| blosum62_index(b).is_some())
+}
+
+/// Check that both sequences only use residues from `alphabet`.
+pub fn check_alphabet(seq1: &str, seq2: &str, alphabet: Alphabet) -> Result<(), AlignmentError> {
+    check_residues(seq1, seq2, |b| alphabet.is_valid(b))
+}
+
+/// Check the shape of an alignment problem before allocating the matrices:
+/// both sequences non-empty and printable ASCII, and the matrices within
+/// [`MAX_DP_CELLS`].
+pub(crate) fn check_problem(seq1: &str, seq2: &str) -> Result<(), AlignmentError> {
+    if seq1.is_empty() {
+        return Err(AlignmentError::EmptySequence { sequence: 1 });
+    }
+    if seq2.is_empty() {
+        return Err(AlignmentError::EmptySequence { sequence: 2 });
+    }
+    check_residues(seq1, seq2, |b| b.is_ascii_graphic())?;
+    let cells = (seq1.len() + 1).saturating_mul(seq2.len() + 1);
+    if cells > MAX_DP_CELLS {
+        return Err(AlignmentError::SizeLimitExceeded {
+            cells,
+            limit: MAX_DP_CELLS,
+        });
+    }
+    Ok(())
+}
+
+/// Gap scores must be finite and non-positive (they are added to the score).
+pub(crate) fn check_gaps(gap_open: f64, gap_extend: f64) -> Result<(), AlignmentError> {
+    for (name, value) in [("gap open", gap_open), ("gap extend", gap_extend)] {
+        if !value.is_finite() {
+            return Err(AlignmentError::InvalidGapParameters(format!(
+                "{} score must be a finite number, got {}",
+                name, value
+            )));
+        }
+        if value > 0.0 {
+            return Err(AlignmentError::InvalidGapParameters(format!(
+                "{} score must be zero or negative, got {}",
+                name, value
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn check_match_mismatch(match_score: f64, mismatch_penalty: f64) -> Result<(), AlignmentError> {
+    if !match_score.is_finite() || !mismatch_penalty.is_finite() {
+        return Err(AlignmentError::InvalidScoringParameters(format!(
+            "match and mismatch scores must be finite numbers, got {} and {}",
+            match_score, mismatch_penalty
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlignmentResult {
     pub aligned_seq1: String,
     pub aligned_seq2: String,
@@ -64,313 +212,1517 @@ pub struct AlignmentResult {
     pub aligned_identity: f64,
     pub score: f64,
     pub alignment_markup: String,
+    /// Per-column score breakdown, only filled in when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<ColumnScore>>,
 }
 
-pub fn smith_waterman_with_matrix<F>(
-    seq1: &str,
-    seq2: &str,
+/// How an alignment column contributes to the score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnKind {
+    /// Two residues aligned to each other, scored by the substitution score.
+    Substitution,
+    /// First column of a gap.
+    GapOpen,
+    /// Any further column of a gap.
+    GapExtend,
+    /// Flanking residue outside a local alignment; contributes nothing.
+    Unaligned,
+}
+
+/// Contribution of one alignment column, with the score accumulated from the
+/// left up to and including this column.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ColumnScore {
+    pub kind: ColumnKind,
+    pub score: f64,
+    pub running_score: f64,
+}
+
+/// Assemble an [`AlignmentResult`] from the two gapped rows of an alignment.
+///
+/// Identity and aligned length are computed over the columns where neither
+/// row has a gap; `score_fn` is only used to tell similar from dissimilar
+/// residues in the markup line.
+pub(crate) fn build_alignment_result<F>(
+    aligned_seq1: Vec<u8>,
+    aligned_seq2: Vec<u8>,
+    score: f64,
+    score_fn: F,
+) -> AlignmentResult
+where
+    F: Fn(u8, u8) -> f64,
+{
+    let mut aligned_length = 0;
+    let mut aligned_identity = 0.0;
+    let mut markup = String::with_capacity(aligned_seq1.len());
+    for (&a, &b) in aligned_seq1.iter().zip(aligned_seq2.iter()) {
+        let ch = if a == b'-' || b == b'-' {
+            ' '
+        } else {
+            aligned_length += 1;
+            if a.eq_ignore_ascii_case(&b) {
+                aligned_identity += 1.0;
+                '|'
+            } else if score_fn(a, b) > 0.0 {
+                ':'
+            } else {
+                '.'
+            }
+        };
+        markup.push(ch);
+    }
+
+    AlignmentResult {
+        aligned_seq1: String::from_utf8_lossy(&aligned_seq1).into_owned(),
+        aligned_seq2: String::from_utf8_lossy(&aligned_seq2).into_owned(),
+        aligned_length,
+        aligned_identity: if aligned_length > 0 {
+            aligned_identity / aligned_length as f64
+        } else {
+            0.0
+        },
+        score,
+        alignment_markup: markup,
+        columns: None,
+    }
+}
+
+/// Plain residue-pair scoring function, e.g. a substitution matrix lookup.
+pub type ScoreFn = fn(u8, u8) -> f64;
+
+/// Scoring function chosen at run time, e.g. from a preset.
+pub type BoxedScoreFn = Box<dyn Fn(u8, u8) -> f64>;
+
+/// A dynamic programming computation that fills its matrices a few rows at a
+/// time, so that long-running alignments can report progress and be
+/// abandoned between chunks.
+pub trait AlignmentTask {
+    type Output;
+
+    /// Number of rows the task has to fill in total.
+    fn total_rows(&self) -> usize;
+
+    /// Number of rows filled so far.
+    fn rows_done(&self) -> usize;
+
+    /// Fill at most `max_rows` more rows; returns `true` once every row is
+    /// filled.
+    fn step(&mut self, max_rows: usize) -> bool;
+
+    /// Fill any remaining rows and trace back the result.
+    fn finish(self) -> Result<Self::Output, AlignmentError>;
+
+    /// Fraction of the rows filled so far, between 0 and 1.
+    fn progress(&self) -> f64 {
+        let total = self.total_rows();
+        if total == 0 {
+            1.0
+        } else {
+            self.rows_done() as f64 / total as f64
+        }
+    }
+}
+
+/// Affine-gap (Gotoh) alignment, local or global, filled row by row.
+pub struct GotohTask<F> {
+    seq1: Vec<u8>,
+    seq2: Vec<u8>,
     gap_open: f64,
     gap_extend: f64,
     score_fn: F,
-) -> AlignmentResult
+    local: bool,
+    score_matrix: Vec<Vec<f64>>,
+    ins_matrix: Vec<Vec<f64>>,
+    del_matrix: Vec<Vec<f64>>,
+    next_row: usize,
+    max_score: f64,
+    max_pos: (usize, usize),
+    column_scores: bool,
+}
+
+/// The three Gotoh matrices: best score ending in any column type, ending
+/// in a gap in seq2 (insertion of a seq1 residue), or ending in a gap in
+/// seq1 (deletion).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DpMatrixKind {
+    Score,
+    Insert,
+    Delete,
+}
+
+/// Where the value of a DP cell came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TracebackPointer {
+    /// Score cell reached by aligning the two residues.
+    Diagonal,
+    /// Score cell taken from the insert matrix.
+    Insert,
+    /// Score cell taken from the delete matrix.
+    Delete,
+    /// Gap cell that opens a gap from the score matrix.
+    Open,
+    /// Gap cell that extends the gap of the previous cell.
+    Extend,
+}
+
+/// One DP matrix with its traceback pointers, indexed `[i][j]` with row 0
+/// and column 0 standing for the empty prefixes. Unreachable cells
+/// (minus infinity) are `None`, as are the pointers of cells where a path
+/// starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DpMatrix {
+    pub values: Vec<Vec<Option<f64>>>,
+    pub pointers: Vec<Vec<Option<TracebackPointer>>>,
+}
+
+/// A cell visited by the traceback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathCell {
+    pub matrix: DpMatrixKind,
+    pub i: usize,
+    pub j: usize,
+}
+
+/// Filled Gotoh matrices of an alignment, for display.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DpMatrices {
+    pub seq1: String,
+    pub seq2: String,
+    pub local: bool,
+    pub score: DpMatrix,
+    pub insert: DpMatrix,
+    pub delete: DpMatrix,
+    /// Cells visited by the traceback, from the start of the alignment to
+    /// its end.
+    pub path: Vec<PathCell>,
+    pub alignment: AlignmentResult,
+}
+
+impl<F> GotohTask<F>
 where
     F: Fn(u8, u8) -> f64,
 {
-    let seq1 = seq1.as_bytes();
-    let seq2 = seq2.as_bytes();
-    let len1 = seq1.len();
-    let len2 = seq2.len();
+    fn new(
+        seq1: &str,
+        seq2: &str,
+        gap_open: f64,
+        gap_extend: f64,
+        score_fn: F,
+        local: bool,
+    ) -> Result<Self, AlignmentError> {
+        check_problem(seq1, seq2)?;
+        check_gaps(gap_open, gap_extend)?;
 
-    let mut score_matrix = vec![vec![0.0; len2 + 1]; len1 + 1];
-    let mut ins_matrix = vec![vec![f64::NEG_INFINITY; len2 + 1]; len1 + 1];
-    let mut del_matrix = vec![vec![f64::NEG_INFINITY; len2 + 1]; len1 + 1];
+        let len1 = seq1.len();
+        let len2 = seq2.len();
+        let mut score_matrix = vec![vec![0.0; len2 + 1]; len1 + 1];
+        let mut ins_matrix = vec![vec![f64::NEG_INFINITY; len2 + 1]; len1 + 1];
+        let mut del_matrix = vec![vec![f64::NEG_INFINITY; len2 + 1]; len1 + 1];
 
-    let mut max_score = 0.0;
-    let mut max_pos = (0, 0);
+        if !local {
+            for i in 1..=len1 {
+                ins_matrix[i][0] = if i == 1 {
+                    score_matrix[i - 1][0] + gap_open
+                } else {
+                    ins_matrix[i - 1][0] + gap_extend
+                };
+                score_matrix[i][0] = ins_matrix[i][0];
+            }
+            for j in 1..=len2 {
+                del_matrix[0][j] = if j == 1 {
+                    score_matrix[0][j - 1] + gap_open
+                } else {
+                    del_matrix[0][j - 1] + gap_extend
+                };
+                score_matrix[0][j] = del_matrix[0][j];
+            }
+        }
 
-    for i in 1..=len1 {
-        for j in 1..=len2 {
+        Ok(GotohTask {
+            seq1: seq1.as_bytes().to_vec(),
+            seq2: seq2.as_bytes().to_vec(),
+            gap_open,
+            gap_extend,
+            score_fn,
+            local,
+            score_matrix,
+            ins_matrix,
+            del_matrix,
+            next_row: 1,
+            max_score: 0.0,
+            max_pos: (0, 0),
+            column_scores: false,
+        })
+    }
+
+    /// Also report the per-column score breakdown
+    /// ([`AlignmentResult::columns`]) when the task finishes.
+    pub fn with_column_scores(mut self, enabled: bool) -> Self {
+        self.column_scores = enabled;
+        self
+    }
+
+    /// Start a Smith-Waterman (local) alignment.
+    pub fn smith_waterman(
+        seq1: &str,
+        seq2: &str,
+        gap_open: f64,
+        gap_extend: f64,
+        score_fn: F,
+    ) -> Result<Self, AlignmentError> {
+        Self::new(seq1, seq2, gap_open, gap_extend, score_fn, true)
+    }
+
+    /// Start a Needleman-Wunsch (global) alignment.
+    pub fn needleman_wunsch(
+        seq1: &str,
+        seq2: &str,
+        gap_open: f64,
+        gap_extend: f64,
+        score_fn: F,
+    ) -> Result<Self, AlignmentError> {
+        Self::new(seq1, seq2, gap_open, gap_extend, score_fn, false)
+    }
+
+    fn fill_row(&mut self, i: usize) {
+        let GotohTask {
+            seq1,
+            seq2,
+            gap_open,
+            gap_extend,
+            score_fn,
+            local,
+            score_matrix,
+            ins_matrix,
+            del_matrix,
+            max_score,
+            max_pos,
+            ..
+        } = self;
+        for j in 1..=seq2.len() {
             let match_mismatch = score_fn(seq1[i - 1], seq2[j - 1]);
             let diagonal_score = score_matrix[i - 1][j - 1] + match_mismatch;
 
             ins_matrix[i][j] =
-                (score_matrix[i - 1][j] + gap_open).max(ins_matrix[i - 1][j] + gap_extend);
+                (score_matrix[i - 1][j] + *gap_open).max(ins_matrix[i - 1][j] + *gap_extend);
             del_matrix[i][j] =
-                (score_matrix[i][j - 1] + gap_open).max(del_matrix[i][j - 1] + gap_extend);
+                (score_matrix[i][j - 1] + *gap_open).max(del_matrix[i][j - 1] + *gap_extend);
 
-            let cell_score = diagonal_score
-                .max(ins_matrix[i][j])
-                .max(del_matrix[i][j])
-                .max(0.0);
-            score_matrix[i][j] = cell_score;
+            let cell_score = diagonal_score.max(ins_matrix[i][j]).max(del_matrix[i][j]);
+            if *local {
+                let cell_score = cell_score.max(0.0);
+                score_matrix[i][j] = cell_score;
+                if cell_score > *max_score {
+                    *max_score = cell_score;
+                    *max_pos = (i, j);
+                }
+            } else {
+                score_matrix[i][j] = cell_score;
+            }
+        }
+    }
+
+    /// Walk back from the end cell, tracking which matrix the path is in so
+    /// that every column is charged exactly what the fill charged it.
+    /// Comparisons are exact: each value is recomputed with the same
+    /// operations used to fill the matrices, so the running score of the
+    /// breakdown reproduces the alignment score bit for bit.
+    fn traceback(&self) -> (AlignmentResult, Vec<PathCell>) {
+        let GotohTask {
+            seq1,
+            seq2,
+            gap_open,
+            gap_extend,
+            score_fn,
+            local,
+            score_matrix,
+            ins_matrix,
+            del_matrix,
+            max_score,
+            max_pos,
+            column_scores,
+            ..
+        } = self;
+        let (gap_open, gap_extend, local) = (*gap_open, *gap_extend, *local);
+        let len1 = seq1.len();
+        let len2 = seq2.len();
 
-            if cell_score > max_score {
-                max_score = cell_score;
-                max_pos = (i, j);
+        let (end_i, end_j, score) = if local {
+            (max_pos.0, max_pos.1, *max_score)
+        } else {
+            (len1, len2, score_matrix[len1][len2])
+        };
+
+        let mut aligned_seq1 = Vec::new();
+        let mut aligned_seq2 = Vec::new();
+        let mut kinds = Vec::new();
+        let mut path = Vec::new();
+
+        let mut i = len1;
+        let mut j = len2;
+        while i > end_i {
+            aligned_seq1.push(seq1[i - 1]);
+            aligned_seq2.push(b'-');
+            kinds.push(ColumnKind::Unaligned);
+            i -= 1;
+        }
+        while j > end_j {
+            aligned_seq1.push(b'-');
+            aligned_seq2.push(seq2[j - 1]);
+            kinds.push(ColumnKind::Unaligned);
+            j -= 1;
+        }
+
+        let mut state = DpMatrixKind::Score;
+        loop {
+            path.push(PathCell {
+                matrix: state,
+                i,
+                j,
+            });
+            if i == 0 && j == 0 {
+                break;
+            }
+            match state {
+                DpMatrixKind::Score => {
+                    let current = score_matrix[i][j];
+                    if local && current <= 0.0 {
+                        break;
+                    }
+                    if i > 0
+                        && j > 0
+                        && current == score_matrix[i - 1][j - 1] + score_fn(seq1[i - 1], seq2[j - 1])
+                    {
+                        aligned_seq1.push(seq1[i - 1]);
+                        aligned_seq2.push(seq2[j - 1]);
+                        kinds.push(ColumnKind::Substitution);
+                        i -= 1;
+                        j -= 1;
+                    } else if i > 0 && current == ins_matrix[i][j] {
+                        state = DpMatrixKind::Insert;
+                    } else if j > 0 && current == del_matrix[i][j] {
+                        state = DpMatrixKind::Delete;
+                    } else {
+                        break;
+                    }
+                }
+                DpMatrixKind::Insert => {
+                    let opened = ins_matrix[i][j] == score_matrix[i - 1][j] + gap_open;
+                    aligned_seq1.push(seq1[i - 1]);
+                    aligned_seq2.push(b'-');
+                    i -= 1;
+                    if opened {
+                        kinds.push(ColumnKind::GapOpen);
+                        state = DpMatrixKind::Score;
+                    } else {
+                        kinds.push(ColumnKind::GapExtend);
+                    }
+                }
+                DpMatrixKind::Delete => {
+                    let opened = del_matrix[i][j] == score_matrix[i][j - 1] + gap_open;
+                    aligned_seq1.push(b'-');
+                    aligned_seq2.push(seq2[j - 1]);
+                    j -= 1;
+                    if opened {
+                        kinds.push(ColumnKind::GapOpen);
+                        state = DpMatrixKind::Score;
+                    } else {
+                        kinds.push(ColumnKind::GapExtend);
+                    }
+                }
             }
         }
+        while i > 0 {
+            aligned_seq1.push(seq1[i - 1]);
+            aligned_seq2.push(b'-');
+            kinds.push(ColumnKind::Unaligned);
+            i -= 1;
+        }
+        while j > 0 {
+            aligned_seq1.push(b'-');
+            aligned_seq2.push(seq2[j - 1]);
+            kinds.push(ColumnKind::Unaligned);
+            j -= 1;
+        }
+
+        aligned_seq1.reverse();
+        aligned_seq2.reverse();
+        kinds.reverse();
+
+        path.reverse();
+
+        let columns = column_scores.then(|| {
+            let mut running_score = 0.0;
+            kinds
+                .iter()
+                .zip(aligned_seq1.iter().zip(&aligned_seq2))
+                .map(|(&kind, (&a, &b))| {
+                    let score = match kind {
+                        ColumnKind::Substitution => score_fn(a, b),
+                        ColumnKind::GapOpen => gap_open,
+                        ColumnKind::GapExtend => gap_extend,
+                        ColumnKind::Unaligned => 0.0,
+                    };
+                    if kind != ColumnKind::Unaligned {
+                        running_score += score;
+                    }
+                    ColumnScore {
+                        kind,
+                        score,
+                        running_score,
+                    }
+                })
+                .collect()
+        });
+
+        let mut result = build_alignment_result(aligned_seq1, aligned_seq2, score, score_fn);
+        result.columns = columns;
+        (result, path)
     }
 
-    let mut i = len1;
-    let mut j = len2;
-    let mut aligned_seq1 = Vec::new();
-    let mut aligned_seq2 = Vec::new();
-    let mut aligned_length = 0;
-    let mut aligned_identity = 0.0;
+    /// Pointer of every cell of the three matrices, using the same
+    /// preferences as the traceback.
+    fn pointers(&self) -> [Vec<Vec<Option<TracebackPointer>>>; 3] {
+        let len1 = self.seq1.len();
+        let len2 = self.seq2.len();
+        let mut score = vec![vec![None; len2 + 1]; len1 + 1];
+        let mut insert = vec![vec![None; len2 + 1]; len1 + 1];
+        let mut delete = vec![vec![None; len2 + 1]; len1 + 1];
+        for i in 0..=len1 {
+            for j in 0..=len2 {
+                let current = self.score_matrix[i][j];
+                score[i][j] = if self.local && current <= 0.0 {
+                    None
+                } else if i > 0
+                    && j > 0
+                    && current
+                        == self.score_matrix[i - 1][j - 1]
+                            + (self.score_fn)(self.seq1[i - 1], self.seq2[j - 1])
+                {
+                    Some(TracebackPointer::Diagonal)
+                } else if i > 0 && current == self.ins_matrix[i][j] {
+                    Some(TracebackPointer::Insert)
+                } else if j > 0 && current == self.del_matrix[i][j] {
+                    Some(TracebackPointer::Delete)
+                } else {
+                    None
+                };
+                if i > 0 && self.ins_matrix[i][j].is_finite() {
+                    insert[i][j] = Some(
+                        if self.ins_matrix[i][j] == self.score_matrix[i - 1][j] + self.gap_open {
+                            TracebackPointer::Open
+                        } else {
+                            TracebackPointer::Extend
+                        },
+                    );
+                }
+                if j > 0 && self.del_matrix[i][j].is_finite() {
+                    delete[i][j] = Some(
+                        if self.del_matrix[i][j] == self.score_matrix[i][j - 1] + self.gap_open {
+                            TracebackPointer::Open
+                        } else {
+                            TracebackPointer::Extend
+                        },
+                    );
+                }
+            }
+        }
+        [score, insert, delete]
+    }
+
+    /// Fill any remaining rows and return the alignment together with the
+    /// filled matrices, the traceback pointers and the traceback path.
+    ///
+    /// Fails with [`AlignmentError::SizeLimitExceeded`] when the matrices
+    /// have more than [`MAX_EXPORT_CELLS`] cells.
+    pub fn finish_with_matrices(mut self) -> Result<DpMatrices, AlignmentError> {
+        let cells = (self.seq1.len() + 1) * (self.seq2.len() + 1);
+        if cells > MAX_EXPORT_CELLS {
+            return Err(AlignmentError::SizeLimitExceeded {
+                cells,
+                limit: MAX_EXPORT_CELLS,
+            });
+        }
+        self.step(usize::MAX);
+        let (alignment, path) = self.traceback();
+        let [score_pointers, insert_pointers, delete_pointers] = self.pointers();
+        let values = |matrix: Vec<Vec<f64>>| -> Vec<Vec<Option<f64>>> {
+            matrix
+                .into_iter()
+                .map(|row| row.into_iter().map(|v| v.is_finite().then_some(v)).collect())
+                .collect()
+        };
+        Ok(DpMatrices {
+            seq1: String::from_utf8_lossy(&self.seq1).into_owned(),
+            seq2: String::from_utf8_lossy(&self.seq2).into_owned(),
+            local: self.local,
+            score: DpMatrix {
+                values: values(self.score_matrix),
+                pointers: score_pointers,
+            },
+            insert: DpMatrix {
+                values: values(self.ins_matrix),
+                pointers: insert_pointers,
+            },
+            delete: DpMatrix {
+                values: values(self.del_matrix),
+                pointers: delete_pointers,
+            },
+            path,
+            alignment,
+        })
+    }
+}
+
+impl<F> AlignmentTask for GotohTask<F>
+where
+    F: Fn(u8, u8) -> f64,
+{
+    type Output = AlignmentResult;
+
+    fn total_rows(&self) -> usize {
+        self.seq1.len()
+    }
+
+    fn rows_done(&self) -> usize {
+        self.next_row - 1
+    }
+
+    fn step(&mut self, max_rows: usize) -> bool {
+        let end = self.next_row.saturating_add(max_rows).min(self.seq1.len() + 1);
+        for i in self.next_row..end {
+            self.fill_row(i);
+        }
+        self.next_row = end;
+        self.next_row > self.seq1.len()
+    }
+
+    fn finish(mut self) -> Result<AlignmentResult, AlignmentError> {
+        self.step(usize::MAX);
+        Ok(self.traceback().0)
+    }
+}
+
+/// Scoring function giving `match_score` to identical residues (ignoring
+/// case) and `mismatch_penalty` to everything else.
+pub(crate) fn match_mismatch_score(
+    match_score: f64,
+    mismatch_penalty: f64,
+) -> impl Fn(u8, u8) -> f64 + Copy {
+    move |a: u8, b: u8| {
+        if a.eq_ignore_ascii_case(&b) {
+            match_score
+        } else {
+            mismatch_penalty
+        }
+    }
+}
+
+pub fn smith_waterman_blosum62_task(
+    seq1: &str,
+    seq2: &str,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<GotohTask<ScoreFn>, AlignmentError> {
+    check_blosum62(seq1, seq2)?;
+    GotohTask::smith_waterman(seq1, seq2, gap_open, gap_extend, blosum62_score)
+}
+
+pub fn smith_waterman_blosum62_internal(
+    seq1: &str,
+    seq2: &str,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<AlignmentResult, AlignmentError> {
+    smith_waterman_blosum62_task(seq1, seq2, gap_open, gap_extend)?.finish()
+}
+
+pub fn smith_waterman_task(
+    seq1: &str,
+    seq2: &str,
+    match_score: f64,
+    mismatch_penalty: f64,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<GotohTask<impl Fn(u8, u8) -> f64>, AlignmentError> {
+    check_match_mismatch(match_score, mismatch_penalty)?;
+    let score_fn = match_mismatch_score(match_score, mismatch_penalty);
+    GotohTask::smith_waterman(seq1, seq2, gap_open, gap_extend, score_fn)
+}
+
+pub fn smith_waterman_internal(
+    seq1: &str,
+    seq2: &str,
+    match_score: f64,
+    mismatch_penalty: f64,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<AlignmentResult, AlignmentError> {
+    smith_waterman_task(seq1, seq2, match_score, mismatch_penalty, gap_open, gap_extend)?.finish()
+}
+
+pub fn needleman_wunsch_blosum62_task(
+    seq1: &str,
+    seq2: &str,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<GotohTask<ScoreFn>, AlignmentError> {
+    check_blosum62(seq1, seq2)?;
+    GotohTask::needleman_wunsch(seq1, seq2, gap_open, gap_extend, blosum62_score)
+}
+
+pub fn needleman_wunsch_blosum62_internal(
+    seq1: &str,
+    seq2: &str,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<AlignmentResult, AlignmentError> {
+    needleman_wunsch_blosum62_task(seq1, seq2, gap_open, gap_extend)?.finish()
+}
+
+pub fn needleman_wunsch_task(
+    seq1: &str,
+    seq2: &str,
+    match_score: f64,
+    mismatch_penalty: f64,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<GotohTask<impl Fn(u8, u8) -> f64>, AlignmentError> {
+    check_match_mismatch(match_score, mismatch_penalty)?;
+    let score_fn = match_mismatch_score(match_score, mismatch_penalty);
+    GotohTask::needleman_wunsch(seq1, seq2, gap_open, gap_extend, score_fn)
+}
+
+pub fn needleman_wunsch_internal(
+    seq1: &str,
+    seq2: &str,
+    match_score: f64,
+    mismatch_penalty: f64,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<AlignmentResult, AlignmentError> {
+    needleman_wunsch_task(seq1, seq2, match_score, mismatch_penalty, gap_open, gap_extend)?.finish()
+}
+
+/// A pair of regions that must be aligned to each other: `start1..end1` of
+/// seq1 and `start2..end2` of seq2 (0-based, half-open).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub start1: usize,
+    pub end1: usize,
+    pub start2: usize,
+    pub end2: usize,
+}
+
+fn check_anchors(len1: usize, len2: usize, anchors: &[Anchor]) -> Result<(), AlignmentError> {
+    let (mut prev1, mut prev2) = (0, 0);
+    for (idx, anchor) in anchors.iter().enumerate() {
+        if anchor.start1 >= anchor.end1 || anchor.start2 >= anchor.end2 {
+            return Err(AlignmentError::InvalidAnchor(format!(
+                "anchor {} is empty",
+                idx + 1
+            )));
+        }
+        if anchor.end1 - anchor.start1 != anchor.end2 - anchor.start2 {
+            return Err(AlignmentError::InvalidAnchor(format!(
+                "the two regions of anchor {} differ in length",
+                idx + 1
+            )));
+        }
+        if anchor.end1 > len1 || anchor.end2 > len2 {
+            return Err(AlignmentError::InvalidAnchor(format!(
+                "anchor {} extends past the end of the sequences",
+                idx + 1
+            )));
+        }
+        if anchor.start1 < prev1 || anchor.start2 < prev2 {
+            return Err(AlignmentError::InvalidAnchor(format!(
+                "anchor {} overlaps or precedes the previous anchor",
+                idx + 1
+            )));
+        }
+        prev1 = anchor.end1;
+        prev2 = anchor.end2;
+    }
+    Ok(())
+}
+
+/// Affine score of an alignment given as gapped strings.
+fn affine_score<F>(
+    aligned1: &[u8],
+    aligned2: &[u8],
+    gap_open: f64,
+    gap_extend: f64,
+    score_fn: F,
+) -> f64
+where
+    F: Fn(u8, u8) -> f64,
+{
+    let mut total = 0.0;
+    let mut previous_gap = None;
+    for (&a, &b) in aligned1.iter().zip(aligned2) {
+        let gap = if a == b'-' {
+            Some(1)
+        } else if b == b'-' {
+            Some(2)
+        } else {
+            None
+        };
+        total += match gap {
+            None => score_fn(a, b),
+            Some(_) if gap == previous_gap => gap_extend,
+            Some(_) => gap_open,
+        };
+        previous_gap = gap;
+    }
+    total
+}
+
+/// Global alignment of the pieces between anchors, stitched into a single
+/// result. The two regions of an anchor must have the same length and are
+/// aligned residue by residue, without gaps. The reported score is
+/// recomputed on the stitched alignment, so a gap run that continues across a
+/// piece boundary is charged a single opening.
+pub fn needleman_wunsch_anchored_with_matrix<F>(
+    seq1: &str,
+    seq2: &str,
+    anchors: &[Anchor],
+    gap_open: f64,
+    gap_extend: f64,
+    score_fn: F,
+) -> Result<AlignmentResult, AlignmentError>
+where
+    F: Fn(u8, u8) -> f64,
+{
+    check_problem(seq1, seq2)?;
+    check_gaps(gap_open, gap_extend)?;
+    let (bytes1, bytes2) = (seq1.as_bytes(), seq2.as_bytes());
+    check_anchors(bytes1.len(), bytes2.len(), anchors)?;
+
+    let mut aligned1 = Vec::with_capacity(bytes1.len() + bytes2.len());
+    let mut aligned2 = Vec::with_capacity(bytes1.len() + bytes2.len());
+    let mut align_piece = |piece1: &[u8], piece2: &[u8], ungapped: bool| {
+        if ungapped || piece2.is_empty() || piece1.is_empty() {
+            aligned1.extend_from_slice(piece1);
+            aligned1.extend(std::iter::repeat_n(b'-', piece2.len().saturating_sub(piece1.len())));
+            aligned2.extend_from_slice(piece2);
+            aligned2.extend(std::iter::repeat_n(b'-', piece1.len().saturating_sub(piece2.len())));
+            return Ok(());
+        }
+        // Both pieces come from already validated sequences.
+        let piece1 = std::str::from_utf8(piece1).unwrap();
+        let piece2 = std::str::from_utf8(piece2).unwrap();
+        let result =
+            GotohTask::needleman_wunsch(piece1, piece2, gap_open, gap_extend, &score_fn)?.finish()?;
+        aligned1.extend_from_slice(result.aligned_seq1.as_bytes());
+        aligned2.extend_from_slice(result.aligned_seq2.as_bytes());
+        Ok::<(), AlignmentError>(())
+    };
 
-    while i > max_pos.0 {
-        aligned_seq1.push(seq1[i - 1]);
-        aligned_seq2.push(b'-');
-        i -= 1;
+    let (mut pos1, mut pos2) = (0, 0);
+    for anchor in anchors {
+        align_piece(&bytes1[pos1..anchor.start1], &bytes2[pos2..anchor.start2], false)?;
+        // Anchors have regions of equal length, placed without gaps.
+        let anchored1 = &bytes1[anchor.start1..anchor.end1];
+        let anchored2 = &bytes2[anchor.start2..anchor.end2];
+        align_piece(anchored1, anchored2, true)?;
+        pos1 = anchor.end1;
+        pos2 = anchor.end2;
     }
+    align_piece(&bytes1[pos1..], &bytes2[pos2..], false)?;
+
+    let score = affine_score(&aligned1, &aligned2, gap_open, gap_extend, &score_fn);
+    Ok(build_alignment_result(aligned1, aligned2, score, score_fn))
+}
+
+pub fn needleman_wunsch_anchored_internal(
+    seq1: &str,
+    seq2: &str,
+    anchors: &[Anchor],
+    match_score: f64,
+    mismatch_penalty: f64,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<AlignmentResult, AlignmentError> {
+    check_match_mismatch(match_score, mismatch_penalty)?;
+    let score_fn = match_mismatch_score(match_score, mismatch_penalty);
+    needleman_wunsch_anchored_with_matrix(seq1, seq2, anchors, gap_open, gap_extend, score_fn)
+}
 
-    while j > max_pos.1 {
-        aligned_seq1.push(b'-');
-        aligned_seq2.push(seq2[j - 1]);
-        j -= 1;
+pub fn needleman_wunsch_anchored_blosum62_internal(
+    seq1: &str,
+    seq2: &str,
+    anchors: &[Anchor],
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<AlignmentResult, AlignmentError> {
+    check_blosum62(seq1, seq2)?;
+    needleman_wunsch_anchored_with_matrix(seq1, seq2, anchors, gap_open, gap_extend, blosum62_score)
+}
+
+/// Two-piece (convex) affine gap model, as used by minimap2.
+///
+/// A gap of length `L` scores the better of the two affine pieces,
+/// `max(gap_open + (L - 1) * gap_extend, long_gap_open + (L - 1) * long_gap_extend)`,
+/// so short gaps follow the first piece and long gaps the cheaper-to-extend
+/// second piece.  As elsewhere in this module, gap scores are negative.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DualAffineGaps {
+    pub gap_open: f64,
+    pub gap_extend: f64,
+    pub long_gap_open: f64,
+    pub long_gap_extend: f64,
+}
+
+impl DualAffineGaps {
+    /// Score of a single gap of `len` residues.
+    pub fn gap_score(&self, len: usize) -> f64 {
+        if len == 0 {
+            return 0.0;
+        }
+        let extra = (len - 1) as f64;
+        (self.gap_open + extra * self.gap_extend)
+            .max(self.long_gap_open + extra * self.long_gap_extend)
     }
 
-    while i > 0 && j > 0 && score_matrix[i][j] > 0.0 {
-        let current_score = score_matrix[i][j];
-        let diagonal_score = score_matrix[i - 1][j - 1];
-        let up_score = ins_matrix[i][j];
-        let left_score = del_matrix[i][j];
+    fn check(&self) -> Result<(), AlignmentError> {
+        check_gaps(self.gap_open, self.gap_extend)?;
+        check_gaps(self.long_gap_open, self.long_gap_extend)
+    }
 
-        let diag = diagonal_score + score_fn(seq1[i - 1], seq2[j - 1]);
-        if (current_score - diag).abs() < 1e-6 {
+    fn pieces(&self) -> [(f64, f64); 2] {
+        [
+            (self.gap_open, self.gap_extend),
+            (self.long_gap_open, self.long_gap_extend),
+        ]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DualState {
+    Score,
+    Insert(usize),
+    Delete(usize),
+}
+
+/// Gotoh-style alignment with a [`DualAffineGaps`] model, filled row by row.
+///
+/// Keeps one insertion and one deletion matrix per gap piece.  As in
+/// [`GotohTask`], the traceback tracks which matrix it is in, so a gap is
+/// always walked back within the piece that produced it.  Traceback
+/// comparisons are exact: every value is recomputed with the same operations
+/// used to fill the matrices.
+pub struct DualAffineTask<F> {
+    seq1: Vec<u8>,
+    seq2: Vec<u8>,
+    pieces: [(f64, f64); 2],
+    score_fn: F,
+    local: bool,
+    score_matrix: Vec<Vec<f64>>,
+    ins: Vec<Vec<Vec<f64>>>,
+    del: Vec<Vec<Vec<f64>>>,
+    next_row: usize,
+    max_score: f64,
+    max_pos: (usize, usize),
+}
+
+impl<F> DualAffineTask<F>
+where
+    F: Fn(u8, u8) -> f64,
+{
+    fn new(
+        seq1: &str,
+        seq2: &str,
+        gaps: &DualAffineGaps,
+        score_fn: F,
+        local: bool,
+    ) -> Result<Self, AlignmentError> {
+        check_problem(seq1, seq2)?;
+        gaps.check()?;
+        // Five matrices instead of three.
+        let cells = (seq1.len() + 1) * (seq2.len() + 1) * 5 / 3;
+        if cells > MAX_DP_CELLS {
+            return Err(AlignmentError::SizeLimitExceeded {
+                cells,
+                limit: MAX_DP_CELLS,
+            });
+        }
+
+        let len1 = seq1.len();
+        let len2 = seq2.len();
+        let pieces = gaps.pieces();
+
+        let mut score_matrix = vec![vec![0.0; len2 + 1]; len1 + 1];
+        let mut ins = vec![vec![vec![f64::NEG_INFINITY; len2 + 1]; len1 + 1]; 2];
+        let mut del = vec![vec![vec![f64::NEG_INFINITY; len2 + 1]; len1 + 1]; 2];
+
+        if !local {
+            for (k, &(open, extend)) in pieces.iter().enumerate() {
+                for i in 1..=len1 {
+                    ins[k][i][0] = if i == 1 {
+                        score_matrix[0][0] + open
+                    } else {
+                        ins[k][i - 1][0] + extend
+                    };
+                }
+                for j in 1..=len2 {
+                    del[k][0][j] = if j == 1 {
+                        score_matrix[0][0] + open
+                    } else {
+                        del[k][0][j - 1] + extend
+                    };
+                }
+            }
+            for i in 1..=len1 {
+                score_matrix[i][0] = ins[0][i][0].max(ins[1][i][0]);
+            }
+            for j in 1..=len2 {
+                score_matrix[0][j] = del[0][0][j].max(del[1][0][j]);
+            }
+        }
+
+        Ok(DualAffineTask {
+            seq1: seq1.as_bytes().to_vec(),
+            seq2: seq2.as_bytes().to_vec(),
+            pieces,
+            score_fn,
+            local,
+            score_matrix,
+            ins,
+            del,
+            next_row: 1,
+            max_score: 0.0,
+            max_pos: (0, 0),
+        })
+    }
+
+    /// Start a local alignment.
+    pub fn smith_waterman(
+        seq1: &str,
+        seq2: &str,
+        gaps: &DualAffineGaps,
+        score_fn: F,
+    ) -> Result<Self, AlignmentError> {
+        Self::new(seq1, seq2, gaps, score_fn, true)
+    }
+
+    /// Start a global alignment.
+    pub fn needleman_wunsch(
+        seq1: &str,
+        seq2: &str,
+        gaps: &DualAffineGaps,
+        score_fn: F,
+    ) -> Result<Self, AlignmentError> {
+        Self::new(seq1, seq2, gaps, score_fn, false)
+    }
+
+    fn fill_row(&mut self, i: usize) {
+        let DualAffineTask {
+            seq1,
+            seq2,
+            pieces,
+            score_fn,
+            local,
+            score_matrix,
+            ins,
+            del,
+            max_score,
+            max_pos,
+            ..
+        } = self;
+        for j in 1..=seq2.len() {
+            let mut best = score_matrix[i - 1][j - 1] + score_fn(seq1[i - 1], seq2[j - 1]);
+            for (k, &(open, extend)) in pieces.iter().enumerate() {
+                ins[k][i][j] = (score_matrix[i - 1][j] + open).max(ins[k][i - 1][j] + extend);
+                del[k][i][j] = (score_matrix[i][j - 1] + open).max(del[k][i][j - 1] + extend);
+                best = best.max(ins[k][i][j]).max(del[k][i][j]);
+            }
+            if *local {
+                best = best.max(0.0);
+                if best > *max_score {
+                    *max_score = best;
+                    *max_pos = (i, j);
+                }
+            }
+            score_matrix[i][j] = best;
+        }
+    }
+
+    fn traceback(self) -> AlignmentResult {
+        let DualAffineTask {
+            seq1,
+            seq2,
+            pieces,
+            score_fn,
+            local,
+            score_matrix,
+            ins,
+            del,
+            max_score,
+            max_pos,
+            ..
+        } = self;
+        let len1 = seq1.len();
+        let len2 = seq2.len();
+
+        let (end_i, end_j, score) = if local {
+            (max_pos.0, max_pos.1, max_score)
+        } else {
+            (len1, len2, score_matrix[len1][len2])
+        };
+
+        let mut aligned_seq1 = Vec::new();
+        let mut aligned_seq2 = Vec::new();
+
+        let mut i = len1;
+        let mut j = len2;
+        while i > end_i {
             aligned_seq1.push(seq1[i - 1]);
-            aligned_seq2.push(seq2[j - 1]);
+            aligned_seq2.push(b'-');
             i -= 1;
+        }
+        while j > end_j {
+            aligned_seq1.push(b'-');
+            aligned_seq2.push(seq2[j - 1]);
             j -= 1;
-            aligned_length += 1;
-            if seq1[i].to_ascii_uppercase() == seq2[j].to_ascii_uppercase() {
-                aligned_identity += 1.0;
+        }
+
+        let mut state = DualState::Score;
+        while i > 0 || j > 0 {
+            match state {
+                DualState::Score => {
+                    let current = score_matrix[i][j];
+                    if local && current <= 0.0 {
+                        break;
+                    }
+                    if i > 0
+                        && j > 0
+                        && current == score_matrix[i - 1][j - 1] + score_fn(seq1[i - 1], seq2[j - 1])
+                    {
+                        aligned_seq1.push(seq1[i - 1]);
+                        aligned_seq2.push(seq2[j - 1]);
+                        i -= 1;
+                        j -= 1;
+                    } else if let Some(k) = (0..2).find(|&k| i > 0 && current == ins[k][i][j]) {
+                        state = DualState::Insert(k);
+                    } else if let Some(k) = (0..2).find(|&k| j > 0 && current == del[k][i][j]) {
+                        state = DualState::Delete(k);
+                    } else {
+                        break;
+                    }
+                }
+                DualState::Insert(k) => {
+                    let extend = pieces[k].1;
+                    let extended = i > 1 && ins[k][i][j] == ins[k][i - 1][j] + extend;
+                    aligned_seq1.push(seq1[i - 1]);
+                    aligned_seq2.push(b'-');
+                    i -= 1;
+                    if !extended {
+                        state = DualState::Score;
+                    }
+                }
+                DualState::Delete(k) => {
+                    let extend = pieces[k].1;
+                    let extended = j > 1 && del[k][i][j] == del[k][i][j - 1] + extend;
+                    aligned_seq1.push(b'-');
+                    aligned_seq2.push(seq2[j - 1]);
+                    j -= 1;
+                    if !extended {
+                        state = DualState::Score;
+                    }
+                }
             }
-        } else if (current_score - up_score).abs() < 1e-6 {
+        }
+        while i > 0 {
             aligned_seq1.push(seq1[i - 1]);
             aligned_seq2.push(b'-');
             i -= 1;
-        } else if (current_score - left_score).abs() < 1e-6 {
+        }
+        while j > 0 {
             aligned_seq1.push(b'-');
             aligned_seq2.push(seq2[j - 1]);
             j -= 1;
-        } else {
-            break;
         }
+
+        aligned_seq1.reverse();
+        aligned_seq2.reverse();
+
+        build_alignment_result(aligned_seq1, aligned_seq2, score, score_fn)
     }
-    while i > 0 {
-        aligned_seq1.push(seq1[i - 1]);
-        aligned_seq2.push(b'-');
-        i -= 1;
-    }
-    while j > 0 {
-        aligned_seq1.push(b'-');
-        aligned_seq2.push(seq2[j - 1]);
-        j -= 1;
+}
+
+impl<F> AlignmentTask for DualAffineTask<F>
+where
+    F: Fn(u8, u8) -> f64,
+{
+    type Output = AlignmentResult;
+
+    fn total_rows(&self) -> usize {
+        self.seq1.len()
     }
 
-    aligned_seq1.reverse();
-    aligned_seq2.reverse();
+    fn rows_done(&self) -> usize {
+        self.next_row - 1
+    }
 
-    let mut markup = String::with_capacity(aligned_seq1.len());
-    for (&a, &b) in aligned_seq1.iter().zip(aligned_seq2.iter()) {
-        let ch = if a.to_ascii_uppercase() == b.to_ascii_uppercase() && a != b'-' && b != b'-' {
-            '|'
-        } else if a == b'-' || b == b'-' {
-            ' '
-        } else if score_fn(a, b) > 0.0 {
-            ':'
-        } else {
-            '.'
-        };
-        markup.push(ch);
+    fn step(&mut self, max_rows: usize) -> bool {
+        let end = self.next_row.saturating_add(max_rows).min(self.seq1.len() + 1);
+        for i in self.next_row..end {
+            self.fill_row(i);
+        }
+        self.next_row = end;
+        self.next_row > self.seq1.len()
     }
 
-    AlignmentResult {
-        aligned_seq1: String::from_utf8(aligned_seq1).unwrap(),
-        aligned_seq2: String::from_utf8(aligned_seq2).unwrap(),
-        aligned_length,
-        aligned_identity: if aligned_length > 0 {
-            aligned_identity / aligned_length as f64
-        } else {
-            0.0
-        },
-        score: max_score,
-        alignment_markup: markup,
+    fn finish(mut self) -> Result<AlignmentResult, AlignmentError> {
+        self.step(usize::MAX);
+        Ok(self.traceback())
     }
 }
 
-pub fn smith_waterman_blosum62_internal(
+pub fn smith_waterman_dual_affine_blosum62_task(
     seq1: &str,
     seq2: &str,
-    gap_open: f64,
-    gap_extend: f64,
-) -> AlignmentResult {
-    smith_waterman_with_matrix(seq1, seq2, gap_open, gap_extend, |a, b| {
-        blosum62_score(a, b) as f64
-    })
+    gaps: &DualAffineGaps,
+) -> Result<DualAffineTask<ScoreFn>, AlignmentError> {
+    check_blosum62(seq1, seq2)?;
+    DualAffineTask::smith_waterman(seq1, seq2, gaps, blosum62_score)
 }
 
-pub fn smith_waterman_internal(
+pub fn smith_waterman_dual_affine_blosum62_internal(
+    seq1: &str,
+    seq2: &str,
+    gaps: &DualAffineGaps,
+) -> Result<AlignmentResult, AlignmentError> {
+    smith_waterman_dual_affine_blosum62_task(seq1, seq2, gaps)?.finish()
+}
+
+pub fn smith_waterman_dual_affine_task(
     seq1: &str,
     seq2: &str,
     match_score: f64,
     mismatch_penalty: f64,
-    gap_open: f64,
-    gap_extend: f64,
-) -> AlignmentResult {
-    smith_waterman_with_matrix(seq1, seq2, gap_open, gap_extend, |a, b| {
-        if a.to_ascii_uppercase() == b.to_ascii_uppercase() {
-            match_score
-        } else {
-            mismatch_penalty
-        }
-    })
+    gaps: &DualAffineGaps,
+) -> Result<DualAffineTask<impl Fn(u8, u8) -> f64>, AlignmentError> {
+    check_match_mismatch(match_score, mismatch_penalty)?;
+    let score_fn = match_mismatch_score(match_score, mismatch_penalty);
+    DualAffineTask::smith_waterman(seq1, seq2, gaps, score_fn)
 }
 
-pub fn needleman_wunsch_with_matrix<F>(
+pub fn smith_waterman_dual_affine_internal(
     seq1: &str,
     seq2: &str,
-    gap_open: f64,
-    gap_extend: f64,
-    score_fn: F,
-) -> AlignmentResult
-where
-    F: Fn(u8, u8) -> f64,
-{
-    let seq1 = seq1.as_bytes();
-    let seq2 = seq2.as_bytes();
-    let len1 = seq1.len();
-    let len2 = seq2.len();
-
-    let mut score_matrix = vec![vec![0.0; len2 + 1]; len1 + 1];
-    let mut ins_matrix = vec![vec![f64::NEG_INFINITY; len2 + 1]; len1 + 1];
-    let mut del_matrix = vec![vec![f64::NEG_INFINITY; len2 + 1]; len1 + 1];
-
-    for i in 1..=len1 {
-        ins_matrix[i][0] = if i == 1 {
-            score_matrix[i - 1][0] + gap_open
-        } else {
-            ins_matrix[i - 1][0] + gap_extend
-        };
-        score_matrix[i][0] = ins_matrix[i][0];
-    }
-    for j in 1..=len2 {
-        del_matrix[0][j] = if j == 1 {
-            score_matrix[0][j - 1] + gap_open
-        } else {
-            del_matrix[0][j - 1] + gap_extend
-        };
-        score_matrix[0][j] = del_matrix[0][j];
-    }
+    match_score: f64,
+    mismatch_penalty: f64,
+    gaps: &DualAffineGaps,
+) -> Result<AlignmentResult, AlignmentError> {
+    smith_waterman_dual_affine_task(seq1, seq2, match_score, mismatch_penalty, gaps)?.finish()
+}
 
-    for i in 1..=len1 {
-        for j in 1..=len2 {
-            let match_mismatch = score_fn(seq1[i - 1], seq2[j - 1]);
-            let diagonal_score = score_matrix[i - 1][j - 1] + match_mismatch;
+pub fn needleman_wunsch_dual_affine_blosum62_task(
+    seq1: &str,
+    seq2: &str,
+    gaps: &DualAffineGaps,
+) -> Result<DualAffineTask<ScoreFn>, AlignmentError> {
+    check_blosum62(seq1, seq2)?;
+    DualAffineTask::needleman_wunsch(seq1, seq2, gaps, blosum62_score)
+}
 
-            ins_matrix[i][j] =
-                (score_matrix[i - 1][j] + gap_open).max(ins_matrix[i - 1][j] + gap_extend);
-            del_matrix[i][j] =
-                (score_matrix[i][j - 1] + gap_open).max(del_matrix[i][j - 1] + gap_extend);
+pub fn needleman_wunsch_dual_affine_blosum62_internal(
+    seq1: &str,
+    seq2: &str,
+    gaps: &DualAffineGaps,
+) -> Result<AlignmentResult, AlignmentError> {
+    needleman_wunsch_dual_affine_blosum62_task(seq1, seq2, gaps)?.finish()
+}
 
-            score_matrix[i][j] = diagonal_score.max(ins_matrix[i][j]).max(del_matrix[i][j]);
-        }
+pub fn needleman_wunsch_dual_affine_task(
+    seq1: &str,
+    seq2: &str,
+    match_score: f64,
+    mismatch_penalty: f64,
+    gaps: &DualAffineGaps,
+) -> Result<DualAffineTask<impl Fn(u8, u8) -> f64>, AlignmentError> {
+    check_match_mismatch(match_score, mismatch_penalty)?;
+    let score_fn = match_mismatch_score(match_score, mismatch_penalty);
+    DualAffineTask::needleman_wunsch(seq1, seq2, gaps, score_fn)
+}
+
+pub fn needleman_wunsch_dual_affine_internal(
+    seq1: &str,
+    seq2: &str,
+    match_score: f64,
+    mismatch_penalty: f64,
+    gaps: &DualAffineGaps,
+) -> Result<AlignmentResult, AlignmentError> {
+    needleman_wunsch_dual_affine_task(seq1, seq2, match_score, mismatch_penalty, gaps)?.finish()
+}
+
+/// Unit-cost (Levenshtein) alignment of `seq1` against `seq2`, computed with
+/// the bit-parallel algorithm of Myers (1999) in Hyyrö's blocked form.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditDistanceResult {
+    pub distance: usize,
+    /// 0-based, half-open range of `seq2` that `seq1` was aligned to; all of
+    /// `seq2` for a global alignment.
+    pub start: usize,
+    pub end: usize,
+    /// Cheap lower bound on the identity, `1 - distance / max(len1, end - start)`,
+    /// available without a traceback.
+    pub estimated_identity: f64,
+    /// Full alignment (score is `-distance`), if a traceback was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alignment: Option<AlignmentResult>,
+}
+
+const WORD_BITS: usize = 64;
+
+/// One column step of a 64-row block (Hyyrö 2003). `hin` is the horizontal
+/// delta entering the top of the block; returns the delta leaving the row
+/// selected by `out_bit`.
+fn advance_block(pv: &mut u64, mv: &mut u64, mut eq: u64, hin: i32, out_bit: u64) -> i32 {
+    let xv = eq | *mv;
+    if hin < 0 {
+        eq |= 1;
     }
+    let xh = (((eq & *pv).wrapping_add(*pv)) ^ *pv) | eq;
+    let mut ph = *mv | !(xh | *pv);
+    let mut mh = *pv & xh;
+    let hout = if ph & out_bit != 0 {
+        1
+    } else if mh & out_bit != 0 {
+        -1
+    } else {
+        0
+    };
+    ph <<= 1;
+    mh <<= 1;
+    if hin < 0 {
+        mh |= 1;
+    } else if hin > 0 {
+        ph |= 1;
+    }
+    *pv = mh | !(xv | ph);
+    *mv = ph & xv;
+    hout
+}
 
-    let mut i = len1;
-    let mut j = len2;
-    let mut aligned_seq1 = Vec::new();
-    let mut aligned_seq2 = Vec::new();
-    let mut aligned_length = 0;
-    let mut aligned_identity = 0.0;
+struct MyersColumns {
+    blocks: usize,
+    /// Vertical deltas `(pv, mv)` of every block after each column of
+    /// `seq2`, kept only when a traceback is wanted.
+    history: Vec<(u64, u64)>,
+}
 
-    while i > 0 || j > 0 {
-        if i > 0
-            && j > 0
-            && (score_matrix[i][j]
-                - (score_matrix[i - 1][j - 1] + score_fn(seq1[i - 1], seq2[j - 1])))
-                .abs()
-                < 1e-6
-        {
-            aligned_seq1.push(seq1[i - 1]);
-            aligned_seq2.push(seq2[j - 1]);
-            if seq1[i - 1].to_ascii_uppercase() == seq2[j - 1].to_ascii_uppercase() {
-                aligned_identity += 1.0;
+impl MyersColumns {
+    /// `D[i][j] - D[0][j]`, from the vertical deltas of column `j`.
+    fn offset(&self, i: usize, j: usize) -> isize {
+        if j == 0 {
+            return i as isize;
+        }
+        let column = &self.history[(j - 1) * self.blocks..j * self.blocks];
+        let mut total = 0isize;
+        for (block, &(pv, mv)) in column.iter().enumerate() {
+            let first = block * WORD_BITS;
+            if first >= i {
+                break;
             }
-            aligned_length += 1;
-            i -= 1;
-            j -= 1;
-        } else if i > 0 && (score_matrix[i][j] - ins_matrix[i][j]).abs() < 1e-6 {
-            aligned_seq1.push(seq1[i - 1]);
-            aligned_seq2.push(b'-');
-            i -= 1;
-        } else if j > 0 && (score_matrix[i][j] - del_matrix[i][j]).abs() < 1e-6 {
-            aligned_seq1.push(b'-');
-            aligned_seq2.push(seq2[j - 1]);
-            j -= 1;
-        } else {
-            break;
+            let rows = (i - first).min(WORD_BITS);
+            let mask = if rows == WORD_BITS {
+                u64::MAX
+            } else {
+                (1u64 << rows) - 1
+            };
+            total += (pv & mask).count_ones() as isize - (mv & mask).count_ones() as isize;
         }
+        total
     }
+}
 
-    aligned_seq1.reverse();
-    aligned_seq2.reverse();
+/// Run the bit-parallel recurrence of `pattern` over `text`; returns the
+/// distance and the end column in `text`, or `None` past `max_distance`.
+fn myers_scan(
+    pattern: &[u8],
+    text: &[u8],
+    semi_global: bool,
+    max_distance: Option<usize>,
+    columns: &mut MyersColumns,
+    keep_history: bool,
+) -> Option<(usize, usize)> {
+    let m = pattern.len();
+    let n = text.len();
+    let blocks = columns.blocks;
+    if !semi_global && max_distance.is_some_and(|k| m.abs_diff(n) > k) {
+        return None;
+    }
 
-    let mut markup = String::with_capacity(aligned_seq1.len());
-    for (&a, &b) in aligned_seq1.iter().zip(aligned_seq2.iter()) {
-        let ch = if a.to_ascii_uppercase() == b.to_ascii_uppercase() && a != b'-' && b != b'-' {
-            '|'
-        } else if a == b'-' || b == b'-' {
-            ' '
-        } else if score_fn(a, b) > 0.0 {
-            ':'
-        } else {
-            '.'
-        };
-        markup.push(ch);
+    let mut peq = vec![0u64; 256 * blocks];
+    for (i, &b) in pattern.iter().enumerate() {
+        peq[b.to_ascii_uppercase() as usize * blocks + i / WORD_BITS] |= 1 << (i % WORD_BITS);
     }
+    let last_bit = 1u64 << ((m - 1) % WORD_BITS);
+    let top_delta = if semi_global { 0 } else { 1 };
 
-    AlignmentResult {
-        aligned_seq1: String::from_utf8(aligned_seq1).unwrap(),
-        aligned_seq2: String::from_utf8(aligned_seq2).unwrap(),
-        aligned_length,
-        aligned_identity: if aligned_length > 0 {
-            aligned_identity / aligned_length as f64
-        } else {
-            0.0
-        },
-        score: score_matrix[len1][len2],
-        alignment_markup: markup,
+    let mut pv = vec![u64::MAX; blocks];
+    let mut mv = vec![0u64; blocks];
+    let mut score = m;
+    let (mut best, mut best_end) = (m, 0);
+    for (j, &c) in text.iter().enumerate() {
+        let eq = &peq[c.to_ascii_uppercase() as usize * blocks..][..blocks];
+        let mut carry = top_delta;
+        for b in 0..blocks {
+            let out_bit = if b + 1 == blocks { last_bit } else { 1 << 63 };
+            carry = advance_block(&mut pv[b], &mut mv[b], eq[b], carry, out_bit);
+        }
+        score = (score as isize + carry as isize) as usize;
+        if keep_history {
+            columns
+                .history
+                .extend(pv.iter().copied().zip(mv.iter().copied()));
+        }
+        if semi_global && score < best {
+            best = score;
+            best_end = j + 1;
+        }
+        // The last row changes by at most one per column, so the final
+        // global distance is at least `score - remaining columns`.
+        if !semi_global && max_distance.is_some_and(|k| score > k + (n - j - 1)) {
+            return None;
+        }
+    }
+    let (distance, end) = if semi_global { (best, best_end) } else { (score, n) };
+    if max_distance.is_some_and(|k| distance > k) {
+        return None;
     }
+    Some((distance, end))
 }
 
-pub fn needleman_wunsch_blosum62_internal(
+fn edit_distance_impl(
     seq1: &str,
     seq2: &str,
-    gap_open: f64,
-    gap_extend: f64,
-) -> AlignmentResult {
-    needleman_wunsch_with_matrix(seq1, seq2, gap_open, gap_extend, |a, b| {
-        blosum62_score(a, b) as f64
-    })
+    semi_global: bool,
+    max_distance: Option<usize>,
+    traceback: bool,
+) -> Result<Option<EditDistanceResult>, AlignmentError> {
+    if seq1.is_empty() {
+        return Err(AlignmentError::EmptySequence { sequence: 1 });
+    }
+    if seq2.is_empty() {
+        return Err(AlignmentError::EmptySequence { sequence: 2 });
+    }
+    check_residues(seq1, seq2, |b| b.is_ascii_graphic())?;
+    let pattern = seq1.as_bytes();
+    let text = seq2.as_bytes();
+    let m = pattern.len();
+    let n = text.len();
+    let blocks = m.div_ceil(WORD_BITS);
+    if traceback {
+        let words = blocks.saturating_mul(n);
+        if words > MAX_DP_CELLS {
+            return Err(AlignmentError::SizeLimitExceeded {
+                cells: words,
+                limit: MAX_DP_CELLS,
+            });
+        }
+    }
+
+    let mut columns = MyersColumns {
+        blocks,
+        history: Vec::with_capacity(if traceback { blocks * n } else { 0 }),
+    };
+    let Some((distance, end)) =
+        myers_scan(pattern, text, semi_global, max_distance, &mut columns, traceback)
+    else {
+        return Ok(None);
+    };
+
+    let top = |j: usize| if semi_global { 0 } else { j as isize };
+    let cell = |i: usize, j: usize| top(j) + columns.offset(i, j);
+    let mut start = 0;
+    let alignment = if traceback {
+        let mut aligned1 = Vec::with_capacity(m + n);
+        let mut aligned2 = Vec::with_capacity(m + n);
+        let (mut i, mut j) = (m, end);
+        while i > 0 || (!semi_global && j > 0) {
+            let here = cell(i, j);
+            if i > 0 && j > 0 {
+                let cost = !pattern[i - 1].eq_ignore_ascii_case(&text[j - 1]) as isize;
+                if cell(i - 1, j - 1) + cost == here {
+                    aligned1.push(pattern[i - 1]);
+                    aligned2.push(text[j - 1]);
+                    i -= 1;
+                    j -= 1;
+                    continue;
+                }
+            }
+            if i > 0 && cell(i - 1, j) + 1 == here {
+                aligned1.push(pattern[i - 1]);
+                aligned2.push(b'-');
+                i -= 1;
+            } else {
+                aligned1.push(b'-');
+                aligned2.push(text[j - 1]);
+                j -= 1;
+            }
+        }
+        start = j;
+        aligned1.reverse();
+        aligned2.reverse();
+        Some(build_alignment_result(
+            aligned1,
+            aligned2,
+            -(distance as f64),
+            |a: u8, b: u8| if a.eq_ignore_ascii_case(&b) { 1.0 } else { -1.0 },
+        ))
+    } else {
+        if semi_global {
+            // Without a traceback, find the start by scanning the reversed
+            // pattern backwards from the end position.
+            let rev_pattern: Vec<u8> = pattern.iter().rev().copied().collect();
+            let rev_text: Vec<u8> = text[..end].iter().rev().copied().collect();
+            if let Some((_, back)) =
+                myers_scan(&rev_pattern, &rev_text, true, Some(distance), &mut columns, false)
+            {
+                start = end - back;
+            }
+        }
+        None
+    };
+
+    let span = m.max(end - start);
+    Ok(Some(EditDistanceResult {
+        distance,
+        start,
+        end,
+        estimated_identity: 1.0 - distance as f64 / span as f64,
+        alignment,
+    }))
 }
 
-pub fn needleman_wunsch_internal(
+/// Global Levenshtein distance between `seq1` and `seq2` (case-insensitive).
+/// Returns `Ok(None)` if the distance is larger than `max_distance`.
+pub fn edit_distance_internal(
     seq1: &str,
     seq2: &str,
-    match_score: f64,
-    mismatch_penalty: f64,
-    gap_open: f64,
-    gap_extend: f64,
-) -> AlignmentResult {
-    needleman_wunsch_with_matrix(seq1, seq2, gap_open, gap_extend, |a, b| {
-        if a.to_ascii_uppercase() == b.to_ascii_uppercase() {
-            match_score
-        } else {
-            mismatch_penalty
-        }
-    })
+    max_distance: Option<usize>,
+    traceback: bool,
+) -> Result<Option<EditDistanceResult>, AlignmentError> {
+    edit_distance_impl(seq1, seq2, false, max_distance, traceback)
+}
+
+/// Best Levenshtein distance of all of `seq1` against any substring of
+/// `seq2` (end gaps in `seq2` are free).
+pub fn edit_distance_semi_global_internal(
+    seq1: &str,
+    seq2: &str,
+    max_distance: Option<usize>,
+    traceback: bool,
+) -> Result<Option<EditDistanceResult>, AlignmentError> {
+    edit_distance_impl(seq1, seq2, true, max_distance, traceback)
 }
 
 #[cfg(test)]
@@ -379,7 +1731,7 @@ mod tests {
 
     #[test]
     fn align_identical_sequences() {
-        let r = smith_waterman_internal("GATTACA", "GATTACA", 2.0, -1.0, -1.0, -0.5);
+        let r = smith_waterman_internal("GATTACA", "GATTACA", 2.0, -1.0, -1.0, -0.5).unwrap();
         assert_eq!(r.aligned_seq1, "GATTACA");
         assert_eq!(r.aligned_seq2, "GATTACA");
         assert_eq!(r.aligned_length, 7);
@@ -389,7 +1741,7 @@ mod tests {
 
     #[test]
     fn align_acacacta_agcacaca() {
-        let r = smith_waterman_internal("ACACACTA", "AGCACACA", 2.0, -1.0, -1.0, -0.5);
+        let r = smith_waterman_internal("ACACACTA", "AGCACACA", 2.0, -1.0, -1.0, -0.5).unwrap();
         assert_eq!(r.aligned_seq1, "A-CACACTA");
         assert_eq!(r.aligned_seq2, "AGCACAC-A");
         assert_eq!(r.aligned_length, 7);
@@ -398,7 +1750,7 @@ mod tests {
 
     #[test]
     fn align_gattaca_gcatgcu() {
-        let r = smith_waterman_internal("GATTACA", "GCATGCU", 2.0, -1.0, -1.0, -0.5);
+        let r = smith_waterman_internal("GATTACA", "GCATGCU", 2.0, -1.0, -1.0, -0.5).unwrap();
         assert_eq!(r.aligned_seq1, "G-AT---TACA");
         assert_eq!(r.aligned_seq2, "GCATGCU----");
         assert_eq!(r.aligned_length, 3);
@@ -407,7 +1759,7 @@ mod tests {
 
     #[test]
     fn blosum62_identical_sequences() {
-        let r = smith_waterman_blosum62_internal("GATTACA", "GATTACA", -1.0, -0.5);
+        let r = smith_waterman_blosum62_internal("GATTACA", "GATTACA", -1.0, -0.5).unwrap();
         assert_eq!(r.aligned_seq1, "GATTACA");
         assert_eq!(r.aligned_seq2, "GATTACA");
         assert_eq!(r.aligned_length, 7);
@@ -416,7 +1768,7 @@ mod tests {
 
     #[test]
     fn smith_waterman_case_insensitive() {
-        let r = smith_waterman_internal("gAttAcA", "GATTACA", 2.0, -1.0, -1.0, -0.5);
+        let r = smith_waterman_internal("gAttAcA", "GATTACA", 2.0, -1.0, -1.0, -0.5).unwrap();
         assert_eq!(r.aligned_seq1, "gAttAcA");
         assert_eq!(r.aligned_seq2, "GATTACA");
         assert_eq!(r.aligned_length, 7);
@@ -426,7 +1778,7 @@ mod tests {
 
     #[test]
     fn nw_identical_sequences() {
-        let r = needleman_wunsch_internal("GATTACA", "GATTACA", 2.0, -1.0, -1.0, -0.5);
+        let r = needleman_wunsch_internal("GATTACA", "GATTACA", 2.0, -1.0, -1.0, -0.5).unwrap();
         assert_eq!(r.aligned_seq1, "GATTACA");
         assert_eq!(r.aligned_seq2, "GATTACA");
         assert_eq!(r.aligned_length, 7);
@@ -435,7 +1787,7 @@ mod tests {
 
     #[test]
     fn needleman_wunsch_case_insensitive() {
-        let r = needleman_wunsch_internal("GATTACA", "gattaca", 2.0, -1.0, -1.0, -0.5);
+        let r = needleman_wunsch_internal("GATTACA", "gattaca", 2.0, -1.0, -1.0, -0.5).unwrap();
         assert_eq!(r.aligned_seq1, "GATTACA");
         assert_eq!(r.aligned_seq2, "gattaca");
         assert_eq!(r.aligned_length, 7);
@@ -445,7 +1797,7 @@ mod tests {
 
     #[test]
     fn nw_blosum62_identical_sequences() {
-        let r = needleman_wunsch_blosum62_internal("GATTACA", "GATTACA", -1.0, -0.5);
+        let r = needleman_wunsch_blosum62_internal("GATTACA", "GATTACA", -1.0, -0.5).unwrap();
         assert_eq!(r.aligned_seq1, "GATTACA");
         assert_eq!(r.aligned_seq2, "GATTACA");
         assert_eq!(r.aligned_length, 7);
@@ -454,10 +1806,10 @@ mod tests {
 
     #[test]
     fn blosum62_symmetry() {
-        for i in 0..20 {
-            for j in 0..20 {
+        for (i, row) in BLOSUM62_MATRIX.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
                 assert_eq!(
-                    BLOSUM62_MATRIX[i][j],
+                    value,
                     BLOSUM62_MATRIX[j][i],
                     "BLOSUM62 matrix is not symmetric at indices ({}, {})",
                     i, j
@@ -466,6 +1818,540 @@ mod tests {
         }
     }
 
+    #[test]
+    fn blosum62_ambiguity_codes() {
+        assert_eq!(blosum62_score(b'B', b'D'), 4.0);
+        assert_eq!(blosum62_score(b'z', b'E'), 4.0);
+        assert_eq!(blosum62_score(b'X', b'W'), -2.0);
+        assert_eq!(blosum62_score(b'*', b'*'), 1.0);
+        let result = needleman_wunsch_blosum62_internal("MKBZX", "MKDEA", -10.0, -0.5).unwrap();
+        assert_eq!(result.score, 5.0 + 5.0 + 4.0 + 4.0 + 0.0);
+        for residue in ['J', 'U', 'O'] {
+            let seq = format!("MK{}", residue);
+            let err = needleman_wunsch_blosum62_internal(&seq, "MKL", -10.0, -0.5).unwrap_err();
+            assert_eq!(
+                err,
+                AlignmentError::InvalidResidue {
+                    sequence: 1,
+                    position: 3,
+                    residue
+                }
+            );
+        }
+    }
+
+    /// Reference alignment score with an arbitrary gap function, using the
+    /// cubic-time recurrence that tries every gap length explicitly.
+    fn reference_score<G, F>(seq1: &[u8], seq2: &[u8], gap: G, score_fn: F, local: bool) -> f64
+    where
+        G: Fn(usize) -> f64,
+        F: Fn(u8, u8) -> f64,
+    {
+        let (len1, len2) = (seq1.len(), seq2.len());
+        let mut h = vec![vec![0.0; len2 + 1]; len1 + 1];
+        if !local {
+            for (i, row) in h.iter_mut().enumerate().skip(1) {
+                row[0] = gap(i);
+            }
+            for (j, cell) in h[0].iter_mut().enumerate().skip(1) {
+                *cell = gap(j);
+            }
+        }
+        let mut best = 0.0f64;
+        for i in 1..=len1 {
+            for j in 1..=len2 {
+                let mut v = h[i - 1][j - 1] + score_fn(seq1[i - 1], seq2[j - 1]);
+                for k in 1..=i {
+                    v = v.max(h[i - k][j] + gap(k));
+                }
+                for k in 1..=j {
+                    v = v.max(h[i][j - k] + gap(k));
+                }
+                if local {
+                    v = v.max(0.0);
+                    best = best.max(v);
+                }
+                h[i][j] = v;
+            }
+        }
+        if local {
+            best
+        } else {
+            h[len1][len2]
+        }
+    }
+
+    /// Score an alignment from its gapped rows under a dual-affine model.
+    fn rescore<F>(aligned1: &str, aligned2: &str, gaps: &DualAffineGaps, score_fn: F) -> f64
+    where
+        F: Fn(u8, u8) -> f64,
+    {
+        let (a1, a2) = (aligned1.as_bytes(), aligned2.as_bytes());
+        let mut total = 0.0;
+        let mut col = 0;
+        while col < a1.len() {
+            if a1[col] == b'-' || a2[col] == b'-' {
+                let in_first = a1[col] == b'-';
+                let mut len = 0;
+                while col < a1.len() && (a1[col] == b'-') == in_first && (a2[col] == b'-') != in_first {
+                    len += 1;
+                    col += 1;
+                }
+                total += gaps.gap_score(len);
+            } else {
+                total += score_fn(a1[col], a2[col]);
+                col += 1;
+            }
+        }
+        total
+    }
+
+    fn pseudo_random_dna(seed: &mut u64, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                b"ACGT"[(*seed >> 33) as usize % 4]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dual_affine_matches_reference() {
+        let gaps = DualAffineGaps {
+            gap_open: -4.0,
+            gap_extend: -2.0,
+            long_gap_open: -13.0,
+            long_gap_extend: -0.5,
+        };
+        let score_fn = |a: u8, b: u8| if a == b { 2.0 } else { -4.0 };
+        let mut seed = 7;
+        for round in 0..40 {
+            let seq1 = pseudo_random_dna(&mut seed, 5 + round % 17);
+            let mut seq2 = pseudo_random_dna(&mut seed, 3 + round % 11);
+            if round % 3 == 0 {
+                // plant a long deletion so the second piece matters
+                seq2 = [&seq1[..seq1.len() / 3], &seq2[..], &seq1[seq1.len() / 3..]].concat();
+                seq2.drain(2..seq2.len().min(14));
+            }
+            let s1 = std::str::from_utf8(&seq1).unwrap();
+            let s2 = std::str::from_utf8(&seq2).unwrap();
+            for local in [false, true] {
+                let expected = reference_score(&seq1, &seq2, |l| gaps.gap_score(l), score_fn, local);
+                let r = DualAffineTask::new(s1, s2, &gaps, score_fn, local)
+                    .and_then(|task| task.finish())
+                    .unwrap();
+                assert!(
+                    (r.score - expected).abs() < 1e-9,
+                    "local={} {} vs {}: got {}, expected {}",
+                    local, s1, s2, r.score, expected
+                );
+                if !local {
+                    let rescored = rescore(&r.aligned_seq1, &r.aligned_seq2, &gaps, score_fn);
+                    assert!((rescored - r.score).abs() < 1e-9);
+                }
+                assert_eq!(r.aligned_seq1.replace('-', ""), s1);
+                assert_eq!(r.aligned_seq2.replace('-', ""), s2);
+            }
+        }
+    }
+
+    #[test]
+    fn dual_affine_long_gap_uses_second_piece() {
+        let gaps = DualAffineGaps {
+            gap_open: -4.0,
+            gap_extend: -2.0,
+            long_gap_open: -13.0,
+            long_gap_extend: -0.5,
+        };
+        let r = needleman_wunsch_dual_affine_internal(
+            "ACGTACGTTTTTTTTTTTTTTTTTTTTGCATGCA",
+            "ACGTACGTGCATGCA",
+            2.0,
+            -4.0,
+            &gaps,
+        )
+        .unwrap();
+        assert!(r.aligned_seq2.contains(&"-".repeat(19)));
+        assert!(!r.aligned_seq1.contains('-'));
+        // 15 matches (30) plus one gap of 19: max(-4 - 36, -13 - 9)
+        assert!((r.score - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn dual_affine_with_equal_pieces_is_affine() {
+        let seq1 = "MTFSSTSSAPPPSPLLPATRITVYGCGRDEAALFRRTAPRFGVEATLTEAAVSEENAEMAAGNQCISIDHKTPVTPATLRALHRAG";
+        let seq2 = "MSYRDLGLIDSEVIAERRVRALDDSSPSAVPTTGVRVFGCGHDEAVLFREMGTRLGITPSITEEAISETNAELARGNRCISVSHKTQ";
+        let gaps = DualAffineGaps {
+            gap_open: -10.0,
+            gap_extend: -0.5,
+            long_gap_open: -10.0,
+            long_gap_extend: -0.5,
+        };
+        assert_eq!(
+            smith_waterman_dual_affine_blosum62_internal(seq1, seq2, &gaps).unwrap().score,
+            smith_waterman_blosum62_internal(seq1, seq2, -10.0, -0.5).unwrap().score
+        );
+        assert_eq!(
+            needleman_wunsch_dual_affine_blosum62_internal(seq1, seq2, &gaps).unwrap().score,
+            needleman_wunsch_blosum62_internal(seq1, seq2, -10.0, -0.5).unwrap().score
+        );
+    }
+
+    #[test]
+    fn rejects_empty_sequences() {
+        let err = smith_waterman_internal("", "ACGT", 2.0, -1.0, -1.0, -0.5).unwrap_err();
+        assert_eq!(err, AlignmentError::EmptySequence { sequence: 1 });
+        let err = needleman_wunsch_blosum62_internal("MKV", "", -10.0, -0.5).unwrap_err();
+        assert_eq!(err, AlignmentError::EmptySequence { sequence: 2 });
+    }
+
+    #[test]
+    fn rejects_invalid_residues() {
+        let err = smith_waterman_blosum62_internal("MK1V", "MKV", -10.0, -0.5).unwrap_err();
+        assert_eq!(
+            err,
+            AlignmentError::InvalidResidue {
+                sequence: 1,
+                position: 3,
+                residue: '1'
+            }
+        );
+        let err = needleman_wunsch_internal("ACGT", "ACÉT", 2.0, -1.0, -1.0, -0.5).unwrap_err();
+        assert_eq!(
+            err,
+            AlignmentError::InvalidResidue {
+                sequence: 2,
+                position: 3,
+                residue: 'É'
+            }
+        );
+        assert!(smith_waterman_internal("AC-GT", "ACGT", 2.0, -1.0, -1.0, -0.5).is_err());
+    }
+
+    #[test]
+    fn rejects_incoherent_gap_parameters() {
+        let err = smith_waterman_internal("ACGT", "ACGT", 2.0, -1.0, 1.0, -0.5).unwrap_err();
+        assert!(matches!(err, AlignmentError::InvalidGapParameters(_)));
+        let err = needleman_wunsch_internal("ACGT", "ACGT", 2.0, -1.0, -1.0, f64::NAN).unwrap_err();
+        assert!(matches!(err, AlignmentError::InvalidGapParameters(_)));
+        let gaps = DualAffineGaps {
+            gap_open: -4.0,
+            gap_extend: -2.0,
+            long_gap_open: -13.0,
+            long_gap_extend: 0.5,
+        };
+        let err = needleman_wunsch_dual_affine_internal("ACGT", "ACGT", 2.0, -4.0, &gaps).unwrap_err();
+        assert!(matches!(err, AlignmentError::InvalidGapParameters(_)));
+    }
+
+    #[test]
+    fn rejects_oversized_problems() {
+        // 2000 x 5000 cells, including the first row and column, is the
+        // largest allowed matrix.
+        let seq1 = "A".repeat(1_999);
+        let seq2 = "A".repeat(4_999);
+        assert!(check_problem(&seq1, &seq2).is_ok());
+        let seq1 = "A".repeat(2_000);
+        let err = smith_waterman_internal(&seq1, &seq2, 2.0, -1.0, -1.0, -0.5).unwrap_err();
+        assert_eq!(
+            err,
+            AlignmentError::SizeLimitExceeded {
+                cells: 2_001 * 5_000,
+                limit: MAX_DP_CELLS
+            }
+        );
+    }
+
+    #[test]
+    fn stepping_in_chunks_matches_one_shot() {
+        let mut seed = 7;
+        let seq1 = String::from_utf8(pseudo_random_dna(&mut seed, 120)).unwrap();
+        let seq2 = String::from_utf8(pseudo_random_dna(&mut seed, 97)).unwrap();
+        let gaps = DualAffineGaps {
+            gap_open: -4.0,
+            gap_extend: -1.0,
+            long_gap_open: -12.0,
+            long_gap_extend: -0.25,
+        };
+
+        let mut task = needleman_wunsch_task(&seq1, &seq2, 2.0, -1.0, -3.0, -0.5).unwrap();
+        assert_eq!(task.progress(), 0.0);
+        let mut steps = 0;
+        while !task.step(7) {
+            steps += 1;
+            assert_eq!(task.rows_done(), 7 * steps);
+        }
+        assert_eq!(task.rows_done(), task.total_rows());
+        assert_eq!(task.progress(), 1.0);
+        let chunked = task.finish().unwrap();
+        let one_shot = needleman_wunsch_internal(&seq1, &seq2, 2.0, -1.0, -3.0, -0.5).unwrap();
+        assert_eq!(chunked.aligned_seq1, one_shot.aligned_seq1);
+        assert_eq!(chunked.aligned_seq2, one_shot.aligned_seq2);
+        assert_eq!(chunked.score, one_shot.score);
+
+        let mut task = smith_waterman_dual_affine_task(&seq1, &seq2, 2.0, -1.0, &gaps).unwrap();
+        while !task.step(13) {}
+        let chunked = task.finish().unwrap();
+        let one_shot =
+            smith_waterman_dual_affine_internal(&seq1, &seq2, 2.0, -1.0, &gaps).unwrap();
+        assert_eq!(chunked.aligned_seq1, one_shot.aligned_seq1);
+        assert_eq!(chunked.aligned_seq2, one_shot.aligned_seq2);
+        assert_eq!(chunked.score, one_shot.score);
+    }
+
+    #[test]
+    fn column_scores_reproduce_score() {
+        fn check(result: &AlignmentResult) {
+            let columns = result.columns.as_ref().unwrap();
+            assert_eq!(columns.len(), result.aligned_seq1.len());
+            let mut total = 0.0;
+            for (column, (a, b)) in columns
+                .iter()
+                .zip(result.aligned_seq1.bytes().zip(result.aligned_seq2.bytes()))
+            {
+                let gapped = a == b'-' || b == b'-';
+                assert_eq!(column.kind == ColumnKind::Substitution, !gapped);
+                if column.kind != ColumnKind::Unaligned {
+                    total += column.score;
+                }
+                assert_eq!(column.running_score, total);
+            }
+            assert_eq!(total, result.score);
+        }
+
+        let mut seed = 11;
+        for _ in 0..20 {
+            let seq1 = String::from_utf8(pseudo_random_dna(&mut seed, 60)).unwrap();
+            let seq2 = String::from_utf8(pseudo_random_dna(&mut seed, 45)).unwrap();
+            let local = smith_waterman_task(&seq1, &seq2, 1.3, -0.7, -3.1, -0.3)
+                .unwrap()
+                .with_column_scores(true)
+                .finish()
+                .unwrap();
+            check(&local);
+            let global = needleman_wunsch_task(&seq1, &seq2, 1.3, -0.7, -3.1, -0.3)
+                .unwrap()
+                .with_column_scores(true)
+                .finish()
+                .unwrap();
+            check(&global);
+            let plain = needleman_wunsch_internal(&seq1, &seq2, 1.3, -0.7, -3.1, -0.3).unwrap();
+            assert!(plain.columns.is_none());
+            assert_eq!(plain.aligned_seq1, global.aligned_seq1);
+        }
+
+        let result = smith_waterman_blosum62_task("HEAGAWGHEE", "PAWHEAE", -10.0, -0.5)
+            .unwrap()
+            .with_column_scores(true)
+            .finish()
+            .unwrap();
+        check(&result);
+        let result =
+            needleman_wunsch_blosum62_task("MKTAYIAKQRQISFVKSHFSRQ", "MKTAYIAKQRHQISFVKSQ", -10.0, -0.5)
+                .unwrap()
+                .with_column_scores(true)
+                .finish()
+                .unwrap();
+        check(&result);
+        assert!(result
+            .columns
+            .unwrap()
+            .iter()
+            .any(|column| column.kind == ColumnKind::GapOpen));
+    }
+
+    #[test]
+    fn exports_matrices_and_path() {
+        let matrices = needleman_wunsch_task("GATTACA", "GCATGCU", 2.0, -1.0, -3.0, -0.5)
+            .unwrap()
+            .finish_with_matrices()
+            .unwrap();
+        let plain = needleman_wunsch_internal("GATTACA", "GCATGCU", 2.0, -1.0, -3.0, -0.5).unwrap();
+        assert_eq!(matrices.alignment.aligned_seq1, plain.aligned_seq1);
+        assert_eq!(matrices.score.values.len(), 8);
+        assert_eq!(matrices.score.values[0].len(), 8);
+        assert_eq!(matrices.score.values[7][7], Some(plain.score));
+        assert_eq!(matrices.insert.values[0][3], None);
+        assert_eq!(matrices.score.pointers[0][0], None);
+        assert_eq!(matrices.score.pointers[0][2], Some(TracebackPointer::Delete));
+        assert_eq!(matrices.delete.pointers[0][1], Some(TracebackPointer::Open));
+        assert_eq!(matrices.delete.pointers[0][2], Some(TracebackPointer::Extend));
+
+        let path = &matrices.path;
+        assert_eq!(path[0], PathCell { matrix: DpMatrixKind::Score, i: 0, j: 0 });
+        assert_eq!(path[path.len() - 1], PathCell { matrix: DpMatrixKind::Score, i: 7, j: 7 });
+        let moves = path.windows(2).filter(|w| (w[0].i, w[0].j) != (w[1].i, w[1].j)).count();
+        assert_eq!(moves, plain.aligned_seq1.len());
+        for cell in path {
+            let matrix = match cell.matrix {
+                DpMatrixKind::Score => &matrices.score,
+                DpMatrixKind::Insert => &matrices.insert,
+                DpMatrixKind::Delete => &matrices.delete,
+            };
+            assert!(matrix.values[cell.i][cell.j].is_some());
+        }
+
+        let matrices = smith_waterman_blosum62_task("HEAGAWGHEE", "PAWHEAE", -10.0, -0.5)
+            .unwrap()
+            .finish_with_matrices()
+            .unwrap();
+        let last = matrices.path[matrices.path.len() - 1];
+        assert_eq!(matrices.score.values[last.i][last.j], Some(matrices.alignment.score));
+        let first = matrices.path[0];
+        assert_eq!(matrices.score.values[first.i][first.j], Some(0.0));
+        assert_eq!(matrices.score.pointers[first.i][first.j], None);
+
+        let long = "A".repeat(100);
+        let err = smith_waterman_task(&long, &long, 2.0, -1.0, -1.0, -0.5)
+            .unwrap()
+            .finish_with_matrices()
+            .unwrap_err();
+        assert!(matches!(err, AlignmentError::SizeLimitExceeded { limit: MAX_EXPORT_CELLS, .. }));
+    }
+
+    #[test]
+    fn anchored_alignment_respects_anchors() {
+        let seq1 = "GATTACAGATTACA";
+        let seq2 = "GATTACATTACA";
+        let free = needleman_wunsch_internal(seq1, seq2, 2.0, -1.0, -3.0, -0.5).unwrap();
+        let unanchored =
+            needleman_wunsch_anchored_internal(seq1, seq2, &[], 2.0, -1.0, -3.0, -0.5).unwrap();
+        assert_eq!(unanchored.score, free.score);
+        assert_eq!(unanchored.aligned_seq1, free.aligned_seq1);
+
+        // Force seq1[7..10] ("GAT") onto seq2[9..12] ("ACA").
+        let anchor = Anchor {
+            start1: 7,
+            end1: 10,
+            start2: 9,
+            end2: 12,
+        };
+        let r = needleman_wunsch_anchored_internal(seq1, seq2, &[anchor], 2.0, -1.0, -3.0, -0.5)
+            .unwrap();
+        assert_eq!(r.aligned_seq1.replace('-', ""), seq1);
+        assert_eq!(r.aligned_seq2.replace('-', ""), seq2);
+        let column = |s: &str, residues: usize| {
+            s.char_indices()
+                .filter(|&(_, c)| c != '-')
+                .nth(residues)
+                .map(|(idx, _)| idx)
+                .unwrap()
+        };
+        for k in 0..3 {
+            assert_ne!(column(&free.aligned_seq1, 7 + k), column(&free.aligned_seq2, 9 + k));
+            assert_eq!(column(&r.aligned_seq1, 7 + k), column(&r.aligned_seq2, 9 + k));
+        }
+        assert!(r.score < free.score);
+        let rescored = affine_score(
+            r.aligned_seq1.as_bytes(),
+            r.aligned_seq2.as_bytes(),
+            -3.0,
+            -0.5,
+            match_mismatch_score(2.0, -1.0),
+        );
+        assert_eq!(r.score, rescored);
+    }
+
+    #[test]
+    fn rejects_invalid_anchors() {
+        let anchor = |start1, end1, start2, end2| Anchor {
+            start1,
+            end1,
+            start2,
+            end2,
+        };
+        for anchors in [
+            vec![anchor(2, 2, 1, 3)],
+            vec![anchor(0, 30, 0, 30)],
+            vec![anchor(0, 3, 0, 2)],
+            vec![anchor(4, 6, 4, 6), anchor(5, 7, 7, 8)],
+        ] {
+            let err = needleman_wunsch_anchored_blosum62_internal(
+                "HEAGAWGHEE",
+                "PAWHEAE",
+                &anchors,
+                -10.0,
+                -0.5,
+            )
+            .unwrap_err();
+            assert!(matches!(err, AlignmentError::InvalidAnchor(_)));
+        }
+    }
+
+    fn levenshtein_reference(a: &[u8], b: &[u8], semi_global: bool) -> usize {
+        let mut prev: Vec<usize> = (0..=b.len())
+            .map(|j| if semi_global { 0 } else { j })
+            .collect();
+        for i in 1..=a.len() {
+            let mut row = vec![i; b.len() + 1];
+            for j in 1..=b.len() {
+                let sub = prev[j - 1] + (a[i - 1] != b[j - 1]) as usize;
+                row[j] = sub.min(prev[j] + 1).min(row[j - 1] + 1);
+            }
+            prev = row;
+        }
+        if semi_global {
+            *prev.iter().min().unwrap()
+        } else {
+            prev[b.len()]
+        }
+    }
+
+    fn unit_cost(result: &AlignmentResult) -> usize {
+        result
+            .aligned_seq1
+            .bytes()
+            .zip(result.aligned_seq2.bytes())
+            .filter(|(a, b)| a != b)
+            .count()
+    }
+
+    #[test]
+    fn edit_distance_matches_reference() {
+        let mut seed = 3;
+        for (len1, len2) in [(1, 5), (20, 17), (64, 70), (65, 64), (150, 131), (200, 260)] {
+            let seq1 = pseudo_random_dna(&mut seed, len1);
+            let seq2 = pseudo_random_dna(&mut seed, len2);
+            let s1 = std::str::from_utf8(&seq1).unwrap();
+            let s2 = std::str::from_utf8(&seq2).unwrap();
+
+            let global = edit_distance_internal(s1, s2, None, true).unwrap().unwrap();
+            assert_eq!(global.distance, levenshtein_reference(&seq1, &seq2, false));
+            let alignment = global.alignment.unwrap();
+            assert_eq!(unit_cost(&alignment), global.distance);
+            assert_eq!(alignment.aligned_seq2.replace('-', ""), s2);
+
+            let semi = edit_distance_semi_global_internal(s1, s2, None, true)
+                .unwrap()
+                .unwrap();
+            assert_eq!(semi.distance, levenshtein_reference(&seq1, &seq2, true));
+            let alignment = semi.alignment.unwrap();
+            assert_eq!(unit_cost(&alignment), semi.distance);
+            assert_eq!(alignment.aligned_seq1.replace('-', ""), s1);
+            assert_eq!(alignment.aligned_seq2.replace('-', ""), &s2[semi.start..semi.end]);
+
+            let quick = edit_distance_semi_global_internal(s1, s2, None, false)
+                .unwrap()
+                .unwrap();
+            assert_eq!(quick.distance, semi.distance);
+            let span = &seq2[quick.start..quick.end];
+            assert_eq!(levenshtein_reference(&seq1, span, false), quick.distance);
+        }
+    }
+
+    #[test]
+    fn edit_distance_cutoff() {
+        let r = edit_distance_internal("kitten", "SITTING", None, false).unwrap().unwrap();
+        assert_eq!(r.distance, 3);
+        assert!(edit_distance_internal("kitten", "sitting", Some(3), false).unwrap().is_some());
+        assert!(edit_distance_internal("kitten", "sitting", Some(2), false).unwrap().is_none());
+        assert!(edit_distance_internal("ACGT", "ACGTACGTACGT", Some(4), false).unwrap().is_none());
+        let r = edit_distance_semi_global_internal("GATTACA", "CCCCGATTTACACCC", Some(1), false)
+            .unwrap()
+            .unwrap();
+        assert_eq!((r.distance, r.start, r.end), (1, 4, 12));
+    }
+
     #[test]
     fn sw_known_cases() {
         let seq1 = "MTFSSTSSAPPPSPLLPATRITVYGCGRDEAALFRRTAPRFGVEATLTEAAVSEENAEMAAGNQCISIDHKTPVTPATLRALHRAGVTYISTRSIGYNHIDVTYAAGVGISVENVTYSPAGVADYTLMLMLMAVRNAKSTVRRAELHDYRLNEIRGKELRDLTVGVIGTGRIGAAVVDRLRGFGSRVLAYGKRPTIAADYVSLDELLRSSDIVSLHVPLTPDTHHLLDQSRIRRMKSGAFVINTGRGPLIDTEALVPALESGRLSGAALDVIEGEEGIFYADCRNRTIESTWLPRLQKMPNVLISPHTAYYTDHALMDTVENSIINCLNFGSRKQHGVGQVGQVEGRHRIRGLFRRTRRFRQVRPGGRTQPRHREVPAVLRGDHEGRRLETLRRARPGLGERRLPS";
@@ -473,31 +2359,31 @@ mod tests {
 
         // tested againt EMBL-EBI alignment tool & against Biopython
         assert_eq!(
-            smith_waterman_blosum62_internal(seq1, seq2, -10.0, -0.5).score,
+            smith_waterman_blosum62_internal(seq1, seq2, -10.0, -0.5).unwrap().score,
             1178.0);
 
-        let r5_005 = smith_waterman_blosum62_internal(seq1, seq2, -5.0, -0.05);
+        let r5_005 = smith_waterman_blosum62_internal(seq1, seq2, -5.0, -0.05).unwrap();
         assert!((r5_005.score - 1198.1).abs() < 0.01, "Expected score close to 1198.1, got {}", r5_005.score);
 
         assert_eq!(
-            smith_waterman_blosum62_internal(seq1, seq2, -5.0, -0.5).score,
+            smith_waterman_blosum62_internal(seq1, seq2, -5.0, -0.5).unwrap().score,
             1185.0);
 
         // NW Tested againt Biopython's implementation
         assert_eq!(
-            needleman_wunsch_blosum62_internal(seq1, seq2, -10.0, -0.5).score,
+            needleman_wunsch_blosum62_internal(seq1, seq2, -10.0, -0.5).unwrap().score,
             1130.0);
 
         assert_eq!(
-            needleman_wunsch_blosum62_internal(seq1, seq2, -2.0, -0.5).score,
+            needleman_wunsch_blosum62_internal(seq1, seq2, -2.0, -0.5).unwrap().score,
             1181.0);
 
         assert_eq!(
-            needleman_wunsch_blosum62_internal(&seq1[10..100], &seq2[10..100], -2.0, -0.5).score,
+            needleman_wunsch_blosum62_internal(&seq1[10..100], &seq2[10..100], -2.0, -0.5).unwrap().score,
             201.0);
 
         assert_eq!(
-            needleman_wunsch_blosum62_internal(&seq1[10..100], &seq2[10..100], -7.0, -0.5).score,
+            needleman_wunsch_blosum62_internal(&seq1[10..100], &seq2[10..100], -7.0, -0.5).unwrap().score,
             178.5);
 
     }
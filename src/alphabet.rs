@@ -0,0 +1,363 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+
+/// Residue alphabets understood by the tools.
+///
+/// Each alphabet lists its canonical residues first, followed by the IUPAC
+/// ambiguity codes it accepts. The digital code of a residue is its index in
+/// [`Alphabet::symbols`], so codes below [`Alphabet::canonical_len`] are
+/// canonical residues (the same layout HMMER uses).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alphabet {
+    Dna,
+    Rna,
+    Protein,
+}
+
+const DNA_SYMBOLS: &[u8] = b"ACGTRYMKSWHBVDN";
+const RNA_SYMBOLS: &[u8] = b"ACGURYMKSWHBVDN";
+const PROTEIN_SYMBOLS: &[u8] = b"ACDEFGHIKLMNPQRSTVWYBJZOUX*";
+
+impl Alphabet {
+    /// Parse an alphabet name: `dna`, `rna`, or `protein` (also `amino`/`aa`),
+    /// ignoring case.
+    pub fn from_name(name: &str) -> Result<Alphabet, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "dna" | "nucleotide" => Ok(Alphabet::Dna),
+            "rna" => Ok(Alphabet::Rna),
+            "protein" | "amino" | "aa" => Ok(Alphabet::Protein),
+            _ => Err(format!(
+                "Unknown alphabet '{}' (expected dna, rna or protein)",
+                name
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Alphabet::Dna => "dna",
+            Alphabet::Rna => "rna",
+            Alphabet::Protein => "protein",
+        }
+    }
+
+    /// Canonical residues followed by ambiguity codes, all upper case.
+    pub fn symbols(self) -> &'static [u8] {
+        match self {
+            Alphabet::Dna => DNA_SYMBOLS,
+            Alphabet::Rna => RNA_SYMBOLS,
+            Alphabet::Protein => PROTEIN_SYMBOLS,
+        }
+    }
+
+    pub fn canonical_len(self) -> usize {
+        match self {
+            Alphabet::Dna | Alphabet::Rna => 4,
+            Alphabet::Protein => 20,
+        }
+    }
+
+    pub fn canonical(self) -> &'static [u8] {
+        &self.symbols()[..self.canonical_len()]
+    }
+
+    pub fn is_nucleotide(self) -> bool {
+        self != Alphabet::Protein
+    }
+
+    /// Digital code of a residue (case-insensitive).
+    pub fn encode(self, residue: u8) -> Option<u8> {
+        let upper = residue.to_ascii_uppercase();
+        self.symbols()
+            .iter()
+            .position(|&s| s == upper)
+            .map(|idx| idx as u8)
+    }
+
+    pub fn decode(self, code: u8) -> Option<u8> {
+        self.symbols().get(code as usize).copied()
+    }
+
+    pub fn is_valid(self, residue: u8) -> bool {
+        self.encode(residue).is_some()
+    }
+
+    pub fn is_canonical(self, residue: u8) -> bool {
+        self.encode(residue)
+            .is_some_and(|code| (code as usize) < self.canonical_len())
+    }
+
+    /// Best guess of the alphabet of a raw sequence: nucleotide if at least
+    /// 90% of the letters are A, C, G, T/U or N, protein otherwise.
+    pub fn guess(sequence: &str) -> Alphabet {
+        let mut letters = 0usize;
+        let mut nucleotides = 0usize;
+        let mut has_u = false;
+        let mut has_t = false;
+        for b in sequence.bytes().filter(|b| b.is_ascii_alphabetic()) {
+            letters += 1;
+            match b.to_ascii_uppercase() {
+                b'A' | b'C' | b'G' | b'N' => nucleotides += 1,
+                b'T' => {
+                    nucleotides += 1;
+                    has_t = true;
+                }
+                b'U' => {
+                    nucleotides += 1;
+                    has_u = true;
+                }
+                _ => {}
+            }
+        }
+        if letters == 0 || nucleotides * 10 < letters * 9 {
+            Alphabet::Protein
+        } else if has_u && !has_t {
+            Alphabet::Rna
+        } else {
+            Alphabet::Dna
+        }
+    }
+}
+
+impl fmt::Display for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A residue that does not belong to the expected alphabet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidResidue {
+    pub alphabet: Alphabet,
+    /// 0-based position in the input text (whitespace included).
+    pub position: usize,
+    pub residue: char,
+}
+
+impl fmt::Display for InvalidResidue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid {} residue '{}' at position {}",
+            self.alphabet,
+            self.residue,
+            self.position + 1
+        )
+    }
+}
+
+impl std::error::Error for InvalidResidue {}
+
+/// A sequence whose residues have been checked against an alphabet.
+///
+/// Residues are stored upper case with whitespace removed; the sequence
+/// dereferences to `str` so it can be passed wherever raw text is accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Sequence {
+    alphabet: Alphabet,
+    residues: String,
+}
+
+impl Sequence {
+    pub fn new(alphabet: Alphabet, text: &str) -> Result<Sequence, InvalidResidue> {
+        let mut residues = String::with_capacity(text.len());
+        for (position, ch) in text.char_indices() {
+            if ch.is_whitespace() {
+                continue;
+            }
+            if !ch.is_ascii() || !alphabet.is_valid(ch as u8) {
+                return Err(InvalidResidue {
+                    alphabet,
+                    position,
+                    residue: ch,
+                });
+            }
+            residues.push(ch.to_ascii_uppercase());
+        }
+        Ok(Sequence { alphabet, residues })
+    }
+
+    /// Build a sequence with the alphabet given by [`Alphabet::guess`].
+    pub fn guess(text: &str) -> Result<Sequence, InvalidResidue> {
+        Sequence::new(Alphabet::guess(text), text)
+    }
+
+    pub fn alphabet(&self) -> Alphabet {
+        self.alphabet
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.residues
+    }
+
+    /// Digital codes of the residues, see [`Alphabet::encode`].
+    pub fn encode(&self) -> Vec<u8> {
+        self.residues
+            .bytes()
+            .map(|b| self.alphabet.encode(b).unwrap_or(u8::MAX))
+            .collect()
+    }
+
+    pub fn decode(alphabet: Alphabet, codes: &[u8]) -> Result<Sequence, String> {
+        let residues = codes
+            .iter()
+            .map(|&code| {
+                alphabet
+                    .decode(code)
+                    .map(char::from)
+                    .ok_or_else(|| format!("Invalid {} code {}", alphabet, code))
+            })
+            .collect::<Result<String, String>>()?;
+        Ok(Sequence { alphabet, residues })
+    }
+
+    /// Number of residues that are IUPAC ambiguity codes.
+    pub fn ambiguous_count(&self) -> usize {
+        self.residues
+            .bytes()
+            .filter(|&b| !self.alphabet.is_canonical(b))
+            .count()
+    }
+
+    pub fn into_string(self) -> String {
+        self.residues
+    }
+}
+
+impl Deref for Sequence {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.residues
+    }
+}
+
+impl AsRef<str> for Sequence {
+    fn as_ref(&self) -> &str {
+        &self.residues
+    }
+}
+
+/// Bitmask of the canonical bases a nucleotide symbol stands for, in the
+/// order A, C, G, T (U maps to T). Gaps map to an empty mask.
+pub(crate) fn nucleotide_mask(base: u8) -> Option<u8> {
+    match base {
+        b'A' | b'a' => Some(0b0001),
+        b'C' | b'c' => Some(0b0010),
+        b'G' | b'g' => Some(0b0100),
+        b'T' | b't' | b'U' | b'u' => Some(0b1000),
+        b'R' | b'r' => Some(0b0101),
+        b'Y' | b'y' => Some(0b1010),
+        b'S' | b's' => Some(0b0110),
+        b'W' | b'w' => Some(0b1001),
+        b'K' | b'k' => Some(0b1100),
+        b'M' | b'm' => Some(0b0011),
+        b'B' | b'b' => Some(0b1110),
+        b'D' | b'd' => Some(0b1101),
+        b'H' | b'h' => Some(0b1011),
+        b'V' | b'v' => Some(0b0111),
+        b'N' | b'n' | b'X' | b'x' => Some(0b1111),
+        b'-' => Some(0),
+        _ => None,
+    }
+}
+
+/// The IUPAC symbol standing for exactly the bases of a
+/// [`nucleotide_mask`]; the empty mask gives a gap.
+pub(crate) fn mask_to_nucleotide(mask: u8) -> u8 {
+    b"-ACMGRSVTWYHKDBN"[(mask & 0b1111) as usize]
+}
+
+/// Index of a canonical base in the order A, C, G, T (U maps to T).
+pub(crate) fn canonical_nucleotide_index(base: u8) -> Option<usize> {
+    match base {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' | b'U' | b'u' => Some(3),
+        _ => None,
+    }
+}
+
+/// Index of an amino acid in the row order of the NCBI substitution matrices
+/// (ARNDCQEGHILKMFPSTWYV).
+pub(crate) fn substitution_matrix_index(b: u8) -> Option<usize> {
+    match b.to_ascii_uppercase() {
+        b'A' => Some(0),
+        b'R' => Some(1),
+        b'N' => Some(2),
+        b'D' => Some(3),
+        b'C' => Some(4),
+        b'Q' => Some(5),
+        b'E' => Some(6),
+        b'G' => Some(7),
+        b'H' => Some(8),
+        b'I' => Some(9),
+        b'L' => Some(10),
+        b'K' => Some(11),
+        b'M' => Some(12),
+        b'F' => Some(13),
+        b'P' => Some(14),
+        b'S' => Some(15),
+        b'T' => Some(16),
+        b'W' => Some(17),
+        b'Y' => Some(18),
+        b'V' => Some(19),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        for alphabet in [Alphabet::Dna, Alphabet::Rna, Alphabet::Protein] {
+            for (code, &symbol) in alphabet.symbols().iter().enumerate() {
+                assert_eq!(alphabet.encode(symbol), Some(code as u8));
+                assert_eq!(alphabet.encode(symbol.to_ascii_lowercase()), Some(code as u8));
+                assert_eq!(alphabet.decode(code as u8), Some(symbol));
+            }
+        }
+        assert_eq!(Alphabet::Dna.encode(b'U'), None);
+        assert_eq!(Alphabet::Rna.encode(b'T'), None);
+        assert!(Alphabet::Protein.is_canonical(b'w'));
+        assert!(!Alphabet::Protein.is_canonical(b'X'));
+    }
+
+    #[test]
+    fn sequence_validation() {
+        let seq = Sequence::new(Alphabet::Dna, "acgt\nNNRY").unwrap();
+        assert_eq!(seq.as_str(), "ACGTNNRY");
+        assert_eq!(seq.ambiguous_count(), 4);
+        assert_eq!(seq.encode(), vec![0, 1, 2, 3, 14, 14, 4, 5]);
+        assert_eq!(
+            Sequence::decode(Alphabet::Dna, &seq.encode()).unwrap(),
+            seq
+        );
+
+        let err = Sequence::new(Alphabet::Dna, "ACGU").unwrap_err();
+        assert_eq!(err.position, 3);
+        assert_eq!(err.residue, 'U');
+        assert!(Sequence::new(Alphabet::Protein, "MK-L").is_err());
+    }
+
+    #[test]
+    fn nucleotide_masks_roundtrip() {
+        for &symbol in DNA_SYMBOLS.iter().chain(b"-") {
+            assert_eq!(mask_to_nucleotide(nucleotide_mask(symbol).unwrap()), symbol);
+        }
+    }
+
+    #[test]
+    fn guesses_alphabet() {
+        assert_eq!(Alphabet::guess("ACGTTGCAN"), Alphabet::Dna);
+        assert_eq!(Alphabet::guess("ACGUUGCA"), Alphabet::Rna);
+        assert_eq!(Alphabet::guess("MKTAYIAKQRQISFVKSHFSRQ"), Alphabet::Protein);
+        assert_eq!(Alphabet::from_name("Amino").unwrap(), Alphabet::Protein);
+        assert!(Alphabet::from_name("klingon").is_err());
+    }
+}
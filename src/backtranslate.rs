@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+
+use crate::alphabet::{mask_to_nucleotide, nucleotide_mask};
+use crate::codon_usage::{codon_at, CodonUsage};
+use crate::fna2faa::CodonEncoder;
+use crate::rng::Lcg;
+
+/// How a codon is chosen for each amino acid.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackTranslationMode {
+    /// The synonymous codon with the highest weight in the codon usage table.
+    MostFrequent,
+    /// A synonymous codon drawn at random in proportion to its weight in the
+    /// codon usage table (uniformly without a table).
+    WeightedRandom,
+    /// The IUPAC consensus of all synonymous codons, e.g. `GCN` for alanine.
+    /// The consensus may also match codons of other amino acids (`YTN` for
+    /// leucine includes the phenylalanine codons `TTY`).
+    #[default]
+    Degenerate,
+}
+
+impl BackTranslationMode {
+    pub fn from_name(name: &str) -> Result<BackTranslationMode, String> {
+        match name.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "most_frequent" => Ok(BackTranslationMode::MostFrequent),
+            "weighted_random" | "random" => Ok(BackTranslationMode::WeightedRandom),
+            "degenerate" | "iupac" => Ok(BackTranslationMode::Degenerate),
+            _ => Err(format!(
+                "Unknown back-translation mode '{}' (expected most_frequent, weighted_random \
+                 or degenerate)",
+                name
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct BackTranslationOptions {
+    /// NCBI translation table ID.
+    pub genetic_code: u8,
+    pub mode: BackTranslationMode,
+    /// Seed of [`BackTranslationMode::WeightedRandom`]; the same seed gives
+    /// the same sequence.
+    pub seed: u64,
+}
+
+impl Default for BackTranslationOptions {
+    fn default() -> Self {
+        BackTranslationOptions {
+            genetic_code: 1,
+            mode: BackTranslationMode::Degenerate,
+            seed: 0,
+        }
+    }
+}
+
+/// The amino acids that a protein residue letter may stand for.
+fn residue_members(residue: &u8) -> &[u8] {
+    match residue {
+        b'B' => b"DN",
+        b'Z' => b"EQ",
+        b'J' => b"IL",
+        b'X' => b"ACDEFGHIKLMNPQRSTVWY",
+        _ => std::slice::from_ref(residue),
+    }
+}
+
+/// Back-translate a protein into DNA. Whitespace is ignored, `*` stands for
+/// a stop codon, `-` for a gap (written as `---`), and `B`, `Z`, `J` and `X`
+/// for any of the amino acids they stand for.
+/// [`BackTranslationMode::MostFrequent`] requires a codon usage table.
+pub fn back_translate(
+    protein: &str,
+    options: &BackTranslationOptions,
+    usage: Option<&CodonUsage>,
+) -> Result<String, String> {
+    let encoder = CodonEncoder::for_table(options.genetic_code)?;
+    if options.mode == BackTranslationMode::MostFrequent && usage.is_none() {
+        return Err("The most frequent codon mode requires a codon usage table".to_string());
+    }
+    // The codons of each amino acid, by codon index.
+    let mut synonyms: [Vec<usize>; 128] = std::array::from_fn(|_| Vec::new());
+    for index in 0..64 {
+        let (aa, _) = encoder.translate_triplet(&codon_at(index));
+        synonyms[aa as usize].push(index);
+    }
+
+    let mut rng = Lcg(options.seed);
+    let mut dna = String::with_capacity(3 * protein.len());
+    for (position, ch) in protein.chars().enumerate() {
+        if ch.is_whitespace() {
+            continue;
+        }
+        if ch == '-' {
+            dna.push_str("---");
+            continue;
+        }
+        if !(ch.is_ascii_alphabetic() || ch == '*') {
+            return Err(format!("Invalid character '{}' at position {}", ch, position + 1));
+        }
+        let residue = ch.to_ascii_uppercase() as u8;
+        let codons: Vec<usize> = residue_members(&residue)
+            .iter()
+            .flat_map(|&aa| synonyms[aa as usize].iter().copied())
+            .collect();
+        if codons.is_empty() {
+            return Err(format!(
+                "No codon codes for '{}' (position {}) in genetic code {}",
+                ch,
+                position + 1,
+                options.genetic_code
+            ));
+        }
+        let weights: Vec<f64> = match usage {
+            Some(usage) => codons.iter().map(|&index| usage.weight_at(index)).collect(),
+            None => vec![1.0; codons.len()],
+        };
+        let total: f64 = weights.iter().sum();
+        if options.mode != BackTranslationMode::Degenerate && total <= 0.0 {
+            return Err(format!(
+                "The codon usage table has no codons for '{}' (position {})",
+                ch,
+                position + 1
+            ));
+        }
+        let chosen = match options.mode {
+            BackTranslationMode::Degenerate => {
+                let mut masks = [0u8; 3];
+                for &index in &codons {
+                    for (mask, base) in masks.iter_mut().zip(codon_at(index)) {
+                        *mask |= nucleotide_mask(base).unwrap_or(0);
+                    }
+                }
+                masks.map(mask_to_nucleotide)
+            }
+            BackTranslationMode::MostFrequent => {
+                let best = (0..codons.len())
+                    .rev()
+                    .max_by(|&a, &b| weights[a].total_cmp(&weights[b]))
+                    .unwrap();
+                codon_at(codons[best])
+            }
+            BackTranslationMode::WeightedRandom => {
+                let mut target = rng.next_f64() * total;
+                let mut chosen = codons[codons.len() - 1];
+                for (&index, &weight) in codons.iter().zip(&weights) {
+                    if target < weight {
+                        chosen = index;
+                        break;
+                    }
+                    target -= weight;
+                }
+                codon_at(chosen)
+            }
+        };
+        dna.extend(chosen.iter().map(|&base| base as char));
+    }
+    Ok(dna)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translation::translate_frame;
+
+    fn options(mode: BackTranslationMode, genetic_code: u8) -> BackTranslationOptions {
+        BackTranslationOptions {
+            genetic_code,
+            mode,
+            seed: 42,
+        }
+    }
+
+    #[test]
+    fn degenerate_consensus() {
+        let dna = back_translate("MAL*W", &options(BackTranslationMode::Degenerate, 1), None);
+        assert_eq!(dna.unwrap(), "ATGGCNYTNTRRTGG");
+        // Table 2 (vertebrate mitochondrial): AGA/AGG are stops, TGA is Trp.
+        let dna = back_translate("R*W", &options(BackTranslationMode::Degenerate, 2), None);
+        assert_eq!(dna.unwrap(), "CGNWRRTGR");
+        let dna = back_translate("b-x", &options(BackTranslationMode::Degenerate, 1), None);
+        assert_eq!(dna.unwrap(), "RAY---NNN");
+    }
+
+    #[test]
+    fn codon_usage_modes() {
+        let usage = CodonUsage::parse("GCT 1 GCC 5 GCA 2 GCG 0 TTA 3 CTG 9 ATG 1").unwrap();
+        let most = options(BackTranslationMode::MostFrequent, 1);
+        assert_eq!(back_translate("MAL", &most, Some(&usage)).unwrap(), "ATGGCCCTG");
+        assert!(back_translate("MAL", &most, None).is_err());
+        assert!(back_translate("MW", &most, Some(&usage)).is_err());
+
+        let random = options(BackTranslationMode::WeightedRandom, 1);
+        let protein = "MA".repeat(500);
+        let dna = back_translate(&protein, &random, Some(&usage)).unwrap();
+        assert_eq!(dna, back_translate(&protein, &random, Some(&usage)).unwrap());
+        assert_eq!(translate_frame(&dna, 1, false, 1).unwrap(), protein);
+        let codons: Vec<&str> = (3..dna.len()).step_by(6).map(|i| &dna[i..i + 3]).collect();
+        assert!(!codons.contains(&"GCG"));
+        let gcc = codons.iter().filter(|&&codon| codon == "GCC").count();
+        assert!((550..700).contains(&(gcc * 1000 / codons.len())));
+
+        let uniform = back_translate(&protein, &random, None).unwrap();
+        assert_eq!(translate_frame(&uniform, 1, false, 1).unwrap(), protein);
+    }
+
+    #[test]
+    fn rejects_invalid_residues() {
+        let degenerate = options(BackTranslationMode::Degenerate, 1);
+        assert!(back_translate("MA1", &degenerate, None).unwrap_err().contains("position 3"));
+        assert!(back_translate("MU", &degenerate, None).is_err());
+        assert!(back_translate("M", &options(BackTranslationMode::Degenerate, 7), None).is_err());
+        assert_eq!(
+            BackTranslationMode::from_name("Most-Frequent").unwrap(),
+            BackTranslationMode::MostFrequent
+        );
+    }
+}
@@ -0,0 +1,591 @@
+use serde::Serialize;
+
+use crate::alphabet::canonical_nucleotide_index;
+use crate::fasta::FastaRecord;
+use crate::fna2faa::{codon_index, CodonEncoder};
+use crate::translation::sanitize_sequence;
+
+/// Index of a codon written with canonical bases (U read as T), in the order
+/// of [`codon_index`].
+pub(crate) fn parse_codon(codon: &[u8]) -> Option<usize> {
+    match codon {
+        &[first, second, third] => Some(codon_index(
+            canonical_nucleotide_index(first)?,
+            canonical_nucleotide_index(second)?,
+            canonical_nucleotide_index(third)?,
+        )),
+        _ => None,
+    }
+}
+
+/// The codon at a [`codon_index`], in upper-case DNA.
+pub(crate) fn codon_at(index: usize) -> [u8; 3] {
+    let base = |shift: usize| b"ACGT"[(index >> shift) & 3];
+    [base(4), base(2), base(0)]
+}
+
+/// A codon usage table: a non-negative weight for each of the 64 codons,
+/// such as a count or a frequency per thousand codons. Only the ratios
+/// between weights matter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodonUsage {
+    /// Indexed by [`codon_index`].
+    weights: [f64; 64],
+}
+
+impl CodonUsage {
+    /// Build a table from `(codon, weight)` pairs; codons that are not listed
+    /// get weight 0.
+    pub fn from_weights<I, S>(weights: I) -> Result<CodonUsage, String>
+    where
+        I: IntoIterator<Item = (S, f64)>,
+        S: AsRef<str>,
+    {
+        let mut table = [None; 64];
+        for (codon, weight) in weights {
+            let codon = codon.as_ref().trim();
+            let index = parse_codon(codon.as_bytes())
+                .ok_or_else(|| format!("Invalid codon '{}' in codon usage table", codon))?;
+            if !(weight.is_finite() && weight >= 0.0) {
+                return Err(format!("Invalid usage {} for codon {}", weight, codon));
+            }
+            if table[index].replace(weight).is_some() {
+                return Err(format!("Codon {} is listed twice in codon usage table", codon));
+            }
+        }
+        let weights = table.map(|weight| weight.unwrap_or(0.0));
+        if weights.iter().all(|&weight| weight == 0.0) {
+            return Err("Codon usage table is empty".to_string());
+        }
+        Ok(CodonUsage { weights })
+    }
+
+    /// Parse a codon usage table as text: each codon (`GCU`, `gct`, ...) is
+    /// followed by its usage, the first number after it. This reads the
+    /// tables of the Kazusa codon usage database (`UUU 17.6(714298)`) as well
+    /// as GCG `.cod` files (`Gly GGG 17.00 12.13 0.24`).
+    pub fn parse(text: &str) -> Result<CodonUsage, String> {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let mut weights = Vec::new();
+        for (idx, token) in tokens.iter().enumerate() {
+            if parse_codon(token.as_bytes()).is_none() {
+                continue;
+            }
+            let weight = tokens[idx + 1..]
+                .iter()
+                .take_while(|next| parse_codon(next.as_bytes()).is_none())
+                .find_map(|next| next.split('(').next()?.parse::<f64>().ok())
+                .ok_or_else(|| format!("Missing usage for codon {} in codon usage table", token))?;
+            weights.push((*token, weight));
+        }
+        CodonUsage::from_weights(weights)
+    }
+
+    /// Weight of a codon; 0 for codons with ambiguous bases.
+    pub fn weight(&self, codon: &[u8]) -> f64 {
+        parse_codon(codon).map_or(0.0, |index| self.weights[index])
+    }
+
+    pub(crate) fn weight_at(&self, index: usize) -> f64 {
+        self.weights[index]
+    }
+
+    /// Count the codons of a coding sequence, read in frame 1. Codons with
+    /// ambiguous bases and a trailing incomplete codon are not counted.
+    pub fn count_codons(sequence: &str) -> Result<CodonUsage, String> {
+        let sequence = sanitize_sequence(sequence)?;
+        let mut weights = [0.0; 64];
+        for codon in sequence.as_bytes().chunks_exact(3) {
+            if let Some(index) = parse_codon(codon) {
+                weights[index] += 1.0;
+            }
+        }
+        Ok(CodonUsage { weights })
+    }
+
+    /// Sum of all weights (the number of codons of a counted table).
+    pub fn total(&self) -> f64 {
+        self.weights.iter().sum()
+    }
+
+    /// Add the weights of `other`, e.g. to pool the counts of several genes.
+    pub fn add(&mut self, other: &CodonUsage) {
+        for (weight, other) in self.weights.iter_mut().zip(other.weights) {
+            *weight += other;
+        }
+    }
+
+    /// Usage statistics of every codon, in the order of the NCBI tables
+    /// (TTT, TTC, TTA, TTG, TCT, ...).
+    pub fn stats(&self, encoder: &CodonEncoder) -> Vec<CodonStat> {
+        let amino_acids = codon_amino_acids(encoder);
+        let total = self.total();
+        let mut family_totals = [0.0; 128];
+        let mut family_sizes = [0usize; 128];
+        for (index, &aa) in amino_acids.iter().enumerate() {
+            family_totals[aa as usize] += self.weights[index];
+            family_sizes[aa as usize] += 1;
+        }
+        let order = b"TCAG";
+        let mut stats = Vec::with_capacity(64);
+        for &first in order {
+            for &second in order {
+                for &third in order {
+                    let codon = [first, second, third];
+                    let index = parse_codon(&codon).unwrap();
+                    let aa = amino_acids[index] as usize;
+                    let weight = self.weights[index];
+                    let family = family_totals[aa];
+                    stats.push(CodonStat {
+                        codon: String::from_utf8_lossy(&codon).into_owned(),
+                        amino_acid: aa as u8 as char,
+                        count: weight,
+                        per_thousand: ratio(1000.0 * weight, total),
+                        fraction: ratio(weight, family),
+                        rscu: ratio(weight * family_sizes[aa] as f64, family),
+                    });
+                }
+            }
+        }
+        stats
+    }
+
+    /// Effective number of codons (Wright 1990), from 20 (one codon per
+    /// amino acid) to the number of sense codons (61 in the standard code,
+    /// all synonymous codons used equally). Amino acids are grouped by
+    /// their number of codons `k`; a group with no amino acid seen at least
+    /// twice counts as unbiased. `None` if no amino acid is seen twice.
+    pub fn effective_number_of_codons(&self, encoder: &CodonEncoder) -> Option<f64> {
+        let amino_acids = codon_amino_acids(encoder);
+        // Homozygosity sum and group size for each degeneracy class.
+        let mut classes: Vec<(usize, f64, usize, usize)> = Vec::new();
+        let mut observed = false;
+        for aa in (b'A'..=b'Z').filter(|aa| amino_acids.contains(aa)) {
+            let counts: Vec<f64> = (0..64)
+                .filter(|&index| amino_acids[index] == aa)
+                .map(|index| self.weights[index])
+                .collect();
+            let k = counts.len();
+            let n: f64 = counts.iter().sum();
+            let class = match classes.iter().position(|class| class.0 == k) {
+                Some(position) => position,
+                None => {
+                    classes.push((k, 0.0, 0, 0));
+                    classes.len() - 1
+                }
+            };
+            classes[class].2 += 1;
+            if k > 1 && n >= 2.0 {
+                let homozygosity: f64 = counts.iter().map(|count| (count / n).powi(2)).sum();
+                classes[class].1 += (n * homozygosity - 1.0) / (n - 1.0);
+                classes[class].3 += 1;
+                observed = true;
+            }
+        }
+        if !observed {
+            return None;
+        }
+        Some(
+            classes
+                .iter()
+                .map(|&(k, f_sum, size, seen)| {
+                    let unbiased = 1.0 / k as f64;
+                    let f = if seen > 0 { f_sum / seen as f64 } else { unbiased };
+                    size as f64 / f.max(unbiased)
+                })
+                .sum(),
+        )
+    }
+
+    /// GC content of third codon positions.
+    pub fn gc3(&self) -> Option<f64> {
+        let gc: f64 = (0..64)
+            .filter(|&index| matches!(codon_at(index)[2], b'G' | b'C'))
+            .map(|index| self.weights[index])
+            .sum();
+        let total = self.total();
+        (total > 0.0).then(|| gc / total)
+    }
+
+    /// The table in GCG codon frequency (`.cod`) format.
+    pub fn to_gcg(&self, encoder: &CodonEncoder) -> String {
+        let stats = self.stats(encoder);
+        let mut text = String::from("AmAcid  Codon      Number    /1000     Fraction   ..\n\n");
+        let order = b"GATC";
+        for &first in order {
+            for &second in order {
+                for &third in order {
+                    let codon = [first, second, third];
+                    let stat = stats.iter().find(|stat| stat.codon.as_bytes() == codon).unwrap();
+                    text.push_str(&format!(
+                        "{:<8}{:<8}{:>10.2}{:>10.2}{:>11.2}\n",
+                        three_letter_code(stat.amino_acid as u8),
+                        stat.codon,
+                        stat.count,
+                        stat.per_thousand,
+                        stat.fraction
+                    ));
+                }
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// The table in the format of the CUTG / Kazusa codon usage database:
+    /// each codon (as RNA) with its frequency per thousand and its count.
+    pub fn to_cutg(&self) -> String {
+        let total = self.total();
+        let order = b"UCAG";
+        let mut blocks = Vec::new();
+        for &first in order {
+            let mut block = String::new();
+            for &third in order {
+                let row: Vec<String> = order
+                    .iter()
+                    .map(|&second| {
+                        let codon = [first, second, third];
+                        let weight = self.weight(&codon);
+                        format!(
+                            "{} {:>4.1}({:>6.0})",
+                            String::from_utf8_lossy(&codon),
+                            ratio(1000.0 * weight, total),
+                            weight
+                        )
+                    })
+                    .collect();
+                block.push_str(&row.join("  "));
+                block.push('\n');
+            }
+            blocks.push(block);
+        }
+        blocks.join("\n")
+    }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+/// Amino acid (or `*`) of every codon index under a genetic code.
+fn codon_amino_acids(encoder: &CodonEncoder) -> [u8; 64] {
+    std::array::from_fn(|index| encoder.translate_triplet(&codon_at(index)).0)
+}
+
+fn three_letter_code(aa: u8) -> &'static str {
+    match aa {
+        b'A' => "Ala",
+        b'R' => "Arg",
+        b'N' => "Asn",
+        b'D' => "Asp",
+        b'C' => "Cys",
+        b'Q' => "Gln",
+        b'E' => "Glu",
+        b'G' => "Gly",
+        b'H' => "His",
+        b'I' => "Ile",
+        b'L' => "Leu",
+        b'K' => "Lys",
+        b'M' => "Met",
+        b'F' => "Phe",
+        b'P' => "Pro",
+        b'S' => "Ser",
+        b'T' => "Thr",
+        b'W' => "Trp",
+        b'Y' => "Tyr",
+        b'V' => "Val",
+        b'*' => "End",
+        _ => "Xaa",
+    }
+}
+
+/// Usage of one codon in a [`CodonUsage`] table.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CodonStat {
+    pub codon: String,
+    /// One-letter code; `*` for stop codons.
+    pub amino_acid: char,
+    pub count: f64,
+    pub per_thousand: f64,
+    /// Share of the codon among the codons of its amino acid.
+    pub fraction: f64,
+    /// Relative synonymous codon usage: the codon's usage over the mean
+    /// usage of the codons of its amino acid (1 when used evenly).
+    pub rscu: f64,
+}
+
+/// Relative adaptiveness of each codon in a reference set, such as highly
+/// expressed genes, for the Codon Adaptation Index (Sharp and Li 1987).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaiReference {
+    /// `None` for codons that do not count: stop codons, codons of amino
+    /// acids with a single codon, and of amino acids absent from the
+    /// reference.
+    weights: [Option<f64>; 64],
+}
+
+impl CaiReference {
+    /// Codons never used in the reference get relative adaptiveness 0.01
+    /// rather than 0, so that they lower the index without zeroing it.
+    pub fn new(reference: &CodonUsage, encoder: &CodonEncoder) -> CaiReference {
+        let amino_acids = codon_amino_acids(encoder);
+        let weights = std::array::from_fn(|index| {
+            let aa = amino_acids[index];
+            let family: Vec<usize> = (0..64).filter(|&other| amino_acids[other] == aa).collect();
+            let best = family
+                .iter()
+                .map(|&other| reference.weights[other])
+                .fold(0.0, f64::max);
+            if aa == b'*' || family.len() < 2 || best <= 0.0 {
+                return None;
+            }
+            Some((reference.weights[index] / best).max(0.01))
+        });
+        CaiReference { weights }
+    }
+
+    /// Codon Adaptation Index of a gene: the geometric mean of the relative
+    /// adaptiveness of its codons. `None` if no codon counts.
+    pub fn cai(&self, usage: &CodonUsage) -> Option<f64> {
+        let mut log_sum = 0.0;
+        let mut codons = 0.0;
+        for (weight, count) in self.weights.iter().zip(usage.weights) {
+            if let Some(weight) = weight {
+                log_sum += count * weight.ln();
+                codons += count;
+            }
+        }
+        (codons > 0.0).then(|| (log_sum / codons).exp())
+    }
+}
+
+/// Codon usage of one sequence (or of all sequences pooled).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CodonUsageSummary {
+    pub id: String,
+    pub description: String,
+    /// Number of codons counted.
+    pub codons: usize,
+    pub gc3: Option<f64>,
+    /// Effective number of codons.
+    pub enc: Option<f64>,
+    /// Codon Adaptation Index; only with a reference set.
+    pub cai: Option<f64>,
+    pub stats: Vec<CodonStat>,
+}
+
+impl CodonUsageSummary {
+    fn new(
+        id: String,
+        description: String,
+        usage: &CodonUsage,
+        encoder: &CodonEncoder,
+        reference: Option<&CaiReference>,
+    ) -> Self {
+        CodonUsageSummary {
+            id,
+            description,
+            codons: usage.total().round() as usize,
+            gc3: usage.gc3(),
+            enc: usage.effective_number_of_codons(encoder),
+            cai: reference.and_then(|reference| reference.cai(usage)),
+            stats: usage.stats(encoder),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CodonUsageReport {
+    pub records: Vec<CodonUsageSummary>,
+    /// All records pooled, with `id` `"total"`.
+    pub total: CodonUsageSummary,
+    /// The pooled counts in GCG `.cod` format.
+    pub gcg: String,
+    /// The pooled counts in CUTG / Kazusa format.
+    pub cutg: String,
+}
+
+/// Codon usage of each coding sequence of `records` and of all of them
+/// together. With a `reference` table, the CAI of each record is computed
+/// against it.
+pub fn codon_usage_report(
+    records: &[FastaRecord],
+    genetic_code: u8,
+    reference: Option<&CodonUsage>,
+) -> Result<CodonUsageReport, String> {
+    let encoder = CodonEncoder::for_table(genetic_code)?;
+    let reference = reference.map(|reference| CaiReference::new(reference, &encoder));
+    let mut total = CodonUsage {
+        weights: [0.0; 64],
+    };
+    let mut summaries = Vec::with_capacity(records.len());
+    for record in records {
+        let usage = CodonUsage::count_codons(&record.sequence)
+            .map_err(|err| format!("line {}: {}", record.line, err))?;
+        total.add(&usage);
+        summaries.push(CodonUsageSummary::new(
+            record.id.clone(),
+            record.description.clone(),
+            &usage,
+            &encoder,
+            reference.as_ref(),
+        ));
+    }
+    Ok(CodonUsageReport {
+        records: summaries,
+        total: CodonUsageSummary::new(
+            "total".to_string(),
+            String::new(),
+            &total,
+            &encoder,
+            reference.as_ref(),
+        ),
+        gcg: total.to_gcg(&encoder),
+        cutg: total.to_cutg(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codon_indices_roundtrip() {
+        for index in 0..64 {
+            assert_eq!(parse_codon(&codon_at(index)), Some(index));
+        }
+        assert_eq!(parse_codon(b"ugc"), parse_codon(b"TGC"));
+        assert_eq!(parse_codon(b"TGN"), None);
+    }
+
+    #[test]
+    fn parses_usage_tables() {
+        let kazusa = "UUU 17.6(714298)  UCU 15.2(618711)\nUUC 20.3( 824692)  UCC 17.7(  718892)";
+        let usage = CodonUsage::parse(kazusa).unwrap();
+        assert_eq!(usage.weight(b"TTT"), 17.6);
+        assert_eq!(usage.weight(b"TCC"), 17.7);
+        assert_eq!(usage.weight(b"AAA"), 0.0);
+
+        let gcg = concat!(
+            "AmAcid  Codon     Number    /1000     Fraction   ..\n\n",
+            "Gly     GGG     17.00     12.13      0.24\n",
+            "Gly     GGA      4.00      2.85      0.06\n",
+        );
+        let usage = CodonUsage::parse(gcg).unwrap();
+        assert_eq!(usage.weight(b"GGG"), 17.0);
+        assert_eq!(usage.weight(b"GGA"), 4.0);
+
+        assert!(CodonUsage::parse("no codons here").is_err());
+        assert!(CodonUsage::parse("GGG 1 GGG 2").is_err());
+        assert!(CodonUsage::parse("GGG AAA 2").is_err());
+        assert!(CodonUsage::from_weights([("GGN", 1.0)]).is_err());
+        assert!(CodonUsage::from_weights([("GGG", -1.0)]).is_err());
+    }
+
+    /// The codons of each amino acid of the standard code, in codon order.
+    fn synonymous_codons() -> Vec<Vec<[u8; 3]>> {
+        let encoder = CodonEncoder::mk_encoder();
+        let mut families: Vec<(u8, Vec<[u8; 3]>)> = Vec::new();
+        for index in 0..64 {
+            let codon = codon_at(index);
+            let aa = encoder.translate_triplet(&codon).0;
+            match families.iter_mut().find(|family| family.0 == aa) {
+                Some(family) => family.1.push(codon),
+                None => families.push((aa, vec![codon])),
+            }
+        }
+        families
+            .into_iter()
+            .filter(|family| family.0 != b'*')
+            .map(|family| family.1)
+            .collect()
+    }
+
+    #[test]
+    fn counts_codons() {
+        let encoder = CodonEncoder::mk_encoder();
+        let usage = CodonUsage::count_codons("ATGAAA aagNNNTA").unwrap();
+        assert_eq!(usage.total(), 3.0);
+        let stats = usage.stats(&encoder);
+        assert_eq!(stats.len(), 64);
+        assert_eq!(stats[0].codon, "TTT");
+        let aaa = stats.iter().find(|stat| stat.codon == "AAA").unwrap();
+        assert_eq!(aaa.amino_acid, 'K');
+        assert_eq!(aaa.fraction, 0.5);
+        assert_eq!(aaa.rscu, 1.0);
+        assert!((aaa.per_thousand - 1000.0 / 3.0).abs() < 1e-9);
+        assert_eq!(usage.gc3(), Some(2.0 / 3.0));
+        assert!(CodonUsage::count_codons("ATG1").is_err());
+    }
+
+    #[test]
+    fn effective_number_of_codons() {
+        let encoder = CodonEncoder::mk_encoder();
+        let families = synonymous_codons();
+        let even: String = families
+            .iter()
+            .flatten()
+            .map(|codon| String::from_utf8_lossy(codon).repeat(2))
+            .collect();
+        let even = CodonUsage::count_codons(&even).unwrap();
+        assert!((even.effective_number_of_codons(&encoder).unwrap() - 61.0).abs() < 1e-9);
+
+        let biased: String = families
+            .iter()
+            .map(|family| String::from_utf8_lossy(&family[0]).repeat(3))
+            .collect();
+        let biased = CodonUsage::count_codons(&biased).unwrap();
+        assert!((biased.effective_number_of_codons(&encoder).unwrap() - 20.0).abs() < 1e-9);
+        let single = CodonUsage::count_codons("ATGAAA").unwrap();
+        assert_eq!(single.effective_number_of_codons(&encoder), None);
+    }
+
+    #[test]
+    fn codon_adaptation_index() {
+        let encoder = CodonEncoder::mk_encoder();
+        let reference = CodonUsage::parse("AAA 8 AAG 2 GAA 5 GAG 5 ATG 1").unwrap();
+        let reference = CaiReference::new(&reference, &encoder);
+        let optimal = CodonUsage::count_codons("ATGAAAGAAGAG").unwrap();
+        assert!((reference.cai(&optimal).unwrap() - 1.0).abs() < 1e-9);
+        let gene = CodonUsage::count_codons("AAAAAGTGG").unwrap();
+        assert!((reference.cai(&gene).unwrap() - 0.5).abs() < 1e-9);
+        let unscored = CodonUsage::count_codons("ATGTGGTTT").unwrap();
+        assert_eq!(reference.cai(&unscored), None);
+    }
+
+    #[test]
+    fn exports_tables() {
+        let encoder = CodonEncoder::mk_encoder();
+        let usage = CodonUsage::count_codons(&"ATGAAAAAGGGCTTTTAA".repeat(7)).unwrap();
+        let gcg = usage.to_gcg(&encoder);
+        assert!(gcg.starts_with("AmAcid  Codon      Number    /1000     Fraction   ..\n\n"));
+        assert!(gcg.contains("\nGly     GGC           7.00    166.67       1.00\n"));
+        assert_eq!(CodonUsage::parse(&gcg).unwrap(), usage);
+
+        let cutg = usage.to_cutg();
+        assert!(cutg.starts_with("UUU 166.7(     7)  UCU  0.0(     0)"));
+        assert_eq!(cutg.lines().count(), 19);
+        let parsed = CodonUsage::parse(&cutg).unwrap();
+        assert!((parsed.weight(b"AAA") - 166.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reports_records() {
+        let records = crate::fasta::parse_fasta(">a gene\nATGAAATAA\n>b\nATGAAGTAA\n").unwrap();
+        let reference = CodonUsage::parse("AAA 3 AAG 1").unwrap();
+        let report = codon_usage_report(&records, 11, Some(&reference)).unwrap();
+        assert_eq!(report.records.len(), 2);
+        assert_eq!(report.records[0].description, "gene");
+        assert_eq!(report.records[0].codons, 3);
+        assert_eq!(report.records[0].cai, Some(1.0));
+        assert_eq!(report.total.id, "total");
+        assert_eq!(report.total.codons, 6);
+        assert!(report.cutg.contains("AAG"));
+        let invalid = crate::fasta::parse_fasta(">a\nATG\n>b\nATJ\n").unwrap();
+        assert!(codon_usage_report(&invalid, 1, None).unwrap_err().starts_with("line 3:"));
+    }
+}
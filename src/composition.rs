@@ -0,0 +1,409 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::fasta::FastaRecord;
+use crate::translation::sanitize_sequence;
+
+/// Sliding windows of [`composition_report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CompositionOptions {
+    /// Window length in bases.
+    pub window: usize,
+    /// Distance between the starts of consecutive windows.
+    pub step: usize,
+}
+
+impl Default for CompositionOptions {
+    fn default() -> Self {
+        CompositionOptions {
+            window: 1000,
+            step: 100,
+        }
+    }
+}
+
+/// Base counts of a stretch of sanitized sequence.
+#[derive(Debug, Clone, Copy, Default)]
+struct BaseCounts {
+    a: usize,
+    c: usize,
+    g: usize,
+    t: usize,
+    n: usize,
+    /// IUPAC ambiguity codes other than N.
+    ambiguous: usize,
+}
+
+impl BaseCounts {
+    fn count(sequence: &[u8]) -> BaseCounts {
+        let mut counts = BaseCounts::default();
+        for &base in sequence {
+            counts.add(base);
+        }
+        counts
+    }
+
+    fn add(&mut self, base: u8) {
+        match base {
+            b'A' => self.a += 1,
+            b'C' => self.c += 1,
+            b'G' => self.g += 1,
+            b'T' => self.t += 1,
+            b'N' => self.n += 1,
+            _ => self.ambiguous += 1,
+        }
+    }
+
+    /// GC content of the unambiguous bases.
+    fn gc(&self) -> Option<f64> {
+        ratio(self.g + self.c, self.a + self.c + self.g + self.t)
+    }
+
+    /// `(G - C) / (G + C)`.
+    fn gc_skew(&self) -> Option<f64> {
+        skew(self.g, self.c)
+    }
+
+    /// `(A - T) / (A + T)`.
+    fn at_skew(&self) -> Option<f64> {
+        skew(self.a, self.t)
+    }
+}
+
+fn ratio(part: usize, total: usize) -> Option<f64> {
+    (total > 0).then(|| part as f64 / total as f64)
+}
+
+fn skew(first: usize, second: usize) -> Option<f64> {
+    ratio(first, first + second).map(|fraction| 2.0 * fraction - 1.0)
+}
+
+fn unambiguous_index(base: u8) -> Option<usize> {
+    match base {
+        b'A' => Some(0),
+        b'C' => Some(1),
+        b'G' => Some(2),
+        b'T' => Some(3),
+        _ => None,
+    }
+}
+
+/// Count of one dinucleotide and its relative abundance.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DinucleotideStat {
+    pub dinucleotide: String,
+    pub count: usize,
+    /// Observed over expected frequency, `f(XY) / (f(X) f(Y))` (Karlin's
+    /// rho); 1 when the two bases follow each other by chance.
+    pub rho: Option<f64>,
+}
+
+/// Composition of a whole sequence. Ratios are over the unambiguous bases
+/// and `None` when there are none to count.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CompositionStats {
+    pub length: usize,
+    pub a: usize,
+    pub c: usize,
+    pub g: usize,
+    pub t: usize,
+    pub n: usize,
+    /// IUPAC ambiguity codes other than N.
+    pub ambiguous: usize,
+    pub gc: Option<f64>,
+    pub gc_skew: Option<f64>,
+    pub at_skew: Option<f64>,
+    /// GC content of the first, second and third positions of the codons
+    /// read from the first base.
+    pub gc1: Option<f64>,
+    pub gc2: Option<f64>,
+    pub gc3: Option<f64>,
+    /// The 16 dinucleotides of unambiguous bases, AA, AC, ..., TT.
+    pub dinucleotides: Vec<DinucleotideStat>,
+}
+
+impl CompositionStats {
+    fn new(sequence: &[u8]) -> CompositionStats {
+        let counts = BaseCounts::count(sequence);
+        let mut positions = [BaseCounts::default(); 3];
+        for (index, &base) in sequence.iter().enumerate() {
+            positions[index % 3].add(base);
+        }
+        let mut pairs = [0usize; 16];
+        for pair in sequence.windows(2) {
+            if let (Some(first), Some(second)) =
+                (unambiguous_index(pair[0]), unambiguous_index(pair[1]))
+            {
+                pairs[4 * first + second] += 1;
+            }
+        }
+        let bases = [counts.a, counts.c, counts.g, counts.t];
+        let total_bases: usize = bases.iter().sum();
+        let total_pairs: usize = pairs.iter().sum();
+        let dinucleotides = (0..16)
+            .map(|index| {
+                let expected = bases[index / 4] as f64 * bases[index % 4] as f64;
+                let rho = (total_pairs > 0 && expected > 0.0).then(|| {
+                    pairs[index] as f64 / total_pairs as f64
+                        * (total_bases as f64 * total_bases as f64)
+                        / expected
+                });
+                DinucleotideStat {
+                    dinucleotide: [b"ACGT"[index / 4] as char, b"ACGT"[index % 4] as char]
+                        .iter()
+                        .collect(),
+                    count: pairs[index],
+                    rho,
+                }
+            })
+            .collect();
+        CompositionStats {
+            length: sequence.len(),
+            a: counts.a,
+            c: counts.c,
+            g: counts.g,
+            t: counts.t,
+            n: counts.n,
+            ambiguous: counts.ambiguous,
+            gc: counts.gc(),
+            gc_skew: counts.gc_skew(),
+            at_skew: counts.at_skew(),
+            gc1: positions[0].gc(),
+            gc2: positions[1].gc(),
+            gc3: positions[2].gc(),
+            dinucleotides,
+        }
+    }
+}
+
+/// Composition of one window, `start..end` (0-based, end-exclusive).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CompositionWindow {
+    pub start: usize,
+    pub end: usize,
+    pub gc: Option<f64>,
+    pub gc_skew: Option<f64>,
+    /// Sum of the GC skews of this and all previous windows; its minimum
+    /// and maximum point to the origin and terminus of replication of
+    /// bacterial chromosomes.
+    pub cumulative_gc_skew: f64,
+    pub at_skew: Option<f64>,
+    pub n: usize,
+    pub ambiguous: usize,
+}
+
+/// Windows of `options.window` bases every `options.step` bases; a sequence
+/// shorter than one window gets a single window over all of it.
+fn windows(sequence: &[u8], options: &CompositionOptions) -> Vec<CompositionWindow> {
+    let mut windows = Vec::new();
+    let mut cumulative_gc_skew = 0.0;
+    let mut start = 0;
+    while start < sequence.len() {
+        let end = (start + options.window).min(sequence.len());
+        if end - start < options.window && start > 0 {
+            break;
+        }
+        let counts = BaseCounts::count(&sequence[start..end]);
+        cumulative_gc_skew += counts.gc_skew().unwrap_or(0.0);
+        windows.push(CompositionWindow {
+            start,
+            end,
+            gc: counts.gc(),
+            gc_skew: counts.gc_skew(),
+            cumulative_gc_skew,
+            at_skew: counts.at_skew(),
+            n: counts.n,
+            ambiguous: counts.ambiguous,
+        });
+        start += options.step;
+    }
+    windows
+}
+
+/// Composition of one record: statistics of the whole sequence and of each
+/// sliding window.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CompositionProfile {
+    pub id: String,
+    pub description: String,
+    pub stats: CompositionStats,
+    pub windows: Vec<CompositionWindow>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CompositionReport {
+    pub records: Vec<CompositionProfile>,
+}
+
+fn format_ratio(value: Option<f64>) -> String {
+    value.map_or_else(|| "NA".to_string(), |value| format!("{:.4}", value))
+}
+
+impl CompositionReport {
+    /// One row of whole-sequence statistics per record, with a header line.
+    /// Missing ratios are written as `NA`.
+    pub fn to_tsv(&self) -> String {
+        let mut tsv = String::from(
+            "id\tlength\tA\tC\tG\tT\tN\tambiguous\tgc\tgc_skew\tat_skew\tgc1\tgc2\tgc3",
+        );
+        for pair in ["A", "C", "G", "T"].iter().flat_map(|first| {
+            ["A", "C", "G", "T"]
+                .iter()
+                .map(move |second| format!("{}{}", first, second))
+        }) {
+            let _ = write!(tsv, "\t{}", pair);
+        }
+        tsv.push('\n');
+        for record in &self.records {
+            let stats = &record.stats;
+            let _ = write!(
+                tsv,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                record.id, stats.length, stats.a, stats.c, stats.g, stats.t, stats.n,
+                stats.ambiguous
+            );
+            for value in [
+                stats.gc,
+                stats.gc_skew,
+                stats.at_skew,
+                stats.gc1,
+                stats.gc2,
+                stats.gc3,
+            ] {
+                let _ = write!(tsv, "\t{}", format_ratio(value));
+            }
+            for dinucleotide in &stats.dinucleotides {
+                let _ = write!(tsv, "\t{}", dinucleotide.count);
+            }
+            tsv.push('\n');
+        }
+        tsv
+    }
+
+    /// One row per window of every record, with a header line.
+    pub fn windows_tsv(&self) -> String {
+        let mut tsv = String::from(
+            "id\tstart\tend\tgc\tgc_skew\tcumulative_gc_skew\tat_skew\tN\tambiguous\n",
+        );
+        for record in &self.records {
+            for window in &record.windows {
+                let _ = writeln!(
+                    tsv,
+                    "{}\t{}\t{}\t{}\t{}\t{:.4}\t{}\t{}\t{}",
+                    record.id,
+                    window.start,
+                    window.end,
+                    format_ratio(window.gc),
+                    format_ratio(window.gc_skew),
+                    window.cumulative_gc_skew,
+                    format_ratio(window.at_skew),
+                    window.n,
+                    window.ambiguous
+                );
+            }
+        }
+        tsv
+    }
+}
+
+/// Composition of each nucleotide sequence of `records`. Gaps are removed
+/// and U is read as T before counting.
+pub fn composition_report(
+    records: &[FastaRecord],
+    options: &CompositionOptions,
+) -> Result<CompositionReport, String> {
+    if options.window == 0 || options.step == 0 {
+        return Err("Window length and step must be positive".to_string());
+    }
+    let mut profiles = Vec::with_capacity(records.len());
+    for record in records {
+        let sequence = sanitize_sequence(&record.sequence)
+            .map_err(|err| format!("line {}: {}", record.line, err))?;
+        let sequence = sequence.as_bytes();
+        profiles.push(CompositionProfile {
+            id: record.id.clone(),
+            description: record.description.clone(),
+            stats: CompositionStats::new(sequence),
+            windows: windows(sequence, options),
+        });
+    }
+    Ok(CompositionReport { records: profiles })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fasta::parse_fasta;
+
+    fn profiles(fasta: &str, window: usize, step: usize) -> CompositionReport {
+        let records = parse_fasta(fasta).unwrap();
+        composition_report(&records, &CompositionOptions { window, step }).unwrap()
+    }
+
+    #[test]
+    fn whole_sequence_statistics() {
+        let report = profiles(">s1 test\nGGGCAT\nNRu-A\n>empty\n", 4, 4);
+        let stats = &report.records[0].stats;
+        assert_eq!((stats.length, stats.a, stats.c, stats.g, stats.t), (10, 2, 1, 3, 2));
+        assert_eq!((stats.n, stats.ambiguous), (1, 1));
+        assert_eq!(stats.gc, Some(0.5));
+        assert_eq!(stats.gc_skew, Some(0.5));
+        assert_eq!(stats.at_skew, Some(0.0));
+        // Codon positions: GCNA / GAR / GTT.
+        let positions = (stats.gc1, stats.gc2, stats.gc3);
+        assert_eq!(positions, (Some(2.0 / 3.0), Some(0.5), Some(1.0 / 3.0)));
+        let gg = &stats.dinucleotides[10];
+        assert_eq!((gg.dinucleotide.as_str(), gg.count), ("GG", 2));
+        // 2 of 6 pairs against an expected (3/8)^2.
+        assert!((gg.rho.unwrap() - 2.0 / 6.0 / (9.0 / 64.0)).abs() < 1e-12);
+        assert_eq!(stats.dinucleotides.iter().map(|d| d.count).sum::<usize>(), 6);
+
+        let empty = &report.records[1];
+        assert_eq!((empty.stats.length, empty.stats.gc), (0, None));
+        assert!(empty.windows.is_empty());
+    }
+
+    #[test]
+    fn sliding_windows() {
+        let report = profiles(">s\nGGGGCCCCAAAATTTTGG\n", 8, 4);
+        let windows = &report.records[0].windows;
+        let ranges: Vec<_> = windows.iter().map(|w| (w.start, w.end)).collect();
+        assert_eq!(ranges, vec![(0, 8), (4, 12), (8, 16)]);
+        assert_eq!(windows[0].gc_skew, Some(0.0));
+        assert_eq!(windows[1].gc_skew, Some(-1.0));
+        assert_eq!(windows[2].gc_skew, None);
+        assert_eq!(windows[2].at_skew, Some(0.0));
+        assert_eq!(windows[2].cumulative_gc_skew, -1.0);
+
+        let short = profiles(">s\nGGC\n", 8, 4);
+        assert_eq!(short.records[0].windows[0].end, 3);
+        assert!((short.records[0].windows[0].cumulative_gc_skew - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn writes_tsv() {
+        let report = profiles(">a\nACGTN\n", 3, 2);
+        let tsv = report.to_tsv();
+        let lines: Vec<&str> = tsv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("id\tlength\tA\tC\tG\tT\tN\tambiguous\tgc\t"));
+        assert!(lines[0].ends_with("\tTG\tTT"));
+        assert!(lines[1].starts_with("a\t5\t1\t1\t1\t1\t1\t0\t0.5000\t0.0000\t0.0000\t"));
+        assert_eq!(lines[1].split('\t').count(), lines[0].split('\t').count());
+        assert_eq!(
+            report.windows_tsv().lines().nth(2),
+            Some("a\t2\t5\t0.5000\t1.0000\t1.0000\t-1.0000\t1\t0")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let records = parse_fasta(">a\nACGT\n>b\nACJT\n").unwrap();
+        let options = CompositionOptions::default();
+        assert!(composition_report(&records, &options).unwrap_err().starts_with("line 3:"));
+        let options = CompositionOptions { window: 10, step: 0 };
+        assert!(composition_report(&records[..1], &options).is_err());
+    }
+}
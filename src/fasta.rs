@@ -0,0 +1,320 @@
+use crate::gzip::{is_gzip, GzipDecoder};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// A FASTA record. Input without any `>` header is read as a single record
+/// with an empty `id`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FastaRecord {
+    /// Header up to the first whitespace.
+    pub id: String,
+    /// Rest of the header, trimmed.
+    pub description: String,
+    /// Residues of all sequence lines, with whitespace removed.
+    pub sequence: String,
+    /// 1-based line number of the header (or of the first sequence line of a
+    /// headerless record).
+    pub line: usize,
+}
+
+impl FastaRecord {
+    /// The full header: `id` and `description` separated by a space.
+    pub fn header(&self) -> String {
+        if self.description.is_empty() {
+            self.id.clone()
+        } else {
+            format!("{} {}", self.id, self.description)
+        }
+    }
+}
+
+/// Malformed FASTA input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FastaError {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FastaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for FastaError {}
+
+/// Turns FASTA lines into records; shared by [`parse_fasta`] and
+/// [`FastaReader`].
+#[derive(Debug, Default)]
+struct RecordBuilder {
+    current: Option<FastaRecord>,
+    headerless: bool,
+}
+
+impl RecordBuilder {
+    /// Add line number `line` (without its line ending), returning the record
+    /// that it completes, if any.
+    fn add_line(
+        &mut self,
+        line: usize,
+        content: &str,
+    ) -> Result<Option<FastaRecord>, FastaError> {
+        if content.trim().is_empty() || content.starts_with(';') {
+            return Ok(None);
+        }
+        if let Some(header) = content.strip_prefix('>') {
+            if let Some(record) = self.current.as_ref().filter(|_| self.headerless) {
+                return Err(FastaError {
+                    line: record.line,
+                    message: "sequence data before the first '>' header".to_string(),
+                });
+            }
+            let header = header.trim();
+            let (id, description) = match header.split_once(char::is_whitespace) {
+                Some((id, description)) => (id, description.trim()),
+                None => (header, ""),
+            };
+            return Ok(self.current.replace(FastaRecord {
+                id: id.to_string(),
+                description: description.to_string(),
+                sequence: String::new(),
+                line,
+            }));
+        }
+        let headerless = &mut self.headerless;
+        let record = self.current.get_or_insert_with(|| {
+            *headerless = true;
+            FastaRecord {
+                id: String::new(),
+                description: String::new(),
+                sequence: String::new(),
+                line,
+            }
+        });
+        for ch in content.chars().filter(|ch| !ch.is_whitespace()) {
+            if !(ch.is_ascii_alphabetic() || matches!(ch, '*' | '-' | '.')) {
+                return Err(FastaError {
+                    line,
+                    message: format!("invalid character '{}' in sequence", ch),
+                });
+            }
+            record.sequence.push(ch);
+        }
+        Ok(None)
+    }
+
+    fn finish(&mut self) -> Option<FastaRecord> {
+        self.current.take()
+    }
+}
+
+/// Parse FASTA text: any number of records, sequences wrapped over several
+/// lines, LF or CRLF line endings. Blank lines and `;` comment lines are
+/// skipped, and records without sequence lines are kept with an empty
+/// sequence. Sequence lines may contain letters, `*`, `-` and `.`.
+pub fn parse_fasta(text: &str) -> Result<Vec<FastaRecord>, FastaError> {
+    let mut builder = RecordBuilder::default();
+    let mut records = Vec::new();
+    for (idx, raw) in text.split('\n').enumerate() {
+        let content = raw.strip_suffix('\r').unwrap_or(raw);
+        records.extend(builder.add_line(idx + 1, content)?);
+    }
+    records.extend(builder.finish());
+    Ok(records)
+}
+
+/// Incremental FASTA parser for inputs too large to hold in memory: `push`
+/// the bytes of a file in chunks of any size, take the completed records
+/// with `next_record`, and call `finish` at the end of the input. Gzipped
+/// input is decompressed on the fly. Only the record being read is kept in
+/// memory. Records are parsed as by [`parse_fasta`], except that a
+/// headerless sequence is only returned by `finish`.
+#[derive(Default)]
+pub struct FastaReader {
+    /// `None` until the first two bytes tell whether the input is gzipped.
+    gzip: Option<Option<GzipDecoder>>,
+    /// Bytes received before that.
+    head: Vec<u8>,
+    /// Start of the line being read.
+    partial: Vec<u8>,
+    /// Number of complete lines read.
+    lines: usize,
+    builder: RecordBuilder,
+    ready: VecDeque<FastaRecord>,
+}
+
+impl FastaReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), FastaError> {
+        match &mut self.gzip {
+            Some(Some(decoder)) => {
+                let mut text = Vec::new();
+                let result = decoder.write(chunk, &mut text);
+                self.push_text(&text)?;
+                result.map_err(|message| self.error(message))
+            }
+            Some(None) => self.push_text(chunk),
+            None => {
+                self.head.extend_from_slice(chunk);
+                if self.head.len() < 2 {
+                    return Ok(());
+                }
+                self.start()
+            }
+        }
+    }
+
+    /// Decide whether the input is gzipped and process the bytes held back
+    /// until then.
+    fn start(&mut self) -> Result<(), FastaError> {
+        let head = std::mem::take(&mut self.head);
+        self.gzip = Some(is_gzip(&head).then(GzipDecoder::new));
+        self.push(&head)
+    }
+
+    /// Signal the end of the input. The last record becomes available.
+    pub fn finish(&mut self) -> Result<(), FastaError> {
+        if self.gzip.is_none() {
+            self.start()?;
+        }
+        if let Some(Some(decoder)) = &self.gzip {
+            decoder.finish().map_err(|message| self.error(message))?;
+        }
+        if !self.partial.is_empty() {
+            let line = std::mem::take(&mut self.partial);
+            self.add_line(&line)?;
+        }
+        self.ready.extend(self.builder.finish());
+        Ok(())
+    }
+
+    /// The next completed record, if any.
+    pub fn next_record(&mut self) -> Option<FastaRecord> {
+        self.ready.pop_front()
+    }
+
+    fn push_text(&mut self, bytes: &[u8]) -> Result<(), FastaError> {
+        let mut buffer = std::mem::take(&mut self.partial);
+        // Only the new bytes need to be searched for line ends.
+        let mut search = buffer.len();
+        buffer.extend_from_slice(bytes);
+        let mut start = 0;
+        while let Some(len) = buffer[search..].iter().position(|&b| b == b'\n') {
+            let end = search + len;
+            self.add_line(&buffer[start..end])?;
+            start = end + 1;
+            search = start;
+        }
+        buffer.drain(..start);
+        self.partial = buffer;
+        Ok(())
+    }
+
+    fn add_line(&mut self, raw: &[u8]) -> Result<(), FastaError> {
+        self.lines += 1;
+        let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+        let content = std::str::from_utf8(raw).map_err(|_| FastaError {
+            line: self.lines,
+            message: "invalid UTF-8 text".to_string(),
+        })?;
+        if let Some(record) = self.builder.add_line(self.lines, content)? {
+            self.ready.push_back(record);
+        }
+        Ok(())
+    }
+
+    fn error(&self, message: String) -> FastaError {
+        FastaError {
+            line: self.lines + 1,
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_records() {
+        let text = "; comment\r\n>seq1 first sequence\r\nACGT\r\nac gt\r\n\r\n>seq2\n>seq3  x\nMK*\n";
+        let records = parse_fasta(text).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].id, "seq1");
+        assert_eq!(records[0].description, "first sequence");
+        assert_eq!(records[0].header(), "seq1 first sequence");
+        assert_eq!(records[0].sequence, "ACGTacgt");
+        assert_eq!(records[0].line, 2);
+        assert_eq!(records[1].sequence, "");
+        assert_eq!(records[1].header(), "seq2");
+        assert_eq!(records[2].description, "x");
+        assert_eq!(records[2].sequence, "MK*");
+        assert_eq!(records[2].line, 7);
+    }
+
+    #[test]
+    fn parses_raw_sequences() {
+        let records = parse_fasta("\nACGT\nTT-A\n").unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, "");
+        assert_eq!(records[0].sequence, "ACGTTT-A");
+        assert_eq!(records[0].line, 2);
+        assert!(parse_fasta("  \n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn reports_line_numbers() {
+        let err = parse_fasta(">a\nACGT\nAC1T\n").unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.to_string(), "line 3: invalid character '1' in sequence");
+        let err = parse_fasta("\nACGT\n>a\nACGT\n").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    fn read_chunked(data: &[u8], chunk_size: usize) -> Result<Vec<FastaRecord>, FastaError> {
+        let mut reader = FastaReader::new();
+        let mut records = Vec::new();
+        for chunk in data.chunks(chunk_size) {
+            reader.push(chunk)?;
+            records.extend(std::iter::from_fn(|| reader.next_record()));
+        }
+        reader.finish()?;
+        records.extend(std::iter::from_fn(|| reader.next_record()));
+        Ok(records)
+    }
+
+    #[test]
+    fn reads_in_chunks() {
+        let text = "; comment\r\n>seq1 first\r\nACGT\r\nac gt\r\n\r\n>seq2\n>seq3  x\nMK*";
+        let expected = parse_fasta(text).unwrap();
+        for chunk_size in [1, 2, 5, 100] {
+            assert_eq!(read_chunked(text.as_bytes(), chunk_size).unwrap(), expected);
+        }
+        assert_eq!(read_chunked(b"ACGT\nTT", 3).unwrap(), parse_fasta("ACGT\nTT").unwrap());
+        assert!(read_chunked(b"", 1).unwrap().is_empty());
+        assert_eq!(read_chunked(b">a\nACGT\nAC1T\n", 4).unwrap_err().line, 3);
+        assert_eq!(read_chunked(b"\nACGT\n>a\nACGT\n", 4).unwrap_err().line, 2);
+
+        // Records are returned as soon as the next header is read.
+        let mut reader = FastaReader::new();
+        reader.push(b">a\nAC\nGT\n>b\nTT").unwrap();
+        assert_eq!(reader.next_record().unwrap().sequence, "ACGT");
+        assert!(reader.next_record().is_none());
+        reader.finish().unwrap();
+        assert_eq!(reader.next_record().unwrap().sequence, "TT");
+    }
+
+    #[test]
+    fn reads_gzipped_input() {
+        let text = ">seq1\nACGT\n>seq2\nTTGA\n".repeat(50);
+        let data = crate::gzip::gzip_member(text.as_bytes(), true);
+        let records = read_chunked(&data, 7).unwrap();
+        assert_eq!(records, parse_fasta(&text).unwrap());
+        assert!(read_chunked(&data[..data.len() - 1], 7).is_err());
+    }
+}
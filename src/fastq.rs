@@ -0,0 +1,424 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Offset of the quality characters of a FASTQ file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhredEncoding {
+    /// Sanger and Illumina 1.8+ (`!` is quality 0).
+    Phred33,
+    /// Illumina 1.3 to 1.7 (`@` is quality 0).
+    Phred64,
+}
+
+impl PhredEncoding {
+    pub fn from_name(name: &str) -> Result<PhredEncoding, String> {
+        match name.trim().to_ascii_lowercase().replace(['+', '-', '_'], "").as_str() {
+            "phred33" | "33" | "sanger" => Ok(PhredEncoding::Phred33),
+            "phred64" | "64" => Ok(PhredEncoding::Phred64),
+            _ => Err(format!(
+                "Unknown quality encoding '{}' (expected phred33 or phred64)",
+                name
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PhredEncoding::Phred33 => "phred33",
+            PhredEncoding::Phred64 => "phred64",
+        }
+    }
+
+    pub fn offset(self) -> u8 {
+        match self {
+            PhredEncoding::Phred33 => 33,
+            PhredEncoding::Phred64 => 64,
+        }
+    }
+
+    /// Guess the encoding from the quality characters: any character below
+    /// `@` means Phred+33, any above `J` (the highest Phred+33 quality of
+    /// Illumina 1.8) means Phred+64. Phred+33 is assumed when in doubt.
+    pub fn detect(qualities: &[u8]) -> PhredEncoding {
+        match (qualities.iter().min(), qualities.iter().max()) {
+            (Some(&min), Some(&max)) if min >= b'@' && max > b'J' => PhredEncoding::Phred64,
+            _ => PhredEncoding::Phred33,
+        }
+    }
+}
+
+/// A FASTQ read with its qualities decoded to Phred scores.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FastqRecord {
+    /// Header up to the first whitespace.
+    pub id: String,
+    /// Rest of the header, trimmed.
+    pub description: String,
+    pub sequence: String,
+    /// Phred quality of each base.
+    pub quality: Vec<u8>,
+    /// 1-based line number of the `@` header.
+    pub line: usize,
+}
+
+/// Malformed FASTQ input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FastqError {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FastqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for FastqError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FastqReads {
+    /// The encoding given, or the detected one.
+    pub encoding: PhredEncoding,
+    pub records: Vec<FastqRecord>,
+}
+
+/// A record before its qualities are decoded.
+struct RawRecord<'a> {
+    id: &'a str,
+    description: &'a str,
+    sequence: String,
+    quality: Vec<u8>,
+    line: usize,
+}
+
+/// Parse FASTQ text. Sequence and quality may be wrapped over several lines
+/// (the quality ends once it is as long as the sequence), line endings may
+/// be LF or CRLF and blank lines between records are skipped. With
+/// `encoding` unset, the quality encoding is detected from the whole input.
+pub fn parse_fastq(text: &str, encoding: Option<PhredEncoding>) -> Result<FastqReads, FastqError> {
+    let mut lines = text
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line));
+    let mut raw = Vec::new();
+    while let Some((line, header)) = lines.next() {
+        if header.trim().is_empty() {
+            continue;
+        }
+        let header = header.strip_prefix('@').ok_or_else(|| FastqError {
+            line,
+            message: "expected a '@' header".to_string(),
+        })?;
+        let header = header.trim();
+        let (id, description) = match header.split_once(char::is_whitespace) {
+            Some((id, description)) => (id, description.trim()),
+            None => (header, ""),
+        };
+
+        let mut sequence = String::new();
+        let separator = loop {
+            let Some((number, content)) = lines.next() else {
+                return Err(FastqError {
+                    line,
+                    message: format!("read '{}' has no '+' separator line", id),
+                });
+            };
+            if let Some(repeated) = content.strip_prefix('+') {
+                break (number, repeated.trim());
+            }
+            for ch in content.chars().filter(|ch| !ch.is_whitespace()) {
+                if !(ch.is_ascii_alphabetic() || matches!(ch, '*' | '-' | '.')) {
+                    return Err(FastqError {
+                        line: number,
+                        message: format!("invalid character '{}' in sequence", ch),
+                    });
+                }
+                sequence.push(ch);
+            }
+        };
+        if !separator.1.is_empty() && separator.1 != header {
+            return Err(FastqError {
+                line: separator.0,
+                message: format!("'+' line does not repeat the header of read '{}'", id),
+            });
+        }
+
+        let mut quality = Vec::with_capacity(sequence.len());
+        while quality.len() < sequence.len() {
+            let Some((number, content)) = lines.next() else {
+                break;
+            };
+            quality.extend(content.bytes().filter(|b| !b.is_ascii_whitespace()));
+            if quality.len() > sequence.len() {
+                return Err(FastqError {
+                    line: number,
+                    message: format!(
+                        "read '{}' has {} quality values for {} bases",
+                        id,
+                        quality.len(),
+                        sequence.len()
+                    ),
+                });
+            }
+        }
+        if quality.len() < sequence.len() {
+            return Err(FastqError {
+                line,
+                message: format!(
+                    "read '{}' has {} quality values for {} bases",
+                    id,
+                    quality.len(),
+                    sequence.len()
+                ),
+            });
+        }
+        raw.push(RawRecord {
+            id,
+            description,
+            sequence,
+            quality,
+            line,
+        });
+    }
+
+    let encoding = encoding.unwrap_or_else(|| {
+        let all: Vec<u8> = raw.iter().flat_map(|r| r.quality.iter().copied()).collect();
+        PhredEncoding::detect(&all)
+    });
+    let offset = encoding.offset();
+    let records = raw
+        .into_iter()
+        .map(|r| {
+            let quality = r
+                .quality
+                .iter()
+                .map(|&q| match q {
+                    b'!'..=b'~' if q >= offset => Ok(q - offset),
+                    _ => Err(FastqError {
+                        line: r.line,
+                        message: format!(
+                            "quality character '{}' of read '{}' is invalid for {:?}",
+                            q as char, r.id, encoding
+                        ),
+                    }),
+                })
+                .collect::<Result<Vec<u8>, FastqError>>()?;
+            Ok(FastqRecord {
+                id: r.id.to_string(),
+                description: r.description.to_string(),
+                sequence: r.sequence,
+                quality,
+                line: r.line,
+            })
+        })
+        .collect::<Result<Vec<_>, FastqError>>()?;
+    Ok(FastqReads { encoding, records })
+}
+
+/// Quality trimming and length filtering of reads.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TrimOptions {
+    /// Modified Mott trimming with this error probability limit (e.g. 0.05):
+    /// keep the stretch of the read maximizing the sum of
+    /// `limit - error_probability` over its bases.
+    pub mott_limit: Option<f64>,
+    /// Sliding window trimming as `(window size, minimum mean quality)`: cut
+    /// the read at the first window, scanning from the 5' end, whose mean
+    /// quality falls below the minimum. Applied after Mott trimming.
+    pub sliding_window: Option<(usize, f64)>,
+    /// Drop reads shorter than this after trimming.
+    pub min_length: usize,
+    /// Drop reads longer than this after trimming.
+    pub max_length: Option<usize>,
+}
+
+/// Range of `quality` kept by modified Mott trimming.
+fn mott_range(quality: &[u8], limit: f64) -> (usize, usize) {
+    let mut best = (0, 0);
+    let mut best_sum = 0.0;
+    let mut start = 0;
+    let mut sum = 0.0;
+    for (idx, &q) in quality.iter().enumerate() {
+        sum += limit - 10f64.powf(-f64::from(q) / 10.0);
+        if sum <= 0.0 {
+            sum = 0.0;
+            start = idx + 1;
+        } else if sum > best_sum {
+            best_sum = sum;
+            best = (start, idx + 1);
+        }
+    }
+    best
+}
+
+/// Length of the prefix of `quality` kept by sliding window trimming.
+fn sliding_window_end(quality: &[u8], size: usize, min_quality: f64) -> usize {
+    let size = size.max(1);
+    if quality.len() < size {
+        let mean = quality.iter().map(|&q| f64::from(q)).sum::<f64>() / quality.len() as f64;
+        return if mean < min_quality { 0 } else { quality.len() };
+    }
+    let mut total: u32 = quality[..size].iter().map(|&q| u32::from(q)).sum();
+    for start in 0..=quality.len() - size {
+        if start > 0 {
+            total += u32::from(quality[start + size - 1]);
+            total -= u32::from(quality[start - 1]);
+        }
+        if f64::from(total) / (size as f64) < min_quality {
+            return start;
+        }
+    }
+    quality.len()
+}
+
+/// Trim a read as set by `options`, or `None` if it fails the length filter.
+pub fn trim_read(record: &FastqRecord, options: &TrimOptions) -> Option<FastqRecord> {
+    let (mut start, mut end) = (0, record.quality.len());
+    if let Some(limit) = options.mott_limit {
+        (start, end) = mott_range(&record.quality, limit);
+    }
+    if let Some((size, min_quality)) = options.sliding_window {
+        if end > start {
+            end = start + sliding_window_end(&record.quality[start..end], size, min_quality);
+        }
+    }
+    let length = end - start;
+    if length < options.min_length || options.max_length.is_some_and(|max| length > max) {
+        return None;
+    }
+    Some(FastqRecord {
+        id: record.id.clone(),
+        description: record.description.clone(),
+        sequence: record.sequence[start..end].to_string(),
+        quality: record.quality[start..end].to_vec(),
+        line: record.line,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrimSummary {
+    pub encoding: PhredEncoding,
+    pub input_reads: usize,
+    pub input_bases: usize,
+    pub kept_bases: usize,
+    /// The reads that pass the length filter, trimmed.
+    pub records: Vec<FastqRecord>,
+}
+
+/// Parse FASTQ text and trim and filter its reads.
+pub fn trim_fastq(
+    text: &str,
+    encoding: Option<PhredEncoding>,
+    options: &TrimOptions,
+) -> Result<TrimSummary, FastqError> {
+    let reads = parse_fastq(text, encoding)?;
+    let records: Vec<FastqRecord> = reads
+        .records
+        .iter()
+        .filter_map(|record| trim_read(record, options))
+        .collect();
+    Ok(TrimSummary {
+        encoding: reads.encoding,
+        input_reads: reads.records.len(),
+        input_bases: reads.records.iter().map(|r| r.sequence.len()).sum(),
+        kept_bases: records.iter().map(|r| r.sequence.len()).sum(),
+        records,
+    })
+}
+
+/// Reads as FASTA, keeping the full header.
+pub fn reads_to_fasta(records: &[FastqRecord]) -> String {
+    let mut fasta = String::new();
+    for record in records {
+        fasta.push('>');
+        fasta.push_str(&record.id);
+        if !record.description.is_empty() {
+            fasta.push(' ');
+            fasta.push_str(&record.description);
+        }
+        fasta.push('\n');
+        for line in record.sequence.as_bytes().chunks(60) {
+            fasta.push_str(&String::from_utf8_lossy(line));
+            fasta.push('\n');
+        }
+    }
+    fasta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const READS: &str = "@r1 sample=1\r\nACGTACGTAC\r\n+\r\nIIIIIIII#!\r\n\r\n@r2\nACGT\nTT\n+r2\nIII\nII#\n";
+
+    #[test]
+    fn parses_and_detects_encoding() {
+        let reads = parse_fastq(READS, None).unwrap();
+        assert_eq!(reads.encoding, PhredEncoding::Phred33);
+        assert_eq!(reads.records.len(), 2);
+        let r1 = &reads.records[0];
+        assert_eq!((r1.id.as_str(), r1.description.as_str()), ("r1", "sample=1"));
+        assert_eq!(r1.quality, vec![40, 40, 40, 40, 40, 40, 40, 40, 2, 0]);
+        let r2 = &reads.records[1];
+        assert_eq!((r2.sequence.as_str(), r2.line), ("ACGTTT", 6));
+        assert_eq!(r2.quality, vec![40, 40, 40, 40, 40, 2]);
+
+        let phred64 = parse_fastq("@r\nACG\n+\nhhB\n", None).unwrap();
+        assert_eq!(phred64.encoding, PhredEncoding::Phred64);
+        assert_eq!(phred64.records[0].quality, vec![40, 40, 2]);
+        assert!(parse_fastq("@r\nACG\n+\nII#\n", Some(PhredEncoding::Phred64)).is_err());
+        assert_eq!(PhredEncoding::from_name("Phred+64").unwrap(), PhredEncoding::Phred64);
+    }
+
+    #[test]
+    fn reports_malformed_reads() {
+        let err = parse_fastq("@r\nACGT\n+\nIII\n", None).unwrap_err();
+        assert_eq!(err.line, 1);
+        let err = parse_fastq("@r\nACGT\n+\nIIIII\n", None).unwrap_err();
+        assert_eq!(err.line, 4);
+        let err = parse_fastq("ACGT\n", None).unwrap_err();
+        assert_eq!(err.to_string(), "line 1: expected a '@' header");
+        let err = parse_fastq("@r\nACGT\n+q\nIIII\n", None).unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(parse_fastq("@r\nACGT\n", None).is_err());
+    }
+
+    #[test]
+    fn trims_reads() {
+        let reads = parse_fastq(READS, None).unwrap().records;
+        let mott = TrimOptions {
+            mott_limit: Some(0.05),
+            ..TrimOptions::default()
+        };
+        assert_eq!(trim_read(&reads[0], &mott).unwrap().sequence, "ACGTACGT");
+
+        let window = TrimOptions {
+            sliding_window: Some((2, 22.0)),
+            ..TrimOptions::default()
+        };
+        // The window over the last Q40 base and the Q2 base has a mean of 21.
+        assert_eq!(trim_read(&reads[0], &window).unwrap().sequence, "ACGTACG");
+
+        let filtered = TrimOptions {
+            mott_limit: Some(0.05),
+            min_length: 6,
+            max_length: Some(7),
+            ..TrimOptions::default()
+        };
+        assert!(trim_read(&reads[0], &filtered).is_none());
+        let summary = trim_fastq(READS, None, &filtered).unwrap();
+        assert_eq!((summary.input_reads, summary.records.len()), (2, 0));
+
+        let summary = trim_fastq(READS, None, &mott).unwrap();
+        assert_eq!((summary.input_bases, summary.kept_bases), (16, 13));
+        assert_eq!(
+            reads_to_fasta(&summary.records),
+            ">r1 sample=1\nACGTACGT\n>r2\nACGTT\n"
+        );
+    }
+}
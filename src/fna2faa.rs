@@ -1,40 +1,8 @@
-use serde::Serialize;
-
-/// Mapping for nucleotide characters to bitmasks of canonical bases.
-/// The canonical bases use the order A, C, G, T (U maps to T).
-fn base_mask(base: u8) -> Option<u8> {
-    match base {
-        b'A' | b'a' => Some(0b0001),
-        b'C' | b'c' => Some(0b0010),
-        b'G' | b'g' => Some(0b0100),
-        b'T' | b't' | b'U' | b'u' => Some(0b1000),
-        b'R' | b'r' => Some(0b0101),
-        b'Y' | b'y' => Some(0b1010),
-        b'S' | b's' => Some(0b0110),
-        b'W' | b'w' => Some(0b1001),
-        b'K' | b'k' => Some(0b1100),
-        b'M' | b'm' => Some(0b0011),
-        b'B' | b'b' => Some(0b1110),
-        b'D' | b'd' => Some(0b1101),
-        b'H' | b'h' => Some(0b1011),
-        b'V' | b'v' => Some(0b0111),
-        b'N' | b'n' | b'X' | b'x' => Some(0b1111),
-        b'-' => Some(0),
-        _ => None,
-    }
-}
+use serde::{Deserialize, Serialize};
 
-fn canonical_index(base: u8) -> Option<usize> {
-    match base {
-        b'A' | b'a' => Some(0),
-        b'C' | b'c' => Some(1),
-        b'G' | b'g' => Some(2),
-        b'T' | b't' | b'U' | b'u' => Some(3),
-        _ => None,
-    }
-}
+use crate::alphabet::{canonical_nucleotide_index, nucleotide_mask};
 
-fn codon_index(first: usize, second: usize, third: usize) -> usize {
+pub(crate) fn codon_index(first: usize, second: usize, third: usize) -> usize {
     (first << 4) | (second << 2) | third
 }
 
@@ -65,17 +33,249 @@ pub fn rev_compl_to(src: &[u8], dst: &mut Vec<u8>) {
     }
 }
 
+/// An NCBI translation table.
+///
+/// `amino_acids` gives the product of every codon in the order NCBI prints
+/// the tables: TTT, TTC, TTA, TTG, TCT, ... (bases ordered T, C, A, G, first
+/// codon position varying slowest). `starts` follows the same order and marks
+/// the codons that can initiate translation with `M`.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct GeneticCode {
+    pub id: u8,
+    pub name: &'static str,
+    pub amino_acids: &'static str,
+    pub starts: &'static str,
+}
+
+/// Bases in the order used by [`GeneticCode::amino_acids`].
+const NCBI_BASE_ORDER: &[u8; 4] = b"TCAG";
+
+/// All NCBI translation tables, by increasing ID. IDs 7, 8 and 17-20 were
+/// never assigned or have been merged into other tables.
+pub const GENETIC_CODES: [GeneticCode; 27] = [
+    GeneticCode {
+        id: 1,
+        name: "Standard",
+        amino_acids: "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "---M------**--*----M---------------M----------------------------",
+    },
+    GeneticCode {
+        id: 2,
+        name: "Vertebrate Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+        starts: "----------**--------------------MMMM----------**---M------------",
+    },
+    GeneticCode {
+        id: 3,
+        name: "Yeast Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "----------**----------------------MM---------------M------------",
+    },
+    GeneticCode {
+        id: 4,
+        name: "Mold, Protozoan, and Coelenterate Mitochondrial and Mycoplasma/Spiroplasma",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "--MM------**-------M------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 5,
+        name: "Invertebrate Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+        starts: "---M------**--------------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 6,
+        name: "Ciliate, Dasycladacean and Hexamita Nuclear",
+        amino_acids: "FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "--------------*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 9,
+        name: "Echinoderm and Flatworm Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: "----------**-----------------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 10,
+        name: "Euplotid Nuclear",
+        amino_acids: "FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "----------**-----------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 11,
+        name: "Bacterial, Archaeal and Plant Plastid",
+        amino_acids: "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "---M------**--*----M------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 12,
+        name: "Alternative Yeast Nuclear",
+        amino_acids: "FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "----------**--*----M---------------M----------------------------",
+    },
+    GeneticCode {
+        id: 13,
+        name: "Ascidian Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+        starts: "---M------**----------------------MM---------------M------------",
+    },
+    GeneticCode {
+        id: 14,
+        name: "Alternative Flatworm Mitochondrial",
+        amino_acids: "FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: "-----------*-----------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 15,
+        name: "Blepharisma Macronuclear",
+        amino_acids: "FFLLSSSSYY*QCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "----------*---*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 16,
+        name: "Chlorophycean Mitochondrial",
+        amino_acids: "FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "----------*---*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 21,
+        name: "Trematode Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: "----------**-----------------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 22,
+        name: "Scenedesmus obliquus Mitochondrial",
+        amino_acids: "FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "------*---*---*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 23,
+        name: "Thraustochytrium Mitochondrial",
+        amino_acids: "FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "--*-------**--*-----------------M--M---------------M------------",
+    },
+    GeneticCode {
+        id: 24,
+        name: "Rhabdopleuridae Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        starts: "---M------**-------M---------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 25,
+        name: "Candidate Division SR1 and Gracilibacteria",
+        amino_acids: "FFLLSSSSYY**CCGWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "---M------**-----------------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 26,
+        name: "Pachysolen tannophilus Nuclear",
+        amino_acids: "FFLLSSSSYY**CC*WLLLAPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "----------**--*----M---------------M----------------------------",
+    },
+    GeneticCode {
+        id: 27,
+        name: "Karyorelict Nuclear",
+        amino_acids: "FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "--------------*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 28,
+        name: "Condylostoma Nuclear",
+        amino_acids: "FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "----------**--*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 29,
+        name: "Mesodinium Nuclear",
+        amino_acids: "FFLLSSSSYYYYCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "--------------*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 30,
+        name: "Peritrich Nuclear",
+        amino_acids: "FFLLSSSSYYEECC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "--------------*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 31,
+        name: "Blastocrithidia Nuclear",
+        amino_acids: "FFLLSSSSYYEECCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "----------**-----------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 32,
+        name: "Balanophoraceae Plastid",
+        amino_acids: "FFLLSSSSYY*WCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "---M------*---*----M------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 33,
+        name: "Cephalodiscidae Mitochondrial UAA-Tyr",
+        amino_acids: "FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        starts: "---M-------*-------M---------------M---------------M------------",
+    },
+];
+
+/// The NCBI translation table with the given ID.
+pub fn genetic_code(id: u8) -> Result<&'static GeneticCode, String> {
+    GENETIC_CODES
+        .iter()
+        .find(|code| code.id == id)
+        .ok_or_else(|| {
+            format!(
+                "Unknown genetic code {} (NCBI tables are 1-6, 9-16 and 21-33)",
+                id
+            )
+        })
+}
+
 /// Encapsulates an amino acid lookup table for canonical codons.
 #[derive(Debug, Clone)]
 pub struct CodonEncoder {
     table: [u8; 64],
+    /// Bit `codon_index(..)` is set for the start codons of the code.
+    starts: u64,
 }
 
 impl CodonEncoder {
+    /// Encoder for the standard genetic code (NCBI table 1).
     pub fn mk_encoder() -> Self {
+        Self::with_code(&GENETIC_CODES[0])
+    }
+
+    /// Encoder for the NCBI translation table `id`.
+    pub fn for_table(id: u8) -> Result<Self, String> {
+        genetic_code(id).map(Self::with_code)
+    }
+
+    fn with_code(genetic_code: &GeneticCode) -> Self {
+        let mut starts = 0u64;
+        for (ncbi_idx, mark) in genetic_code.starts.bytes().enumerate() {
+            if mark == b'M' {
+                starts |= 1 << ncbi_to_codon_index(ncbi_idx);
+            }
+        }
         CodonEncoder {
-            table: build_table(),
+            table: build_table(genetic_code),
+            starts,
+        }
+    }
+
+    /// Whether `codon` is a start codon of the genetic code. Codons with
+    /// ambiguous bases are never treated as starts.
+    pub fn is_start_codon(&self, codon: &[u8]) -> bool {
+        if codon.len() != 3 {
+            return false;
+        }
+        let mut idx = 0;
+        for &nt in codon {
+            match canonical_nucleotide_index(nt) {
+                Some(base) => idx = (idx << 2) | base,
+                None => return false,
+            }
         }
+        self.starts & (1 << idx) != 0
     }
 
     pub fn translate_triplet(&self, codon: &[u8]) -> (u8, bool) {
@@ -85,13 +285,13 @@ impl CodonEncoder {
 
         let mut masks = [0u8; 3];
         for (idx, &nt) in codon.iter().enumerate() {
-            match base_mask(nt) {
+            match nucleotide_mask(nt) {
                 Some(mask) => masks[idx] = mask,
                 None => return (b'X', true),
             }
         }
 
-        if masks.iter().any(|&m| m == 0) {
+        if masks.contains(&0) {
             return (b'X', true);
         }
 
@@ -127,93 +327,113 @@ impl CodonEncoder {
     }
 }
 
-pub fn build_table() -> [u8; 64] {
+/// Convert a position in the NCBI codon order to a [`codon_index`].
+fn ncbi_to_codon_index(ncbi_idx: usize) -> usize {
+    let base = |shift: usize| {
+        canonical_nucleotide_index(NCBI_BASE_ORDER[(ncbi_idx >> shift) & 3]).unwrap()
+    };
+    codon_index(base(4), base(2), base(0))
+}
+
+pub fn build_table(genetic_code: &GeneticCode) -> [u8; 64] {
     let mut table = [b'X'; 64];
-    for (codon, aa) in STANDARD_TABLE.iter() {
-        let chars: Vec<u8> = codon.bytes().collect();
-        let idx = codon_index(
-            canonical_index(chars[0]).unwrap(),
-            canonical_index(chars[1]).unwrap(),
-            canonical_index(chars[2]).unwrap(),
-        );
-        table[idx] = *aa as u8;
+    for (ncbi_idx, aa) in genetic_code.amino_acids.bytes().enumerate() {
+        table[ncbi_to_codon_index(ncbi_idx)] = aa;
     }
     table
 }
 
-const STANDARD_TABLE: [(&str, char); 64] = [
-    ("TTT", 'F'),
-    ("TTC", 'F'),
-    ("TTA", 'L'),
-    ("TTG", 'L'),
-    ("TCT", 'S'),
-    ("TCC", 'S'),
-    ("TCA", 'S'),
-    ("TCG", 'S'),
-    ("TAT", 'Y'),
-    ("TAC", 'Y'),
-    ("TAA", '*'),
-    ("TAG", '*'),
-    ("TGT", 'C'),
-    ("TGC", 'C'),
-    ("TGA", '*'),
-    ("TGG", 'W'),
-    ("CTT", 'L'),
-    ("CTC", 'L'),
-    ("CTA", 'L'),
-    ("CTG", 'L'),
-    ("CCT", 'P'),
-    ("CCC", 'P'),
-    ("CCA", 'P'),
-    ("CCG", 'P'),
-    ("CAT", 'H'),
-    ("CAC", 'H'),
-    ("CAA", 'Q'),
-    ("CAG", 'Q'),
-    ("CGT", 'R'),
-    ("CGC", 'R'),
-    ("CGA", 'R'),
-    ("CGG", 'R'),
-    ("ATT", 'I'),
-    ("ATC", 'I'),
-    ("ATA", 'I'),
-    ("ATG", 'M'),
-    ("ACT", 'T'),
-    ("ACC", 'T'),
-    ("ACA", 'T'),
-    ("ACG", 'T'),
-    ("AAT", 'N'),
-    ("AAC", 'N'),
-    ("AAA", 'K'),
-    ("AAG", 'K'),
-    ("AGT", 'S'),
-    ("AGC", 'S'),
-    ("AGA", 'R'),
-    ("AGG", 'R'),
-    ("GTT", 'V'),
-    ("GTC", 'V'),
-    ("GTA", 'V'),
-    ("GTG", 'V'),
-    ("GCT", 'A'),
-    ("GCC", 'A'),
-    ("GCA", 'A'),
-    ("GCG", 'A'),
-    ("GAT", 'D'),
-    ("GAC", 'D'),
-    ("GAA", 'E'),
-    ("GAG", 'E'),
-    ("GGT", 'G'),
-    ("GGC", 'G'),
-    ("GGA", 'G'),
-    ("GGG", 'G'),
-];
+/// A codon of a translated frame, located on the forward strand.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CodonSite {
+    /// Index of the amino acid in [`FrameTranslation::amino_acids`].
+    pub aa_index: usize,
+    /// 0-based, end-exclusive coordinates of the codon on the forward strand
+    /// of the translated sequence (after whitespace and gaps are removed).
+    pub start: usize,
+    pub end: usize,
+    /// The codon as read in the frame (reverse complemented on reverse
+    /// frames).
+    pub codon: String,
+}
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
 pub struct FrameTranslation {
     pub frame: i8,
     pub amino_acids: String,
+    /// Amino-acid indices of the stop codons.
     pub stops: Vec<usize>,
+    /// Amino-acid indices of the ambiguous codons.
     pub ambiguous: Vec<usize>,
+    /// The codons of `stops`, in the same order.
+    pub stop_sites: Vec<CodonSite>,
+    /// The codons of `ambiguous`, in the same order.
+    pub ambiguous_sites: Vec<CodonSite>,
+    /// Amino-acid indices of the stop codons translated as selenocysteine
+    /// (`U`) or pyrrolysine (`O`); these are not in `stops`.
+    pub recoded: Vec<usize>,
+    /// The codons of `recoded`, in the same order.
+    pub recoded_sites: Vec<CodonSite>,
+    /// First codon of the frame, when it is a start codon and
+    /// [`TranslationOptions::report_start_codon`] is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_codon: Option<String>,
+}
+
+/// How a frame is translated, besides the genetic code held by the
+/// [`CodonEncoder`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TranslationOptions {
+    pub stop_at_first_stop: bool,
+    /// Translate the first codon as `M` when it is a start codon of the
+    /// genetic code (e.g. a bacterial GTG or TTG initiator).
+    pub initiator_as_methionine: bool,
+    /// Fill in [`FrameTranslation::start_codon`].
+    pub report_start_codon: bool,
+    /// Translate internal TGA stop codons as selenocysteine (`U`), as in
+    /// genes with a SECIS element.
+    pub selenocysteine: bool,
+    /// Translate internal TAG stop codons as pyrrolysine (`O`).
+    pub pyrrolysine: bool,
+    /// Recode stop codons only in this frame (1 to 3 or -1 to -3).
+    pub recode_frame: Option<i8>,
+    /// Recode only the stop codons covering one of these 0-based
+    /// forward-strand positions, even as the last codon of the frame.
+    /// Without positions, every stop codon followed by another codon is
+    /// recoded.
+    pub recode_positions: Option<Vec<usize>>,
+}
+
+impl TranslationOptions {
+    /// The amino acid a stop `codon` of `frame` is recoded as, if any.
+    /// `site` is where the codon lies and `internal` whether another codon
+    /// follows it in the frame.
+    fn recoded_amino_acid(
+        &self,
+        codon: &[u8],
+        frame: i8,
+        site: &CodonSite,
+        internal: bool,
+    ) -> Option<u8> {
+        let aa = if self.selenocysteine && codon.eq_ignore_ascii_case(b"TGA") {
+            b'U'
+        } else if self.pyrrolysine && codon.eq_ignore_ascii_case(b"TAG") {
+            b'O'
+        } else {
+            return None;
+        };
+        if self.recode_frame.is_some_and(|recode_frame| recode_frame != frame) {
+            return None;
+        }
+        let selected = match &self.recode_positions {
+            Some(positions) => positions
+                .iter()
+                .any(|position| (site.start..site.end).contains(position)),
+            None => internal,
+        };
+        selected.then_some(aa)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
@@ -221,15 +441,63 @@ pub struct TranslationSummary {
     pub frames: Vec<FrameTranslation>,
 }
 
+/// Direction and offset of a reading frame numbered 1 to 3 or -1 to -3.
+pub(crate) fn frame_offset(frame: i8) -> Option<(bool, usize)> {
+    match frame {
+        1..=3 => Some((false, (frame - 1) as usize)),
+        -3..=-1 => Some((true, (-frame - 1) as usize)),
+        _ => None,
+    }
+}
+
+/// Forward-strand coordinates (0-based, end-exclusive) of the codon that
+/// `frame` (1 to 3 or -1 to -3) translates into amino acid `aa_index`, in a
+/// sequence of `seq_len` bases. `None` if there is no such codon.
+pub fn aa_to_nucleotide(seq_len: usize, frame: i8, aa_index: usize) -> Option<(usize, usize)> {
+    let (is_reverse, offset) = frame_offset(frame)?;
+    let begin = offset + 3 * aa_index;
+    let end = begin + 3;
+    if end > seq_len {
+        return None;
+    }
+    Some(if is_reverse {
+        (seq_len - end, seq_len - begin)
+    } else {
+        (begin, end)
+    })
+}
+
+/// The amino acid of `frame` whose codon covers forward-strand `position`,
+/// as `(aa_index, codon_position)` where `codon_position` (0 to 2) counts in
+/// the reading direction. `None` if the position lies outside the codons of
+/// the frame.
+pub fn nucleotide_to_aa(seq_len: usize, frame: i8, position: usize) -> Option<(usize, usize)> {
+    let (is_reverse, offset) = frame_offset(frame)?;
+    if position >= seq_len {
+        return None;
+    }
+    let working = if is_reverse {
+        seq_len - 1 - position
+    } else {
+        position
+    };
+    let in_frame = working.checked_sub(offset)?;
+    let aa_index = in_frame / 3;
+    if offset + 3 * aa_index + 3 > seq_len {
+        return None;
+    }
+    Some((aa_index, in_frame % 3))
+}
+
 pub fn translate_frame_internal(
     encoder: &CodonEncoder,
     sequence: &str,
     frame: i8,
-    stop_at_first: bool,
+    options: &TranslationOptions,
 ) -> Result<FrameTranslation, String> {
     let (is_reverse, offset) = match frame {
-        0 | 1 | 2 => (false, frame as usize),
-        -1 | -2 | -3 => (true, (-frame - 1) as usize),
+        0..=2 => (false, frame as usize),
+        -3..=-1 => (true, (-frame - 1) as usize),
         _ => return Err(format!("Invalid frame: {}", frame)),
     };
 
@@ -240,43 +508,87 @@ pub fn translate_frame_internal(
     };
 
     let seq_bytes = sequence.as_bytes();
+    // Sequences shorter than the frame offset (on either strand) have no
+    // codon in the frame.
+    if seq_bytes.len() < offset {
+        return Ok(FrameTranslation {
+            frame: reported_frame,
+            amino_acids: String::new(),
+            stops: Vec::new(),
+            ambiguous: Vec::new(),
+            stop_sites: Vec::new(),
+            ambiguous_sites: Vec::new(),
+            recoded: Vec::new(),
+            recoded_sites: Vec::new(),
+            start_codon: None,
+        });
+    }
     let mut buffer;
     let working = if is_reverse {
         buffer = Vec::with_capacity(seq_bytes.len());
         rev_compl_to(seq_bytes, &mut buffer);
         &buffer[offset..]
     } else {
-        if seq_bytes.len() < offset {
-            return Ok(FrameTranslation {
-                frame: reported_frame,
-                amino_acids: String::new(),
-                stops: Vec::new(),
-                ambiguous: Vec::new(),
-            });
-        }
         &seq_bytes[offset..]
     };
 
     let mut amino_acids = Vec::with_capacity(working.len() / 3);
     let mut stops = Vec::new();
     let mut ambiguous_positions = Vec::new();
+    let mut stop_sites = Vec::new();
+    let mut ambiguous_sites = Vec::new();
+    let mut recoded = Vec::new();
+    let mut recoded_sites = Vec::new();
+    let site = |aa_index: usize, codon: &[u8]| {
+        let (start, end) = aa_to_nucleotide(seq_bytes.len(), reported_frame, aa_index)
+            .expect("codon lies within the sequence");
+        CodonSite {
+            aa_index,
+            start,
+            end,
+            codon: String::from_utf8_lossy(codon).to_ascii_uppercase(),
+        }
+    };
+
+    let initiator = working.get(..3).filter(|codon| encoder.is_start_codon(codon));
+    let start_codon = initiator
+        .filter(|_| options.report_start_codon)
+        .map(|codon| String::from_utf8_lossy(codon).to_ascii_uppercase());
+    let initiator_as_methionine = initiator.is_some() && options.initiator_as_methionine;
 
     let mut index = 0;
     while index + 3 <= working.len() {
         let codon = &working[index..index + 3];
-        let (aa, ambiguous) = encoder.translate_triplet(codon);
-        let aa_char = aa as char;
+        let (mut aa, ambiguous) = if index == 0 && initiator_as_methionine {
+            (b'M', false)
+        } else {
+            encoder.translate_triplet(codon)
+        };
         let aa_index = amino_acids.len();
+        if aa == b'*' {
+            let codon_site = site(aa_index, codon);
+            let internal = index + 6 <= working.len();
+            if let Some(recoded_aa) =
+                options.recoded_amino_acid(codon, reported_frame, &codon_site, internal)
+            {
+                aa = recoded_aa;
+                recoded.push(aa_index);
+                recoded_sites.push(codon_site);
+            }
+        }
+        let aa_char = aa as char;
 
         if ambiguous {
             ambiguous_positions.push(aa_index);
+            ambiguous_sites.push(site(aa_index, codon));
         }
 
         amino_acids.push(aa);
 
         if aa_char == '*' {
             stops.push(aa_index);
-            if stop_at_first {
+            stop_sites.push(site(aa_index, codon));
+            if options.stop_at_first_stop {
                 break;
             }
         }
@@ -291,19 +603,23 @@ pub fn translate_frame_internal(
         amino_acids,
         stops,
         ambiguous: ambiguous_positions,
+        stop_sites,
+        ambiguous_sites,
+        recoded,
+        recoded_sites,
+        start_codon,
     })
 }
 
 pub fn translate_all_frames_internal(
     encoder: &CodonEncoder,
     sequence: &str,
-    stop_at_first: bool,
+    options: &TranslationOptions,
 ) -> TranslationSummary {
     let mut frames = Vec::with_capacity(6);
     for &frame in &[0, 1, 2, -1, -2, -3] {
-        match translate_frame_internal(encoder, sequence, frame, stop_at_first) {
-            Ok(result) => frames.push(result),
-            Err(_) => {}
+        if let Ok(result) = translate_frame_internal(encoder, sequence, frame, options) {
+            frames.push(result);
         }
     }
     TranslationSummary { frames }
@@ -327,6 +643,148 @@ mod tests {
         assert_eq!(encoder.translate_triplet(b"NNN"), (b'X', true));
     }
 
+    #[test]
+    fn test_alternative_genetic_codes() {
+        for code in GENETIC_CODES.iter() {
+            assert_eq!(code.amino_acids.len(), 64, "table {}", code.id);
+        }
+        let standard = CodonEncoder::for_table(1).unwrap();
+        assert_eq!(standard.translate_triplet(b"TGA"), (b'*', false));
+        assert_eq!(standard.translate_triplet(b"TTG"), (b'L', false));
+        assert_eq!(standard.translate_triplet(b"GGG"), (b'G', false));
+
+        let mycoplasma = CodonEncoder::for_table(4).unwrap();
+        assert_eq!(mycoplasma.translate_triplet(b"TGA"), (b'W', false));
+        // TGA and TGG both code for W in table 4.
+        assert_eq!(mycoplasma.translate_triplet(b"TGR"), (b'W', false));
+
+        let vertebrate_mito = CodonEncoder::for_table(2).unwrap();
+        assert_eq!(vertebrate_mito.translate_triplet(b"AGA"), (b'*', false));
+        assert_eq!(vertebrate_mito.translate_triplet(b"ATA"), (b'M', false));
+
+        let yeast_mito = CodonEncoder::for_table(3).unwrap();
+        assert_eq!(yeast_mito.translate_triplet(b"CTN"), (b'T', false));
+
+        assert!(CodonEncoder::for_table(7).is_err());
+        assert!(CodonEncoder::for_table(34).is_err());
+    }
+
+    #[test]
+    fn test_alternative_start_codons() {
+        let standard = CodonEncoder::for_table(1).unwrap();
+        let bacterial = CodonEncoder::for_table(11).unwrap();
+        assert!(standard.is_start_codon(b"ATG"));
+        assert!(standard.is_start_codon(b"ttg"));
+        assert!(!standard.is_start_codon(b"GTG"));
+        assert!(bacterial.is_start_codon(b"GTG"));
+        assert!(bacterial.is_start_codon(b"ATT"));
+        assert!(!bacterial.is_start_codon(b"ATN"));
+
+        let options = TranslationOptions {
+            initiator_as_methionine: true,
+            report_start_codon: true,
+            ..TranslationOptions::default()
+        };
+        let result = translate_frame_internal(&bacterial, "GTGAAAGTGTAA", 0, &options).unwrap();
+        assert_eq!(result.amino_acids, "MKV*");
+        assert_eq!(result.start_codon.as_deref(), Some("GTG"));
+        let result = translate_frame_internal(&standard, "GTGAAAGTGTAA", 0, &options).unwrap();
+        assert_eq!(result.amino_acids, "VKV*");
+        assert_eq!(result.start_codon, None);
+        let options = TranslationOptions::default();
+        let result = translate_frame_internal(&bacterial, "GTGAAAGTGTAA", 0, &options).unwrap();
+        assert_eq!(result.amino_acids, "VKV*");
+        assert_eq!(result.start_codon, None);
+    }
+
+    #[test]
+    fn test_codon_sites() {
+        let encoder = CodonEncoder::mk_encoder();
+        let options = TranslationOptions::default();
+        // Frame -1 reads TTA CAN CAT: L X H.
+        let seq = "ATGNTGTAA";
+        let result = translate_frame_internal(&encoder, seq, -1, &options).unwrap();
+        assert_eq!(result.amino_acids, "LXH");
+        assert_eq!(result.ambiguous, vec![1]);
+        let site = &result.ambiguous_sites[0];
+        assert_eq!((site.start, site.end, site.codon.as_str()), (3, 6, "CAN"));
+
+        let result = translate_frame_internal(&encoder, seq, 0, &options).unwrap();
+        assert_eq!(result.stops, vec![2]);
+        let site = &result.stop_sites[0];
+        assert_eq!((site.aa_index, site.start, site.end), (2, 6, 9));
+        assert_eq!(site.codon, "TAA");
+    }
+
+    #[test]
+    fn test_recodes_selenocysteine_and_pyrrolysine() {
+        let encoder = CodonEncoder::mk_encoder();
+        // ATG TGA TAG AAA TGA: the last stop is not internal.
+        let seq = "ATGTGATAGAAATGA";
+        let options = TranslationOptions {
+            selenocysteine: true,
+            pyrrolysine: true,
+            ..TranslationOptions::default()
+        };
+        let result = translate_frame_internal(&encoder, seq, 0, &options).unwrap();
+        assert_eq!(result.amino_acids, "MUOK*");
+        assert_eq!(result.recoded, vec![1, 2]);
+        assert_eq!(result.stops, vec![4]);
+        let site = &result.recoded_sites[0];
+        assert_eq!((site.start, site.end, site.codon.as_str()), (3, 6, "TGA"));
+
+        let selenocysteine_only = TranslationOptions {
+            selenocysteine: true,
+            stop_at_first_stop: true,
+            ..TranslationOptions::default()
+        };
+        let result = translate_frame_internal(&encoder, seq, 0, &selenocysteine_only).unwrap();
+        assert_eq!(result.amino_acids, "MU*");
+        assert_eq!(result.stops, vec![2]);
+
+        // Only the codon at position 7, and only in frame +1.
+        let positions = TranslationOptions {
+            recode_positions: Some(vec![7, 13]),
+            recode_frame: Some(1),
+            ..options.clone()
+        };
+        let summary = translate_all_frames_internal(&encoder, seq, &positions);
+        assert_eq!(summary.frames[0].amino_acids, "M*OKU");
+        assert_eq!(summary.frames[0].recoded, vec![2, 4]);
+        assert_eq!(summary.frames[0].stops, vec![1]);
+        assert!(summary.frames[1..].iter().all(|frame| frame.recoded.is_empty()));
+
+        // TGA is tryptophan in table 4, not a stop to recode.
+        let mycoplasma = CodonEncoder::for_table(4).unwrap();
+        let result = translate_frame_internal(&mycoplasma, seq, 0, &options).unwrap();
+        assert_eq!(result.amino_acids, "MWOKW");
+        assert_eq!(result.recoded, vec![2]);
+    }
+
+    #[test]
+    fn test_frame_coordinate_mapping() {
+        assert_eq!(aa_to_nucleotide(10, 1, 0), Some((0, 3)));
+        assert_eq!(aa_to_nucleotide(10, 2, 2), Some((7, 10)));
+        assert_eq!(aa_to_nucleotide(10, 3, 2), None);
+        assert_eq!(aa_to_nucleotide(10, -1, 0), Some((7, 10)));
+        assert_eq!(aa_to_nucleotide(10, -2, 1), Some((3, 6)));
+        assert_eq!(aa_to_nucleotide(10, 0, 0), None);
+
+        assert_eq!(nucleotide_to_aa(10, 2, 0), None);
+        assert_eq!(nucleotide_to_aa(10, 2, 8), Some((2, 1)));
+        assert_eq!(nucleotide_to_aa(10, -1, 9), Some((0, 0)));
+        assert_eq!(nucleotide_to_aa(10, -1, 0), None);
+        for frame in [1, 2, 3, -1, -2, -3] {
+            for aa_index in 0..3 {
+                if let Some((start, end)) = aa_to_nucleotide(11, frame, aa_index) {
+                    for position in start..end {
+                        assert_eq!(nucleotide_to_aa(11, frame, position).unwrap().0, aa_index);
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_rev_complement() {
         let mut buffer = Vec::new();
@@ -337,7 +795,8 @@ mod tests {
     #[test]
     fn test_translate_frame_forward() {
         let encoder = CodonEncoder::mk_encoder();
-        let result = translate_frame_internal(&encoder, "ATGGCC", 0, false).unwrap();
+        let options = TranslationOptions::default();
+        let result = translate_frame_internal(&encoder, "ATGGCC", 0, &options).unwrap();
         assert_eq!(result.amino_acids, "MA");
         assert!(result.stops.is_empty());
         assert!(result.ambiguous.is_empty());
@@ -347,7 +806,8 @@ mod tests {
     #[test]
     fn test_translate_frame_reverse() {
         let encoder = CodonEncoder::mk_encoder();
-        let result = translate_frame_internal(&encoder, "ATGGCC", -1, false).unwrap();
+        let options = TranslationOptions::default();
+        let result = translate_frame_internal(&encoder, "ATGGCC", -1, &options).unwrap();
         assert_eq!(result.amino_acids, "GH");
         assert_eq!(result.frame, -1);
     }
@@ -355,7 +815,11 @@ mod tests {
     #[test]
     fn test_translate_frame_stop_at_first() {
         let encoder = CodonEncoder::mk_encoder();
-        let result = translate_frame_internal(&encoder, "ATGTAATTT", 0, true).unwrap();
+        let options = TranslationOptions {
+            stop_at_first_stop: true,
+            ..TranslationOptions::default()
+        };
+        let result = translate_frame_internal(&encoder, "ATGTAATTT", 0, &options).unwrap();
         assert_eq!(result.amino_acids, "M*");
         assert_eq!(result.stops, vec![1]);
     }
@@ -363,15 +827,31 @@ mod tests {
     #[test]
     fn test_translate_frame_with_ambiguity_marks_position() {
         let encoder = CodonEncoder::mk_encoder();
-        let result = translate_frame_internal(&encoder, "ATNCCC", 0, false).unwrap();
+        let options = TranslationOptions::default();
+        let result = translate_frame_internal(&encoder, "ATNCCC", 0, &options).unwrap();
         assert_eq!(result.amino_acids, "XP");
         assert_eq!(result.ambiguous, vec![0]);
     }
 
+    #[test]
+    fn test_translate_short_sequences() {
+        let encoder = CodonEncoder::mk_encoder();
+        let options = TranslationOptions::default();
+        for seq in ["", "A", "AT", "ATG"] {
+            let summary = translate_all_frames_internal(&encoder, seq, &options);
+            assert_eq!(summary.frames.len(), 6);
+            for frame in &summary.frames {
+                let expected = if seq.len() == 3 && frame.frame.abs() == 1 { 1 } else { 0 };
+                assert_eq!(frame.amino_acids.len(), expected, "{:?} {}", seq, frame.frame);
+            }
+        }
+    }
+
     #[test]
     fn test_translate_all_frames() {
         let encoder = CodonEncoder::mk_encoder();
-        let summary = translate_all_frames_internal(&encoder, "ATGGCC", false);
+        let options = TranslationOptions::default();
+        let summary = translate_all_frames_internal(&encoder, "ATGGCC", &options);
         assert_eq!(summary.frames.len(), 6);
         assert_eq!(summary.frames[0].frame, 1);
         assert_eq!(summary.frames[3].frame, -1);
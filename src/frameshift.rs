@@ -0,0 +1,385 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fasta::FastaRecord;
+use crate::fna2faa::{frame_offset, rev_compl_to, CodonEncoder};
+use crate::translation::sanitize_sequence;
+
+/// Marks a frameshift in [`FrameshiftTranslation::protein`].
+pub const FRAMESHIFT_MARKER: char = '!';
+
+/// Scoring of [`translate_with_frameshifts`]. A path through the sequence
+/// switches frame only where that avoids more stop codons than the switch
+/// costs: with the defaults, where it removes at least two stops.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FrameshiftOptions {
+    /// Frame the translation starts in, 1 to 3 or -1 to -3.
+    pub frame: i8,
+    /// NCBI translation table ID.
+    pub genetic_code: u8,
+    /// Cost of each frame switch.
+    pub shift_penalty: f64,
+    /// Cost of each internal stop codon; a stop as the last codon is free.
+    pub stop_penalty: f64,
+}
+
+impl Default for FrameshiftOptions {
+    fn default() -> Self {
+        FrameshiftOptions {
+            frame: 1,
+            genetic_code: 1,
+            shift_penalty: 15.0,
+            stop_penalty: 10.0,
+        }
+    }
+}
+
+/// A frame switch between two consecutive codons.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Frameshift {
+    /// Index of the marker in [`FrameshiftTranslation::protein`].
+    pub protein_index: usize,
+    /// 0-based forward-strand position of the base skipped (`shift` +1, an
+    /// extra base in the sequence) or read twice (`shift` -1, a missing
+    /// base).
+    pub position: usize,
+    pub shift: i8,
+    /// Frame read after the switch.
+    pub frame: i8,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct FrameshiftTranslation {
+    /// Frame the translation starts in.
+    pub frame: i8,
+    /// The corrected protein, with [`FRAMESHIFT_MARKER`] at each frameshift.
+    pub protein: String,
+    pub frameshifts: Vec<Frameshift>,
+    /// Indices in `protein` of the stop codons left before the last codon.
+    pub internal_stops: Vec<usize>,
+    /// Whether the sequence needed a frameshift or still has internal stop
+    /// codons, as pseudogenes do.
+    pub pseudogene: bool,
+}
+
+/// Best-scoring path of codons through `working` (the sequence read on the
+/// strand of the frame), starting at `offset`. Each codon starts 3 bases
+/// after the previous one, or 2 or 4 bases after it at a frame switch.
+/// Returns the codon starts in order.
+fn best_path(
+    encoder: &CodonEncoder,
+    working: &[u8],
+    offset: usize,
+    options: &FrameshiftOptions,
+) -> Vec<usize> {
+    if working.len() < offset + 3 {
+        return Vec::new();
+    }
+    let last = working.len() - 3;
+    let is_stop = |start: usize| encoder.translate_triplet(&working[start..start + 3]).0 == b'*';
+    let mut scores = vec![f64::NEG_INFINITY; last + 1];
+    let mut shifts = vec![0usize; last + 1];
+    let mut steps = vec![0usize; last + 1];
+    for start in offset..=last {
+        let (mut best, mut best_shifts, mut best_step) = if start == offset {
+            (0.0, 0, 0)
+        } else {
+            (f64::NEG_INFINITY, 0, 0)
+        };
+        // Among equally good paths, take the one with the fewest switches
+        // and then the latest switch, i.e. keep reading the first frame
+        // while it is no worse.
+        let shift_penalty = options.shift_penalty;
+        for (step, penalty) in [(3, 0.0), (2, shift_penalty), (4, shift_penalty)] {
+            if start < offset + step || scores[start - step] == f64::NEG_INFINITY {
+                continue;
+            }
+            let score = scores[start - step] - penalty;
+            let count = shifts[start - step] + usize::from(step != 3);
+            let better = score > best
+                || (score == best
+                    && (count < best_shifts || (count == best_shifts && step != 3)));
+            if better {
+                (best, best_shifts, best_step) = (score, count, step);
+            }
+        }
+        if is_stop(start) {
+            best -= options.stop_penalty;
+        }
+        scores[start] = best;
+        shifts[start] = best_shifts;
+        steps[start] = best_step;
+    }
+
+    // The path ends with a codon leaving fewer than 3 bases after it; the
+    // stop that ends a gene is not penalized. With finite penalties the
+    // first codon, and so some end, is always reachable.
+    let first_end = last.saturating_sub(2).max(offset);
+    let Some(end) = (first_end..=last)
+        .rev()
+        .filter(|&start| scores[start].is_finite())
+        .max_by(|&a, &b| {
+            let final_score = |start: usize| {
+                scores[start] + if is_stop(start) { options.stop_penalty } else { 0.0 }
+            };
+            final_score(a).total_cmp(&final_score(b))
+        })
+    else {
+        return Vec::new();
+    };
+    let mut path = vec![end];
+    let mut start = end;
+    while steps[start] != 0 {
+        start -= steps[start];
+        path.push(start);
+    }
+    path.reverse();
+    path
+}
+
+/// Translate `seq` from `options.frame`, switching frame where a base
+/// appears to be missing or in excess (a dynamic programming trade-off
+/// between stop codons and frame switches). Whitespace and gaps are ignored.
+pub fn translate_with_frameshifts(
+    seq: &str,
+    options: &FrameshiftOptions,
+) -> Result<FrameshiftTranslation, String> {
+    for (name, penalty) in [
+        ("shift", options.shift_penalty),
+        ("stop", options.stop_penalty),
+    ] {
+        if !penalty.is_finite() || penalty < 0.0 {
+            return Err(format!(
+                "The {} penalty must be a finite, non-negative number. Got {}",
+                name, penalty
+            ));
+        }
+    }
+    let sanitized = sanitize_sequence(seq)?;
+    let encoder = CodonEncoder::for_table(options.genetic_code)?;
+    let (is_reverse, offset) = frame_offset(options.frame).ok_or_else(|| {
+        format!("Frame must be between -3 and 3 (excluding 0). Got {}", options.frame)
+    })?;
+    let working = if is_reverse {
+        let mut buffer = Vec::with_capacity(sanitized.len());
+        rev_compl_to(sanitized.as_bytes(), &mut buffer);
+        buffer
+    } else {
+        sanitized.into_bytes()
+    };
+    let to_forward = |position: usize| {
+        if is_reverse {
+            working.len() - 1 - position
+        } else {
+            position
+        }
+    };
+    let frame_of = |start: usize| {
+        let frame = (start % 3) as i8 + 1;
+        if is_reverse {
+            -frame
+        } else {
+            frame
+        }
+    };
+
+    let path = best_path(&encoder, &working, offset, options);
+    let mut protein = String::with_capacity(path.len());
+    let mut frameshifts = Vec::new();
+    let mut internal_stops = Vec::new();
+    for (index, &start) in path.iter().enumerate() {
+        if index > 0 && start != path[index - 1] + 3 {
+            let (shift, position) = if start > path[index - 1] + 3 {
+                (1, start - 1)
+            } else {
+                (-1, start)
+            };
+            frameshifts.push(Frameshift {
+                protein_index: protein.len(),
+                position: to_forward(position),
+                shift,
+                frame: frame_of(start),
+            });
+            protein.push(FRAMESHIFT_MARKER);
+        }
+        let (aa, _) = encoder.translate_triplet(&working[start..start + 3]);
+        if aa == b'*' && index + 1 < path.len() {
+            internal_stops.push(protein.len());
+        }
+        protein.push(aa as char);
+    }
+    Ok(FrameshiftTranslation {
+        frame: options.frame,
+        pseudogene: !frameshifts.is_empty() || !internal_stops.is_empty(),
+        protein,
+        frameshifts,
+        internal_stops,
+    })
+}
+
+/// The frameshift-tolerant translation of one FASTA record.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct FrameshiftRecord {
+    pub id: String,
+    pub description: String,
+    pub translation: FrameshiftTranslation,
+}
+
+impl FrameshiftRecord {
+    /// A protein FASTA entry; likely pseudogenes are flagged at the end of
+    /// the header, e.g. `>seq1 [pseudo frameshifts=1 internal_stops=0]`.
+    pub fn to_fasta(&self) -> String {
+        let id = if self.id.is_empty() { "sequence" } else { &self.id };
+        let mut fasta = format!(">{}", id);
+        if !self.description.is_empty() {
+            fasta.push(' ');
+            fasta.push_str(&self.description);
+        }
+        let translation = &self.translation;
+        if translation.pseudogene {
+            fasta.push_str(&format!(
+                " [pseudo frameshifts={} internal_stops={}]",
+                translation.frameshifts.len(),
+                translation.internal_stops.len()
+            ));
+        }
+        fasta.push('\n');
+        for line in translation.protein.as_bytes().chunks(60) {
+            fasta.push_str(&String::from_utf8_lossy(line));
+            fasta.push('\n');
+        }
+        fasta
+    }
+}
+
+/// [`translate_with_frameshifts`] for each record.
+pub fn translate_records_with_frameshifts(
+    records: &[FastaRecord],
+    options: &FrameshiftOptions,
+) -> Result<Vec<FrameshiftRecord>, String> {
+    records
+        .iter()
+        .map(|record| {
+            let translation = translate_with_frameshifts(&record.sequence, options)
+                .map_err(|err| format!("line {}: {}", record.line, err))?;
+            Ok(FrameshiftRecord {
+                id: record.id.clone(),
+                description: record.description.clone(),
+                translation,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fasta::parse_fasta;
+
+    // MAEKLGIRADS, then LKDK three times, whose CTAAAAGATAAA reads TAA
+    // in both other frames, then LKEHWAKLED and a stop.
+    const GENE: &str = concat!(
+        "ATGGCTGAAAAACTGGGCATTCGTGCGGATAGC",
+        "CTAAAAGATAAACTAAAAGATAAACTAAAAGATAAA",
+        "CTGAAAGAACATTGGGCGAAACTGGAAGATTAA"
+    );
+
+    fn options(frame: i8) -> FrameshiftOptions {
+        FrameshiftOptions {
+            frame,
+            ..FrameshiftOptions::default()
+        }
+    }
+
+    fn plain(protein: &str) -> String {
+        protein.replace(FRAMESHIFT_MARKER, "")
+    }
+
+    #[test]
+    fn intact_genes_are_not_shifted() {
+        let intact = translate_with_frameshifts(GENE, &options(1)).unwrap();
+        assert!(!intact.pseudogene);
+        assert!(intact.frameshifts.is_empty());
+        assert_eq!(intact.protein, "MAEKLGIRADSLKDKLKDKLKDKLKEHWAKLED*");
+
+        let mut reverse = Vec::new();
+        rev_compl_to(GENE.as_bytes(), &mut reverse);
+        let reverse = String::from_utf8(reverse).unwrap();
+        let translated = translate_with_frameshifts(&reverse, &options(-1)).unwrap();
+        assert_eq!(translated.protein, intact.protein);
+        assert_eq!(translated.frame, -1);
+    }
+
+    #[test]
+    fn corrects_insertions_and_deletions() {
+        let intact = translate_with_frameshifts(GENE, &options(1)).unwrap().protein;
+        // An extra base after the eleventh codon.
+        let inserted = format!("{}A{}", &GENE[..33], &GENE[33..]);
+        let shifted = translate_with_frameshifts(&inserted, &options(1)).unwrap();
+        assert!(shifted.pseudogene);
+        assert_eq!(shifted.frameshifts.len(), 1);
+        let shift = &shifted.frameshifts[0];
+        assert_eq!((shift.shift, shift.frame), (1, 2));
+        // Anywhere between the insertion and the first stop it causes.
+        assert!((33..=42).contains(&shift.position));
+        assert_eq!(shifted.protein.chars().nth(shift.protein_index), Some(FRAMESHIFT_MARKER));
+        assert!(shifted.internal_stops.is_empty());
+        assert!(plain(&shifted.protein).ends_with("LKEHWAKLED*"));
+        assert_eq!(plain(&shifted.protein).len(), intact.len());
+
+        // A missing base, on the reverse strand.
+        let deleted = format!("{}{}", &GENE[..40], &GENE[41..]);
+        let mut reverse = Vec::new();
+        rev_compl_to(deleted.as_bytes(), &mut reverse);
+        let reverse = String::from_utf8(reverse).unwrap();
+        let shifted = translate_with_frameshifts(&reverse, &options(-1)).unwrap();
+        assert_eq!(shifted.frameshifts.len(), 1);
+        let shift = &shifted.frameshifts[0];
+        assert_eq!(shift.shift, -1);
+        assert_eq!(shift.frame, -3);
+        let gene_position = reverse.len() - 1 - shift.position;
+        assert!((40..=44).contains(&gene_position));
+        assert!(plain(&shifted.protein).ends_with("LKEHWAKLED*"));
+    }
+
+    #[test]
+    fn single_stops_are_kept() {
+        // One premature stop costs less than two frame switches.
+        let stopped = format!("{}TGA{}", &GENE[..30], &GENE[33..]);
+        let translated = translate_with_frameshifts(&stopped, &options(1)).unwrap();
+        assert!(translated.frameshifts.is_empty());
+        assert_eq!(translated.internal_stops, vec![10]);
+        assert!(translated.pseudogene);
+    }
+
+    #[test]
+    fn translates_records() {
+        let (head, tail) = GENE.split_at(33);
+        let fasta = format!(">good intact\n{}\n>bad\n{}A{}\n>short\nAT\n", GENE, head, tail);
+        let records = translate_records_with_frameshifts(
+            &parse_fasta(&fasta).unwrap(),
+            &FrameshiftOptions::default(),
+        )
+        .unwrap();
+        assert!(records[0].to_fasta().starts_with(">good intact\nMAEK"));
+        let header = ">bad [pseudo frameshifts=1 internal_stops=0]\n";
+        assert!(records[1].to_fasta().starts_with(header));
+        assert_eq!(records[2].translation.protein, "");
+        assert!(translate_with_frameshifts("ACGT", &options(4)).is_err());
+        for penalty in [f64::INFINITY, f64::NAN, -1.0] {
+            let stop = FrameshiftOptions {
+                stop_penalty: penalty,
+                ..FrameshiftOptions::default()
+            };
+            assert!(translate_with_frameshifts("TAAATGGCC", &stop).is_err());
+            let shift = FrameshiftOptions {
+                shift_penalty: penalty,
+                ..FrameshiftOptions::default()
+            };
+            assert!(translate_with_frameshifts("TAAATGGCC", &shift).is_err());
+        }
+        let records = parse_fasta(">a\nACGT\n>b\nACJT\n").unwrap();
+        let err = translate_records_with_frameshifts(&records, &FrameshiftOptions::default());
+        assert!(err.unwrap_err().starts_with("line 3:"));
+    }
+}
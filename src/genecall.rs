@@ -0,0 +1,829 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::alphabet::canonical_nucleotide_index;
+use crate::fna2faa::{rev_compl_to, CodonEncoder};
+use crate::orf::Strand;
+use crate::translation::sanitize_sequence;
+
+/// Start codons considered by the gene caller (as in Prodigal, whatever the
+/// genetic code).
+const START_CODONS: [&[u8]; 3] = [b"ATG", b"GTG", b"TTG"];
+/// Shine-Dalgarno motifs searched upstream of start codons: the substrings
+/// of AGGAGG of at least three bases, longest first.
+const RBS_MOTIFS: [&str; 9] = [
+    "AGGAGG", "AGGAG", "GGAGG", "AGGA", "GGAG", "GAGG", "AGG", "GGA", "GAG",
+];
+/// Spacer bins between the end of the motif and the start codon.
+const RBS_SPACERS: [&str; 4] = ["3-4bp", "5-10bp", "11-12bp", "13-15bp"];
+/// RBS class of a start without any motif; the other classes are
+/// `motif * RBS_SPACERS.len() + spacer`.
+const NO_RBS: usize = RBS_MOTIFS.len() * RBS_SPACERS.len();
+/// Bases upstream of a start codon searched for a motif.
+const RBS_WINDOW: usize = 21;
+/// Length, in codons, of the windows of the GC frame plot.
+const GC_WINDOW: usize = 40;
+/// Rounds of start codon and RBS training before the final calls.
+const START_TRAINING_ROUNDS: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct GeneCallOptions {
+    /// NCBI translation table ID.
+    pub genetic_code: u8,
+    /// Minimum gene length in nucleotides, stop codon included.
+    pub min_gene_length: usize,
+    /// Longest overlap allowed between two genes, in nucleotides.
+    pub max_overlap: usize,
+    /// Do not call genes that run off the ends of a contig.
+    pub closed_ends: bool,
+}
+
+impl Default for GeneCallOptions {
+    fn default() -> Self {
+        GeneCallOptions {
+            genetic_code: 11,
+            min_gene_length: 90,
+            max_overlap: 60,
+            closed_ends: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Contig {
+    pub name: String,
+    pub sequence: String,
+}
+
+/// A predicted protein-coding gene. `start` and `end` are 0-based,
+/// end-exclusive coordinates on the forward strand of the contig (whitespace
+/// and gaps removed) and include the stop codon when there is one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Gene {
+    /// First word of the contig name.
+    pub seqid: String,
+    pub strand: Strand,
+    pub start: usize,
+    pub end: usize,
+    /// The gene runs off the 5' end of the contig (no start codon).
+    pub partial_start: bool,
+    /// The gene runs off the 3' end of the contig (no stop codon).
+    pub partial_end: bool,
+    pub start_codon: Option<String>,
+    pub rbs_motif: Option<String>,
+    pub rbs_spacer: Option<String>,
+    pub gc_content: f64,
+    /// Log-odds of the codons under the trained coding model.
+    pub coding_score: f64,
+    /// Log-odds of the start codon and RBS motif.
+    pub start_score: f64,
+    pub score: f64,
+    /// Translation without the stop codon; the start codon is translated as M.
+    pub protein: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GenePrediction {
+    pub gc_content: f64,
+    /// Relative frequency with which each codon position is the most GC-rich
+    /// one in the training ORFs, scaled so that no bias is 1.
+    pub gc_frame_bias: [f64; 3],
+    /// Number of genes the coding model was trained on.
+    pub training_genes: usize,
+    pub genes: Vec<Gene>,
+}
+
+/// One of the six reading frames of a contig.
+struct Frame<'a> {
+    contig: usize,
+    strand: Strand,
+    offset: usize,
+    /// The contig as read on `strand`.
+    working: &'a [u8],
+    /// Codon indices (bases in A, C, G, T order); `None` if ambiguous.
+    codons: Vec<Option<usize>>,
+    /// Positions of the stop codons, followed by the number of codons.
+    stops: Vec<usize>,
+    /// Most GC-rich codon position in the window starting at each codon.
+    gc_max: Vec<Option<usize>>,
+}
+
+impl<'a> Frame<'a> {
+    fn new(
+        encoder: &CodonEncoder,
+        contig: usize,
+        strand: Strand,
+        offset: usize,
+        working: &'a [u8],
+    ) -> Self {
+        let raw: Vec<&[u8]> = working
+            .get(offset..)
+            .unwrap_or_default()
+            .chunks_exact(3)
+            .collect();
+        let codons = raw
+            .iter()
+            .map(|codon| {
+                codon.iter().try_fold(0, |idx, &nt| {
+                    canonical_nucleotide_index(nt).map(|base| (idx << 2) | base)
+                })
+            })
+            .collect();
+        let stops = raw
+            .iter()
+            .enumerate()
+            .filter(|(_, codon)| encoder.translate_triplet(codon).0 == b'*')
+            .map(|(idx, _)| idx)
+            .chain([raw.len()])
+            .collect();
+
+        let gc: Vec<[usize; 3]> = raw
+            .iter()
+            .map(|codon| {
+                let mut counts = [0; 3];
+                for (pos, nt) in codon.iter().enumerate() {
+                    counts[pos] = usize::from(matches!(nt, b'G' | b'C' | b'S'));
+                }
+                counts
+            })
+            .collect();
+        let mut window = [0usize; 3];
+        let mut gc_max = vec![None; raw.len()];
+        for k in (0..raw.len()).rev() {
+            for pos in 0..3 {
+                window[pos] += gc[k][pos];
+                if let Some(dropped) = gc.get(k + GC_WINDOW) {
+                    window[pos] -= dropped[pos];
+                }
+            }
+            let best = *window.iter().max().unwrap();
+            if best > 0 && window.iter().filter(|&&count| count == best).count() == 1 {
+                gc_max[k] = window.iter().position(|&count| count == best);
+            }
+        }
+
+        Frame {
+            contig,
+            strand,
+            offset,
+            working,
+            codons,
+            stops,
+            gc_max,
+        }
+    }
+
+    fn codon(&self, idx: usize) -> &[u8] {
+        let begin = self.offset + 3 * idx;
+        &self.working[begin..begin + 3]
+    }
+
+    fn start_type(&self, idx: usize) -> Option<usize> {
+        START_CODONS
+            .iter()
+            .position(|start| *start == self.codon(idx))
+    }
+
+    /// Stop-free stretches as `(first codon, stop codon)`; the last one ends
+    /// at the number of codons.
+    fn regions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let mut region_start = 0;
+        self.stops.iter().map(move |&stop| {
+            let region = (region_start, stop);
+            region_start = stop + 1;
+            region
+        })
+    }
+
+    /// Working-strand bases of codons `from..to` plus the stop codon, if any.
+    fn bases(&self, from: usize, to: usize) -> (usize, usize) {
+        let stop = usize::from(to < self.codons.len());
+        (self.offset + 3 * from, self.offset + 3 * (to + stop))
+    }
+
+    /// Forward-strand coordinates of codons `from..to` plus the stop codon.
+    fn span(&self, from: usize, to: usize) -> (usize, usize) {
+        let (begin, finish) = self.bases(from, to);
+        match self.strand {
+            Strand::Forward => (begin, finish),
+            Strand::Reverse => (self.working.len() - finish, self.working.len() - begin),
+        }
+    }
+
+    /// RBS classes of the motifs found upstream of the start at codon `idx`,
+    /// in increasing order.
+    fn rbs_sites(&self, idx: usize) -> Vec<usize> {
+        let start = self.offset + 3 * idx;
+        let window = &self.working[start.saturating_sub(RBS_WINDOW)..start];
+        let mut sites = Vec::new();
+        for (motif_idx, motif) in RBS_MOTIFS.iter().enumerate() {
+            for (pos, candidate) in window.windows(motif.len()).enumerate() {
+                if candidate != motif.as_bytes() {
+                    continue;
+                }
+                let spacer = window.len() - pos - motif.len();
+                let bin = match spacer {
+                    3..=4 => 0,
+                    5..=10 => 1,
+                    11..=12 => 2,
+                    13..=15 => 3,
+                    _ => continue,
+                };
+                sites.push(motif_idx * RBS_SPACERS.len() + bin);
+            }
+        }
+        sites.sort_unstable();
+        sites
+    }
+}
+
+/// Log-odds scores learned from the input.
+struct Model {
+    codons: [f64; 64],
+    starts: [f64; START_CODONS.len()],
+    rbs: [f64; NO_RBS + 1],
+}
+
+impl Model {
+    /// Best scoring RBS class among `sites` (or no motif); ties go to the
+    /// longer motif.
+    fn choose_rbs(&self, sites: &[usize]) -> usize {
+        let mut best = NO_RBS;
+        for &site in sites.iter().rev() {
+            if self.rbs[site] >= self.rbs[best] {
+                best = site;
+            }
+        }
+        best
+    }
+}
+
+/// A possible gene: codons `from..to` of a frame, followed by the stop
+/// codon at `to` unless the frame ends there.
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    frame: usize,
+    from: usize,
+    to: usize,
+    /// Index into [`START_CODONS`]; `None` for genes running off the 5' end.
+    start_type: Option<usize>,
+    rbs: usize,
+    start_score: f64,
+    score: f64,
+}
+
+/// Candidate genes of all frames; with `longest_only`, only the most
+/// upstream start of each stop codon.
+fn candidates(frames: &[Frame], options: &GeneCallOptions, longest_only: bool) -> Vec<Candidate> {
+    let mut found = Vec::new();
+    for (frame_idx, frame) in frames.iter().enumerate() {
+        for (region_start, stop) in frame.regions() {
+            let partial_end = stop == frame.codons.len();
+            if partial_end && options.closed_ends {
+                continue;
+            }
+            let long_enough = |from: usize| {
+                let (begin, finish) = frame.bases(from, stop);
+                finish - begin >= options.min_gene_length
+            };
+            let mut push = |from: usize, start_type: Option<usize>| {
+                found.push(Candidate {
+                    frame: frame_idx,
+                    from,
+                    to: stop,
+                    start_type,
+                    rbs: NO_RBS,
+                    start_score: 0.0,
+                    score: 0.0,
+                });
+            };
+            let edge = region_start == 0
+                && stop > 0
+                && !options.closed_ends
+                && frame.start_type(0).is_none();
+            if edge && long_enough(0) {
+                push(0, None);
+                if longest_only {
+                    continue;
+                }
+            }
+            for from in region_start..stop {
+                if !long_enough(from) {
+                    break;
+                }
+                if let Some(start_type) = frame.start_type(from) {
+                    push(from, Some(start_type));
+                    if longest_only {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Prefix sums of a per-codon score over each frame.
+fn prefix_sums<F>(frames: &[Frame], codon_score: F) -> Vec<Vec<f64>>
+where
+    F: Fn(&Frame, usize) -> f64,
+{
+    frames
+        .iter()
+        .map(|frame| {
+            let mut sums = Vec::with_capacity(frame.codons.len() + 1);
+            let mut total = 0.0;
+            sums.push(total);
+            for idx in 0..frame.codons.len() {
+                total += codon_score(frame, idx);
+                sums.push(total);
+            }
+            sums
+        })
+        .collect()
+}
+
+/// Score `candidates` with the coding prefix sums and the start model.
+fn score_candidates(
+    frames: &[Frame],
+    candidates: &mut [Candidate],
+    coding: &[Vec<f64>],
+    model: &Model,
+) {
+    for candidate in candidates.iter_mut() {
+        let frame = &frames[candidate.frame];
+        candidate.start_score = match candidate.start_type {
+            Some(start_type) => {
+                candidate.rbs = model.choose_rbs(&frame.rbs_sites(candidate.from));
+                model.starts[start_type] + model.rbs[candidate.rbs]
+            }
+            None => 0.0,
+        };
+        let sums = &coding[candidate.frame];
+        candidate.score = sums[candidate.to] - sums[candidate.from] + candidate.start_score;
+    }
+}
+
+/// Highest scoring set of positive candidates in which no two genes overlap
+/// by more than `max_overlap` bases. As every candidate is longer than
+/// `max_overlap`, this is a weighted interval scheduling over the
+/// candidates sorted by end, solved separately for each contig.
+fn select(frames: &[Frame], candidates: &[Candidate], max_overlap: usize) -> Vec<Candidate> {
+    let mut by_end: Vec<(usize, usize, usize, Candidate)> = candidates
+        .iter()
+        .filter(|candidate| candidate.score > 0.0)
+        .map(|candidate| {
+            let frame = &frames[candidate.frame];
+            let (start, end) = frame.span(candidate.from, candidate.to);
+            (frame.contig, end, start, *candidate)
+        })
+        .collect();
+    by_end.sort_by_key(|&(contig, end, start, _)| (contig, end, start));
+
+    let mut selected = Vec::new();
+    let mut contig_begin = 0;
+    while contig_begin < by_end.len() {
+        let contig = by_end[contig_begin].0;
+        let contig_end =
+            contig_begin + by_end[contig_begin..].partition_point(|&(other, ..)| other == contig);
+        let genes = &by_end[contig_begin..contig_end];
+
+        // best[k]: best total over the first k genes, with the gene that
+        // ends that chain.
+        let mut best: Vec<(f64, Option<usize>)> = vec![(0.0, None)];
+        let mut previous = Vec::with_capacity(genes.len());
+        for (idx, &(_, _, start, candidate)) in genes.iter().enumerate() {
+            let compatible = genes.partition_point(|&(_, end, ..)| end <= start + max_overlap);
+            let (before, chain) = best[compatible];
+            previous.push(chain);
+            let total = before + candidate.score;
+            best.push(if total > best[idx].0 {
+                (total, Some(idx))
+            } else {
+                best[idx]
+            });
+        }
+        let mut chain = best[genes.len()].1;
+        let first = selected.len();
+        while let Some(idx) = chain {
+            selected.push(genes[idx].3);
+            chain = previous[idx];
+        }
+        selected[first..].reverse();
+        contig_begin = contig_end;
+    }
+    selected
+}
+
+/// Log-odds of each class being counted in `observed` rather than in
+/// `background`, with add-one smoothing.
+fn log_odds<const N: usize>(observed: &[usize; N], background: &[usize; N]) -> [f64; N] {
+    let observed_total: usize = observed.iter().sum();
+    let background_total: usize = background.iter().sum();
+    let mut scores = [0.0; N];
+    for (score, (&obs, &bg)) in scores.iter_mut().zip(observed.iter().zip(background)) {
+        *score = ((obs + 1) as f64 / (observed_total + N) as f64).ln()
+            - ((bg + 1) as f64 / (background_total + N) as f64).ln();
+    }
+    scores
+}
+
+/// Train the start codon and RBS scores on the starts of `genes` against all
+/// candidate starts that share a stop codon with them.
+fn train_starts(model: &mut Model, genes: &[Candidate], candidates: &[Candidate]) {
+    let mut starts = [0; START_CODONS.len()];
+    let mut rbs = [0; NO_RBS + 1];
+    let mut start_background = [0; START_CODONS.len()];
+    let mut rbs_background = [0; NO_RBS + 1];
+    let mut stops = HashSet::new();
+    for gene in genes {
+        if let Some(start_type) = gene.start_type {
+            starts[start_type] += 1;
+            rbs[gene.rbs] += 1;
+            stops.insert((gene.frame, gene.to));
+        }
+    }
+    for candidate in candidates {
+        if let Some(start_type) = candidate.start_type {
+            if stops.contains(&(candidate.frame, candidate.to)) {
+                start_background[start_type] += 1;
+                rbs_background[candidate.rbs] += 1;
+            }
+        }
+    }
+    model.starts = log_odds(&starts, &start_background);
+    model.rbs = log_odds(&rbs, &rbs_background);
+}
+
+fn gc_fraction(bases: &[u8]) -> f64 {
+    let gc = bases
+        .iter()
+        .filter(|b| matches!(b, b'G' | b'C' | b'S'))
+        .count();
+    let known = bases
+        .iter()
+        .filter(|b| matches!(b, b'A' | b'C' | b'G' | b'T' | b'S' | b'W'))
+        .count();
+    if known == 0 {
+        0.0
+    } else {
+        gc as f64 / known as f64
+    }
+}
+
+/// Predict protein-coding genes in prokaryotic contigs, in the spirit of
+/// Prodigal: the model is trained on the input itself, so all contigs of a
+/// genome should be passed together.
+///
+/// 1. The GC frame bias (which codon position tends to be the most GC-rich)
+///    is measured on the longest ORFs, and the ORFs that score well under it
+///    are used to train a codon usage model against the codon usage of all
+///    six frames.
+/// 2. Start codon and ribosome binding site (Shine-Dalgarno motif and
+///    spacer) scores are then trained iteratively on the starts chosen by the
+///    current model.
+/// 3. Finally, a dynamic programming pass over all candidate start/stop
+///    pairs picks the highest scoring set of genes, allowing overlaps of up
+///    to `max_overlap` bases.
+pub fn predict_genes(
+    contigs: &[Contig],
+    options: &GeneCallOptions,
+) -> Result<GenePrediction, String> {
+    if options.min_gene_length <= options.max_overlap {
+        return Err(format!(
+            "Minimum gene length ({}) must be larger than the maximum overlap ({})",
+            options.min_gene_length, options.max_overlap
+        ));
+    }
+    let encoder = CodonEncoder::for_table(options.genetic_code)?;
+    let mut forward = Vec::with_capacity(contigs.len());
+    for contig in contigs {
+        let sanitized = sanitize_sequence(&contig.sequence)
+            .map_err(|err| format!("{}: {}", contig.name, err))?;
+        forward.push(sanitized.into_bytes());
+    }
+    let reverse: Vec<Vec<u8>> = forward
+        .iter()
+        .map(|seq| {
+            let mut rc = Vec::new();
+            rev_compl_to(seq, &mut rc);
+            rc
+        })
+        .collect();
+    let mut frames = Vec::with_capacity(6 * contigs.len());
+    for (contig, (fwd, rev)) in forward.iter().zip(&reverse).enumerate() {
+        for (strand, working) in [(Strand::Forward, fwd), (Strand::Reverse, rev)] {
+            for offset in 0..3 {
+                frames.push(Frame::new(&encoder, contig, strand, offset, working));
+            }
+        }
+    }
+
+    // GC frame bias of the longest ORFs, and training genes selected by it.
+    let mut model = Model {
+        codons: [0.0; 64],
+        starts: [0.0; START_CODONS.len()],
+        rbs: [0.0; NO_RBS + 1],
+    };
+    let mut longest = candidates(&frames, options, true);
+    let mut gc_counts = [0; 3];
+    for candidate in &longest {
+        let gc_max = &frames[candidate.frame].gc_max[candidate.from..candidate.to];
+        for pos in gc_max.iter().flatten() {
+            gc_counts[*pos] += 1;
+        }
+    }
+    let gc_scores = log_odds(&gc_counts, &[1, 1, 1]);
+    let gc_frame_bias = gc_scores.map(f64::exp);
+    let gc_sums = prefix_sums(&frames, |frame, idx| {
+        frame.gc_max[idx].map_or(0.0, |pos| gc_scores[pos])
+    });
+    score_candidates(&frames, &mut longest, &gc_sums, &model);
+    let training = select(&frames, &longest, options.max_overlap);
+
+    // Codon usage of the training genes (start codons excluded) against all
+    // codons of the six frames.
+    let mut coding_counts = [0; 64];
+    let mut background = [0; 64];
+    for gene in &training {
+        let codons = &frames[gene.frame].codons[gene.from + 1..gene.to];
+        for codon in codons.iter().flatten() {
+            coding_counts[*codon] += 1;
+        }
+    }
+    for frame in &frames {
+        for codon in frame.codons.iter().flatten() {
+            background[*codon] += 1;
+        }
+    }
+    model.codons = log_odds(&coding_counts, &background);
+    let coding = prefix_sums(&frames, |frame, idx| {
+        frame.codons[idx].map_or(0.0, |codon| model.codons[codon])
+    });
+
+    let mut all = candidates(&frames, options, false);
+    for _ in 0..START_TRAINING_ROUNDS {
+        score_candidates(&frames, &mut all, &coding, &model);
+        let genes = select(&frames, &all, options.max_overlap);
+        train_starts(&mut model, &genes, &all);
+    }
+    score_candidates(&frames, &mut all, &coding, &model);
+    let selected = select(&frames, &all, options.max_overlap);
+
+    let seqids: Vec<String> = contigs
+        .iter()
+        .enumerate()
+        .map(
+            |(idx, contig)| match contig.name.split_whitespace().next() {
+                Some(word) => word.to_string(),
+                None => format!("contig_{}", idx + 1),
+            },
+        )
+        .collect();
+    let genes = selected
+        .iter()
+        .map(|candidate| {
+            let frame = &frames[candidate.frame];
+            let (start, end) = frame.span(candidate.from, candidate.to);
+            let (begin, finish) = frame.bases(candidate.from, candidate.to);
+            let mut protein: Vec<u8> = (candidate.from..candidate.to)
+                .map(|idx| encoder.translate_triplet(frame.codon(idx)).0)
+                .collect();
+            let (start_codon, rbs_motif, rbs_spacer) = match candidate.start_type {
+                Some(start_type) => {
+                    protein[0] = b'M';
+                    let (motif, spacer) = match candidate.rbs {
+                        NO_RBS => (None, None),
+                        rbs => (
+                            Some(RBS_MOTIFS[rbs / RBS_SPACERS.len()].to_string()),
+                            Some(RBS_SPACERS[rbs % RBS_SPACERS.len()].to_string()),
+                        ),
+                    };
+                    let codon = String::from_utf8_lossy(START_CODONS[start_type]);
+                    (Some(codon.into_owned()), motif, spacer)
+                }
+                None => (None, None, None),
+            };
+            Gene {
+                seqid: seqids[frame.contig].clone(),
+                strand: frame.strand,
+                start,
+                end,
+                partial_start: candidate.start_type.is_none(),
+                partial_end: candidate.to == frame.codons.len(),
+                start_codon,
+                rbs_motif,
+                rbs_spacer,
+                gc_content: gc_fraction(&frame.working[begin..finish]),
+                coding_score: candidate.score - candidate.start_score,
+                start_score: candidate.start_score,
+                score: candidate.score,
+                protein: String::from_utf8_lossy(&protein).into_owned(),
+            }
+        })
+        .collect();
+
+    Ok(GenePrediction {
+        gc_content: gc_fraction(&forward.concat()),
+        gc_frame_bias,
+        training_genes: training.len(),
+        genes,
+    })
+}
+
+/// Gene IDs `{seqid}_{n}`, numbered from 1 within each contig.
+fn gene_ids(genes: &[Gene]) -> Vec<String> {
+    let mut ids = Vec::with_capacity(genes.len());
+    let mut n = 0;
+    for (idx, gene) in genes.iter().enumerate() {
+        n = if idx > 0 && genes[idx - 1].seqid == gene.seqid {
+            n + 1
+        } else {
+            1
+        };
+        ids.push(format!("{}_{}", gene.seqid, n));
+    }
+    ids
+}
+
+/// Prodigal-style attributes; `partial` flags the left and right ends.
+fn gene_attributes(id: &str, gene: &Gene) -> String {
+    let (left, right) = match gene.strand {
+        Strand::Forward => (gene.partial_start, gene.partial_end),
+        Strand::Reverse => (gene.partial_end, gene.partial_start),
+    };
+    format!(
+        "ID={};partial={}{};start_type={};rbs_motif={};rbs_spacer={};gc_cont={:.3}",
+        id,
+        u8::from(left),
+        u8::from(right),
+        gene.start_codon.as_deref().unwrap_or("Edge"),
+        gene.rbs_motif.as_deref().unwrap_or("None"),
+        gene.rbs_spacer.as_deref().unwrap_or("None"),
+        gene.gc_content
+    )
+}
+
+/// Genes as GFF3 `CDS` features.
+pub fn genes_to_gff3(genes: &[Gene]) -> String {
+    let mut gff = String::from("##gff-version 3\n");
+    for (id, gene) in gene_ids(genes).iter().zip(genes) {
+        gff.push_str(&format!(
+            "{}\tweb_bio_tools\tCDS\t{}\t{}\t{:.1}\t{}\t0\t{}\n",
+            gene.seqid,
+            gene.start + 1,
+            gene.end,
+            gene.score,
+            gene.strand.symbol(),
+            gene_attributes(id, gene)
+        ));
+    }
+    gff
+}
+
+/// Proteins as FASTA with Prodigal-style headers,
+/// `>{id} # {start} # {end} # {1 or -1} # {attributes}`.
+pub fn genes_to_fasta(genes: &[Gene]) -> String {
+    let mut fasta = String::new();
+    for (id, gene) in gene_ids(genes).iter().zip(genes) {
+        fasta.push_str(&format!(
+            ">{} # {} # {} # {} # {}\n",
+            id,
+            gene.start + 1,
+            gene.end,
+            if gene.strand == Strand::Forward {
+                1
+            } else {
+                -1
+            },
+            gene_attributes(id, gene)
+        ));
+        for line in gene.protein.as_bytes().chunks(60) {
+            fasta.push_str(&String::from_utf8_lossy(line));
+            fasta.push('\n');
+        }
+    }
+    fasta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Lcg;
+
+    /// A contig of AT-rich intergenic sequence with GC-rich genes (with a
+    /// Shine-Dalgarno motif) on both strands. Returns the contig and the
+    /// forward-strand coordinates and strand of the genes.
+    fn synthetic_contig() -> (String, Vec<(usize, usize, Strand)>) {
+        const CODONS: [&str; 16] = [
+            "GCC", "GCG", "CTG", "GAC", "GAG", "AAG", "CGC", "GGC", "ACC", "GTG", "CAG", "TTC",
+            "ATC", "AAC", "CCG", "TCG",
+        ];
+        let mut rng = Lcg(7);
+        let intergenic = |rng: &mut Lcg, len: usize| -> String {
+            (0..len).map(|_| b"AATTACGT"[rng.below(8)] as char).collect()
+        };
+        let mut contig = String::new();
+        let mut genes = Vec::new();
+        for idx in 0..8 {
+            contig.push_str(&intergenic(&mut rng, 150));
+            let mut gene = String::from("ATG");
+            for _ in 0..150 + 10 * idx {
+                gene.push_str(CODONS[rng.below(CODONS.len())]);
+            }
+            gene.push_str("TAA");
+            let strand = if idx % 3 == 2 {
+                Strand::Reverse
+            } else {
+                Strand::Forward
+            };
+            let start = contig.len();
+            let element = format!("AGGAGGTATCAT{}", gene);
+            match strand {
+                Strand::Forward => {
+                    contig.push_str(&element);
+                    genes.push((start + 12, contig.len(), strand));
+                }
+                Strand::Reverse => {
+                    let mut rc = Vec::new();
+                    rev_compl_to(element.as_bytes(), &mut rc);
+                    contig.push_str(std::str::from_utf8(&rc).unwrap());
+                    genes.push((start, contig.len() - 12, strand));
+                }
+            }
+        }
+        contig.push_str(&intergenic(&mut rng, 150));
+        (contig, genes)
+    }
+
+    #[test]
+    fn recovers_synthetic_genes() {
+        let (sequence, expected) = synthetic_contig();
+        let contigs = [Contig {
+            name: "contig1 synthetic".to_string(),
+            sequence,
+        }];
+        let prediction = predict_genes(&contigs, &GeneCallOptions::default()).unwrap();
+        assert!(prediction.training_genes > 0);
+        assert_eq!(prediction.genes.len(), expected.len());
+        for &(start, end, strand) in &expected {
+            let gene = prediction
+                .genes
+                .iter()
+                .find(|gene| gene.strand == strand && gene.start == start && gene.end == end)
+                .unwrap_or_else(|| panic!("gene {}..{} {:?} not found", start, end, strand));
+            assert_eq!(gene.seqid, "contig1");
+            assert_eq!(gene.start_codon.as_deref(), Some("ATG"));
+            assert_eq!(gene.rbs_motif.as_deref(), Some("AGGAGG"));
+            assert_eq!(gene.rbs_spacer.as_deref(), Some("5-10bp"));
+            assert!(gene.protein.starts_with('M'));
+            assert_eq!(gene.protein.len() * 3 + 3, end - start);
+        }
+    }
+
+    #[test]
+    fn writes_gff3_and_fasta() {
+        let (sequence, _) = synthetic_contig();
+        let contigs = [Contig {
+            name: "contig1".to_string(),
+            sequence,
+        }];
+        let genes = predict_genes(&contigs, &GeneCallOptions::default())
+            .unwrap()
+            .genes;
+        let gff = genes_to_gff3(&genes);
+        assert!(gff.starts_with("##gff-version 3\n"));
+        assert_eq!(gff.lines().count(), genes.len() + 1);
+        let first: Vec<&str> = gff.lines().nth(1).unwrap().split('\t').collect();
+        assert_eq!(first[0], "contig1");
+        assert_eq!(first[2], "CDS");
+        assert_eq!(first[3], (genes[0].start + 1).to_string());
+        assert!(first[8].starts_with("ID=contig1_1;partial=00;start_type=ATG;"));
+
+        let fasta = genes_to_fasta(&genes);
+        assert_eq!(fasta.matches('>').count(), genes.len());
+        assert!(fasta.starts_with(&format!(">contig1_1 # {} # ", genes[0].start + 1)));
+    }
+
+    #[test]
+    fn rejects_invalid_options() {
+        let contigs = [Contig {
+            name: "c".to_string(),
+            sequence: "ATGAAATAA".to_string(),
+        }];
+        let options = GeneCallOptions {
+            max_overlap: 90,
+            ..GeneCallOptions::default()
+        };
+        assert!(predict_genes(&contigs, &options).is_err());
+        let options = GeneCallOptions {
+            genetic_code: 7,
+            ..GeneCallOptions::default()
+        };
+        assert!(predict_genes(&contigs, &options).is_err());
+        let prediction = predict_genes(&contigs, &GeneCallOptions::default()).unwrap();
+        assert!(prediction.genes.is_empty());
+    }
+}
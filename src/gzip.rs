@@ -0,0 +1,301 @@
+use miniz_oxide::inflate::stream::{inflate, InflateState};
+use miniz_oxide::{DataFormat, MZError, MZFlush, MZStatus};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const FHCRC: u8 = 0x02;
+const FEXTRA: u8 = 0x04;
+const FNAME: u8 = 0x08;
+const FCOMMENT: u8 = 0x10;
+
+/// Upper bound on the size of a file decompressed in one go by
+/// [`decompress_gzip`], so that a small crafted file cannot exhaust the
+/// memory of the page. Streaming decoders hand their output on chunk by
+/// chunk and are not limited.
+pub const MAX_DECOMPRESSED_BYTES: usize = 1 << 30;
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let mut c = !crc;
+    for &b in data {
+        c = CRC32_TABLE[((c ^ u32::from(b)) & 0xff) as usize] ^ (c >> 8);
+    }
+    !c
+}
+
+/// Whether `data` starts with the gzip magic bytes.
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&GZIP_MAGIC)
+}
+
+/// Length of the gzip member header at the start of `data`, or `None` if
+/// more input is needed to tell.
+fn header_len(data: &[u8]) -> Result<Option<usize>, String> {
+    let magic_len = data.len().min(2);
+    if data[..magic_len] != GZIP_MAGIC[..magic_len] {
+        return Err("Not gzip data (bad magic bytes)".to_string());
+    }
+    if data.len() < 10 {
+        return Ok(None);
+    }
+    if data[2] != 8 {
+        return Err(format!("Unsupported gzip compression method {}", data[2]));
+    }
+    let flags = data[3];
+    let mut len = 10;
+    if flags & FEXTRA != 0 {
+        let Some(xlen) = data.get(len..len + 2) else {
+            return Ok(None);
+        };
+        len += 2 + usize::from(u16::from_le_bytes([xlen[0], xlen[1]]));
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            match data.get(len..).and_then(|rest| rest.iter().position(|&b| b == 0)) {
+                Some(end) => len += end + 1,
+                None => return Ok(None),
+            }
+        }
+    }
+    if flags & FHCRC != 0 {
+        len += 2;
+    }
+    Ok((data.len() >= len).then_some(len))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Header,
+    Body,
+    Trailer,
+}
+
+/// Streaming gzip decompressor. Input may be fed in chunks of any size and
+/// may hold several concatenated members, as in BGZF (bgzip) files.
+pub struct GzipDecoder {
+    state: State,
+    /// Input received but not consumed yet.
+    pending: Vec<u8>,
+    inflater: Box<InflateState>,
+    crc: u32,
+    size: u32,
+    members: usize,
+    total: usize,
+    limit: usize,
+}
+
+impl Default for GzipDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GzipDecoder {
+    pub fn new() -> Self {
+        Self::with_limit(usize::MAX)
+    }
+
+    /// A decoder that fails once its total output exceeds `limit` bytes.
+    fn with_limit(limit: usize) -> Self {
+        GzipDecoder {
+            state: State::Header,
+            pending: Vec::new(),
+            inflater: InflateState::new_boxed(DataFormat::Raw),
+            crc: 0,
+            size: 0,
+            members: 0,
+            total: 0,
+            limit,
+        }
+    }
+
+    /// Decompress as much of `input` (and of earlier incomplete input) as
+    /// possible, appending the output to `out`.
+    pub fn write(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), String> {
+        self.pending.extend_from_slice(input);
+        let mut pos = 0;
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let rest = &self.pending[pos..];
+            match self.state {
+                State::Header => {
+                    if rest.is_empty() {
+                        break;
+                    }
+                    // Zero padding after the last member is allowed.
+                    if self.members > 0 && rest.iter().all(|&b| b == 0) {
+                        pos = self.pending.len();
+                        break;
+                    }
+                    let Some(len) = header_len(rest)? else {
+                        break;
+                    };
+                    pos += len;
+                    self.inflater.reset(DataFormat::Raw);
+                    self.crc = 0;
+                    self.size = 0;
+                    self.state = State::Body;
+                }
+                State::Body => {
+                    let result = inflate(&mut self.inflater, rest, &mut buffer, MZFlush::None);
+                    pos += result.bytes_consumed;
+                    let written = &buffer[..result.bytes_written];
+                    self.crc = crc32_update(self.crc, written);
+                    self.size = self.size.wrapping_add(written.len() as u32);
+                    self.total = self.total.saturating_add(written.len());
+                    if self.total > self.limit {
+                        return Err(format!(
+                            "Decompressed input exceeds the limit of {} bytes",
+                            self.limit
+                        ));
+                    }
+                    out.extend_from_slice(written);
+                    match result.status {
+                        Ok(MZStatus::StreamEnd) => self.state = State::Trailer,
+                        Ok(_) if result.bytes_consumed > 0 || result.bytes_written > 0 => {}
+                        Ok(_) | Err(MZError::Buf) => break,
+                        Err(_) => return Err("Corrupt gzip data".to_string()),
+                    }
+                }
+                State::Trailer => {
+                    if rest.len() < 8 {
+                        break;
+                    }
+                    let crc = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]);
+                    let size = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]);
+                    if crc != self.crc || size != self.size {
+                        return Err("Corrupt gzip data (checksum mismatch)".to_string());
+                    }
+                    pos += 8;
+                    self.members += 1;
+                    self.state = State::Header;
+                }
+            }
+        }
+        self.pending.drain(..pos);
+        Ok(())
+    }
+
+    /// Check that the input ended at the end of a member.
+    pub fn finish(&self) -> Result<(), String> {
+        if self.state != State::Header || !self.pending.is_empty() || self.members == 0 {
+            return Err("Truncated gzip data".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Decompress a complete gzip (or BGZF) file.
+pub fn decompress_gzip(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoder = GzipDecoder::with_limit(MAX_DECOMPRESSED_BYTES);
+    let mut out = Vec::new();
+    decoder.write(data, &mut out)?;
+    decoder.finish()?;
+    Ok(out)
+}
+
+/// Text of an input file, decompressed first if it starts with the gzip
+/// magic bytes.
+pub fn read_text(data: &[u8]) -> Result<String, String> {
+    let bytes = if is_gzip(data) {
+        decompress_gzip(data)?
+    } else {
+        data.to_vec()
+    };
+    String::from_utf8(bytes).map_err(|err| {
+        format!(
+            "Input is not valid UTF-8 text (invalid byte at offset {})",
+            err.utf8_error().valid_up_to()
+        )
+    })
+}
+
+/// A gzip member holding `data`, for tests; with `bgzf`, with the BGZF extra
+/// field.
+#[cfg(test)]
+pub(crate) fn gzip_member(data: &[u8], bgzf: bool) -> Vec<u8> {
+    let deflated = miniz_oxide::deflate::compress_to_vec(data, 6);
+    let mut member = vec![0x1f, 0x8b, 8];
+    if bgzf {
+        let block_size = (deflated.len() + 25) as u16;
+        member.extend_from_slice(&[FEXTRA, 0, 0, 0, 0, 0, 0xff, 6, 0, b'B', b'C', 2, 0]);
+        member.extend_from_slice(&block_size.to_le_bytes());
+    } else {
+        member.extend_from_slice(&[FNAME, 0, 0, 0, 0, 0, 3]);
+        member.extend_from_slice(b"reads.fq\0");
+    }
+    member.extend_from_slice(&deflated);
+    member.extend_from_slice(&crc32_update(0, data).to_le_bytes());
+    member.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    member
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_matches_reference() {
+        assert_eq!(crc32_update(0, b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32_update(crc32_update(0, b"1234"), b"56789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn decompresses_multi_member_and_bgzf() {
+        let text = ">seq1\nACGTACGT\n".repeat(1000);
+        let mut data = gzip_member(&text.as_bytes()[..5000], false);
+        data.extend(gzip_member(&text.as_bytes()[5000..], true));
+        // BGZF end-of-file marker: an empty block.
+        data.extend(gzip_member(b"", true));
+        assert!(is_gzip(&data));
+        assert_eq!(read_text(&data).unwrap(), text);
+
+        // Feeding the same input byte by byte gives the same output.
+        let mut decoder = GzipDecoder::new();
+        let mut out = Vec::new();
+        for chunk in data.chunks(7) {
+            decoder.write(chunk, &mut out).unwrap();
+        }
+        decoder.finish().unwrap();
+        assert_eq!(out, text.as_bytes());
+    }
+
+    #[test]
+    fn limits_only_one_shot_decompression() {
+        let data = gzip_member(&[b'A'; 5000], false);
+        let mut limited = GzipDecoder::with_limit(4096);
+        assert!(limited.write(&data, &mut Vec::new()).is_err());
+
+        // Streaming decoders pass their output on and have no limit.
+        let mut decoder = GzipDecoder::new();
+        let mut out = Vec::new();
+        decoder.write(&data, &mut out).unwrap();
+        decoder.finish().unwrap();
+        assert_eq!(out.len(), 5000);
+    }
+
+    #[test]
+    fn rejects_corrupt_input() {
+        let mut data = gzip_member(b"ACGT", false);
+        assert!(decompress_gzip(&data[..data.len() - 3]).is_err());
+        let last = data.len() - 5;
+        data[last] ^= 1;
+        assert!(decompress_gzip(&data).is_err());
+        assert_eq!(read_text(b"plain text").unwrap(), "plain text");
+        assert!(read_text(&[0xff, 0xfe]).is_err());
+    }
+}
@@ -1,6 +1,8 @@
 use serde::Serialize;
 use std::iter::Peekable;
 
+use crate::alphabet::Alphabet;
+
 #[derive(Debug, Serialize)]
 pub struct HeaderField {
     pub key: String,
@@ -21,6 +23,9 @@ pub struct Hmm {
     pub format_line: String,
     pub metadata: Vec<HeaderField>,
     pub alphabet: Vec<String>,
+    /// Residue alphabet of the model, from the `ALPH` line or, failing that,
+    /// recognised from the emission columns. `None` for custom alphabets.
+    pub alphabet_type: Option<Alphabet>,
     pub transition_order: Vec<String>,
     pub states: Vec<HmmState>,
 }
@@ -71,6 +76,7 @@ pub fn parse_hmm(input: &str) -> Result<Hmm, String> {
     if alphabet.is_empty() {
         return Err("Alphabet cannot be empty".into());
     }
+    let alphabet_type = detect_alphabet(&metadata, &alphabet)?;
 
     let transition_header = loop {
         let line = lines
@@ -106,7 +112,7 @@ pub fn parse_hmm(input: &str) -> Result<Hmm, String> {
         if match_tokens.len() < alphabet.len() + 1 {
             return Err(format!(
                 "Match emission line for state '{}' does not have enough values",
-                match_tokens.get(0).unwrap_or(&"?")
+                match_tokens.first().unwrap_or(&"?")
             ));
         }
         let label = match_tokens[0].to_string();
@@ -178,21 +184,68 @@ pub fn parse_hmm(input: &str) -> Result<Hmm, String> {
         format_line,
         metadata,
         alphabet,
+        alphabet_type,
         transition_order,
         states,
     })
 }
 
+impl Hmm {
+    /// Column of `residue` in the emission vectors, if it is one of the
+    /// model's symbols.
+    pub fn residue_index(&self, residue: u8) -> Option<usize> {
+        match self.alphabet_type {
+            Some(alphabet) => alphabet
+                .encode(residue)
+                .map(|code| code as usize)
+                .filter(|&code| code < alphabet.canonical_len()),
+            None => self
+                .alphabet
+                .iter()
+                .position(|sym| sym.as_bytes().eq_ignore_ascii_case(&[residue])),
+        }
+    }
+}
+
+/// HMMER writes the emission columns in the canonical order of the alphabet
+/// named on the `ALPH` line; check that the two agree. Other `ALPH` values
+/// (`coins`, `dice`, custom alphabets) are accepted without an alphabet type.
+fn detect_alphabet(
+    metadata: &[HeaderField],
+    symbols: &[String],
+) -> Result<Option<Alphabet>, String> {
+    let matches = |alphabet: Alphabet| {
+        symbols.len() == alphabet.canonical_len()
+            && symbols
+                .iter()
+                .zip(alphabet.canonical())
+                .all(|(sym, &c)| sym.as_bytes().eq_ignore_ascii_case(&[c]))
+    };
+    match metadata.iter().find(|field| field.key == "ALPH") {
+        Some(field) => {
+            let Ok(alphabet) = Alphabet::from_name(&field.value) else {
+                return Ok(None);
+            };
+            if !matches(alphabet) {
+                return Err(format!(
+                    "HMM columns {} do not match the {} alphabet",
+                    symbols.join(" "),
+                    alphabet
+                ));
+            }
+            Ok(Some(alphabet))
+        }
+        None => Ok([Alphabet::Dna, Alphabet::Rna, Alphabet::Protein]
+            .into_iter()
+            .find(|&alphabet| matches(alphabet))),
+    }
+}
+
 fn next_nonempty_line<'a, I>(lines: &mut Peekable<I>) -> Option<&'a str>
 where
     I: Iterator<Item = &'a str>,
 {
-    while let Some(line) = lines.next() {
-        if !line.trim().is_empty() {
-            return Some(line);
-        }
-    }
-    None
+    lines.find(|line| !line.trim().is_empty())
 }
 
 fn parse_value_slice(
@@ -233,10 +286,25 @@ mod tests {
             .iter()
             .any(|field| field.key == "NAME" && field.value == "SPHERE-III.008_786"));
         assert_eq!(hmm.alphabet.len(), 20);
+        assert_eq!(hmm.alphabet_type, Some(Alphabet::Protein));
+        assert_eq!(hmm.residue_index(b'c'), Some(1));
+        assert_eq!(hmm.residue_index(b'X'), None);
         assert_eq!(hmm.transition_order.len(), 7);
         assert_eq!(hmm.states.len(), 41);
         assert_eq!(hmm.states[0].label, "COMPO");
         assert_eq!(hmm.states[1].label, "1");
-        assert_eq!(hmm.states[1].annotation.get(0), Some(&"1".to_string()));
+        assert_eq!(hmm.states[1].annotation.first(), Some(&"1".to_string()));
+    }
+
+    #[test]
+    fn unknown_alph_has_no_alphabet_type() {
+        let text = include_str!("../static/hmm-example.hmm");
+        let custom = text.replacen("ALPH  amino", "ALPH  custom", 1);
+        let hmm = parse_hmm(&custom).expect("failed to parse HMM");
+        assert_eq!(hmm.alphabet_type, None);
+        assert_eq!(hmm.residue_index(b'c'), Some(1));
+
+        let mismatched = text.replacen("ALPH  amino", "ALPH  DNA", 1);
+        assert!(parse_hmm(&mismatched).is_err());
     }
 }
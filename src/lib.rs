@@ -1,20 +1,92 @@
+use serde::Serialize;
 use serde_wasm_bindgen::to_value;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 
 mod alignment;
+mod alphabet;
+mod backtranslate;
+mod codon_usage;
+mod composition;
+mod frameshift;
+mod fasta;
+mod fastq;
 mod fna2faa;
+mod genecall;
+mod gzip;
 mod hmm;
+mod orf;
+mod pairhmm;
+mod presets;
 #[cfg(all(feature = "python", not(target_arch = "wasm32")))]
 mod python;
+mod rng;
+mod share;
 mod translation;
 
-pub use alignment::AlignmentResult;
-pub use translation::{translate_all_frames, translate_frame};
+pub use alignment::{
+    Anchor, AlignmentError, AlignmentResult, AlignmentTask, ColumnKind, ColumnScore, DpMatrices,
+    DpMatrix, DpMatrixKind, DualAffineGaps, EditDistanceResult, PathCell, TracebackPointer,
+    MAX_EXPORT_CELLS,
+};
+pub use alphabet::{Alphabet, InvalidResidue, Sequence};
+pub use backtranslate::{
+    back_translate, BackTranslationMode, BackTranslationOptions,
+};
+pub use codon_usage::{
+    codon_usage_report, CaiReference, CodonStat, CodonUsage, CodonUsageReport, CodonUsageSummary,
+};
+pub use composition::{
+    composition_report, CompositionOptions, CompositionProfile, CompositionReport,
+    CompositionStats, CompositionWindow, DinucleotideStat,
+};
+pub use fasta::{parse_fasta, FastaError, FastaReader, FastaRecord};
+pub use fastq::{FastqError, FastqReads, FastqRecord, PhredEncoding, TrimOptions, TrimSummary};
+pub use fna2faa::{
+    CodonEncoder, CodonSite, FrameTranslation, GeneticCode, TranslationOptions, TranslationSummary,
+    GENETIC_CODES,
+};
+pub use frameshift::{
+    translate_records_with_frameshifts, translate_with_frameshifts, Frameshift,
+    FrameshiftOptions, FrameshiftRecord, FrameshiftTranslation, FRAMESHIFT_MARKER,
+};
+pub use genecall::{Contig, Gene, GeneCallOptions, GenePrediction};
+pub use gzip::{decompress_gzip, is_gzip, GzipDecoder};
+pub use hmm::Hmm;
+pub use orf::{NestedOrfs, Orf, OrfMode, OrfOptions, Strand};
+pub use pairhmm::{PairHmmParams, PairHmmResult};
+pub use presets::{AlignmentMode, Preset, PresetScoring, PRESETS};
+pub use share::{JobSpec, SharedSequence, Tool};
+pub use translation::{
+    translate_all_frames, translate_all_frames_with_options, translate_frame,
+    translate_frame_with_options, translate_sequence_all_frames, translate_sequence_frame,
+    StreamingTranslator, TranslatedRecord,
+};
+
+fn alignment_to_js<T: Serialize>(
+    result: Result<T, AlignmentError>,
+) -> Result<JsValue, JsValue> {
+    let result = result.map_err(|err| JsValue::from_str(&err.to_string()))?;
+    to_value(&result)
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize alignment: {}", err)))
+}
+
+fn alphabet_from_js(name: &str) -> Result<Alphabet, JsValue> {
+    Alphabet::from_name(name).map_err(|err| JsValue::from_str(&err))
+}
+
+/// Check both sequences against an optional alphabet name before aligning.
+fn check_alphabet_js(seq1: &str, seq2: &str, alphabet: Option<String>) -> Result<(), JsValue> {
+    match alphabet {
+        Some(name) => alignment::check_alphabet(seq1, seq2, alphabet_from_js(&name)?)
+            .map_err(|err| JsValue::from_str(&err.to_string())),
+        None => Ok(()),
+    }
+}
 
 #[wasm_bindgen]
-pub fn smith_waterman(seq1: &str, seq2: &str) -> JsValue {
-    smith_waterman_custom(seq1, seq2, 2.0, -1.0, -1.0, -0.5)
+pub fn smith_waterman(seq1: &str, seq2: &str) -> Result<JsValue, JsValue> {
+    smith_waterman_custom(seq1, seq2, 2.0, -1.0, -1.0, -0.5, None)
 }
 
 #[wasm_bindgen]
@@ -25,7 +97,9 @@ pub fn smith_waterman_custom(
     mismatch_penalty: f64,
     gap_open: f64,
     gap_extend: f64,
-) -> JsValue {
+    alphabet: Option<String>,
+) -> Result<JsValue, JsValue> {
+    check_alphabet_js(seq1, seq2, alphabet)?;
     let result = alignment::smith_waterman_internal(
         seq1,
         seq2,
@@ -34,18 +108,23 @@ pub fn smith_waterman_custom(
         gap_open,
         gap_extend,
     );
-    to_value(&result).unwrap()
+    alignment_to_js(result)
 }
 
 #[wasm_bindgen]
-pub fn smith_waterman_blosum62(seq1: &str, seq2: &str, gap_open: f64, gap_extend: f64) -> JsValue {
+pub fn smith_waterman_blosum62(
+    seq1: &str,
+    seq2: &str,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<JsValue, JsValue> {
     let result = alignment::smith_waterman_blosum62_internal(seq1, seq2, gap_open, gap_extend);
-    to_value(&result).unwrap()
+    alignment_to_js(result)
 }
 
 #[wasm_bindgen]
-pub fn needleman_wunsch(seq1: &str, seq2: &str) -> JsValue {
-    needleman_wunsch_custom(seq1, seq2, 2.0, -1.0, -1.0, -0.5)
+pub fn needleman_wunsch(seq1: &str, seq2: &str) -> Result<JsValue, JsValue> {
+    needleman_wunsch_custom(seq1, seq2, 2.0, -1.0, -1.0, -0.5, None)
 }
 
 #[wasm_bindgen]
@@ -56,7 +135,9 @@ pub fn needleman_wunsch_custom(
     mismatch_penalty: f64,
     gap_open: f64,
     gap_extend: f64,
-) -> JsValue {
+    alphabet: Option<String>,
+) -> Result<JsValue, JsValue> {
+    check_alphabet_js(seq1, seq2, alphabet)?;
     let result = alignment::needleman_wunsch_internal(
         seq1,
         seq2,
@@ -65,7 +146,7 @@ pub fn needleman_wunsch_custom(
         gap_open,
         gap_extend,
     );
-    to_value(&result).unwrap()
+    alignment_to_js(result)
 }
 
 #[wasm_bindgen]
@@ -74,31 +155,1116 @@ pub fn needleman_wunsch_blosum62(
     seq2: &str,
     gap_open: f64,
     gap_extend: f64,
-) -> JsValue {
+) -> Result<JsValue, JsValue> {
     let result = alignment::needleman_wunsch_blosum62_internal(seq1, seq2, gap_open, gap_extend);
-    to_value(&result).unwrap()
+    alignment_to_js(result)
+}
+
+/// Smith-Waterman alignment returned with its filled score, insert and
+/// delete matrices, traceback pointers and path (`DpMatrices`), for
+/// sequences whose matrices have at most `MAX_EXPORT_CELLS` cells.
+#[wasm_bindgen]
+pub fn smith_waterman_matrices(
+    seq1: &str,
+    seq2: &str,
+    match_score: f64,
+    mismatch_penalty: f64,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let result = alignment::smith_waterman_task(
+        seq1,
+        seq2,
+        match_score,
+        mismatch_penalty,
+        gap_open,
+        gap_extend,
+    )
+    .and_then(|task| task.finish_with_matrices());
+    alignment_to_js(result)
+}
+
+#[wasm_bindgen]
+pub fn smith_waterman_blosum62_matrices(
+    seq1: &str,
+    seq2: &str,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let result = alignment::smith_waterman_blosum62_task(seq1, seq2, gap_open, gap_extend)
+        .and_then(|task| task.finish_with_matrices());
+    alignment_to_js(result)
+}
+
+/// Needleman-Wunsch counterpart of [`smith_waterman_matrices`].
+#[wasm_bindgen]
+pub fn needleman_wunsch_matrices(
+    seq1: &str,
+    seq2: &str,
+    match_score: f64,
+    mismatch_penalty: f64,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let result = alignment::needleman_wunsch_task(
+        seq1,
+        seq2,
+        match_score,
+        mismatch_penalty,
+        gap_open,
+        gap_extend,
+    )
+    .and_then(|task| task.finish_with_matrices());
+    alignment_to_js(result)
+}
+
+#[wasm_bindgen]
+pub fn needleman_wunsch_blosum62_matrices(
+    seq1: &str,
+    seq2: &str,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let result = alignment::needleman_wunsch_blosum62_task(seq1, seq2, gap_open, gap_extend)
+        .and_then(|task| task.finish_with_matrices());
+    alignment_to_js(result)
+}
+
+fn anchors_from_js(anchors: JsValue) -> Result<Vec<Anchor>, JsValue> {
+    serde_wasm_bindgen::from_value(anchors).map_err(|err| {
+        JsValue::from_str(&format!(
+            "Anchors must be a list of {{start1, end1, start2, end2}}: {}",
+            err
+        ))
+    })
+}
+
+/// Global alignment forced through `anchors`, a list of
+/// `{start1, end1, start2, end2}` regions of equal length (0-based, end
+/// exclusive).
+#[wasm_bindgen]
+pub fn needleman_wunsch_anchored(
+    seq1: &str,
+    seq2: &str,
+    anchors: JsValue,
+    match_score: f64,
+    mismatch_penalty: f64,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let anchors = anchors_from_js(anchors)?;
+    alignment_to_js(alignment::needleman_wunsch_anchored_internal(
+        seq1,
+        seq2,
+        &anchors,
+        match_score,
+        mismatch_penalty,
+        gap_open,
+        gap_extend,
+    ))
+}
+
+#[wasm_bindgen]
+pub fn needleman_wunsch_anchored_blosum62(
+    seq1: &str,
+    seq2: &str,
+    anchors: JsValue,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let anchors = anchors_from_js(anchors)?;
+    alignment_to_js(alignment::needleman_wunsch_anchored_blosum62_internal(
+        seq1, seq2, &anchors, gap_open, gap_extend,
+    ))
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn smith_waterman_dual_affine(
+    seq1: &str,
+    seq2: &str,
+    match_score: f64,
+    mismatch_penalty: f64,
+    gap_open: f64,
+    gap_extend: f64,
+    long_gap_open: f64,
+    long_gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let gaps = DualAffineGaps {
+        gap_open,
+        gap_extend,
+        long_gap_open,
+        long_gap_extend,
+    };
+    let result = alignment::smith_waterman_dual_affine_internal(
+        seq1,
+        seq2,
+        match_score,
+        mismatch_penalty,
+        &gaps,
+    );
+    alignment_to_js(result)
+}
+
+#[wasm_bindgen]
+pub fn smith_waterman_dual_affine_blosum62(
+    seq1: &str,
+    seq2: &str,
+    gap_open: f64,
+    gap_extend: f64,
+    long_gap_open: f64,
+    long_gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let gaps = DualAffineGaps {
+        gap_open,
+        gap_extend,
+        long_gap_open,
+        long_gap_extend,
+    };
+    let result = alignment::smith_waterman_dual_affine_blosum62_internal(seq1, seq2, &gaps);
+    alignment_to_js(result)
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn needleman_wunsch_dual_affine(
+    seq1: &str,
+    seq2: &str,
+    match_score: f64,
+    mismatch_penalty: f64,
+    gap_open: f64,
+    gap_extend: f64,
+    long_gap_open: f64,
+    long_gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let gaps = DualAffineGaps {
+        gap_open,
+        gap_extend,
+        long_gap_open,
+        long_gap_extend,
+    };
+    let result = alignment::needleman_wunsch_dual_affine_internal(
+        seq1,
+        seq2,
+        match_score,
+        mismatch_penalty,
+        &gaps,
+    );
+    alignment_to_js(result)
+}
+
+#[wasm_bindgen]
+pub fn needleman_wunsch_dual_affine_blosum62(
+    seq1: &str,
+    seq2: &str,
+    gap_open: f64,
+    gap_extend: f64,
+    long_gap_open: f64,
+    long_gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let gaps = DualAffineGaps {
+        gap_open,
+        gap_extend,
+        long_gap_open,
+        long_gap_extend,
+    };
+    let result = alignment::needleman_wunsch_dual_affine_blosum62_internal(seq1, seq2, &gaps);
+    alignment_to_js(result)
+}
+
+/// Levenshtein distance of two sequences; `null` if it exceeds
+/// `max_distance`. With `traceback` the result includes the alignment.
+#[wasm_bindgen]
+pub fn edit_distance(
+    seq1: &str,
+    seq2: &str,
+    max_distance: Option<usize>,
+    traceback: bool,
+) -> Result<JsValue, JsValue> {
+    alignment_to_js(alignment::edit_distance_internal(
+        seq1,
+        seq2,
+        max_distance,
+        traceback,
+    ))
+}
+
+/// Like `edit_distance`, but `seq1` may match anywhere inside `seq2`.
+#[wasm_bindgen]
+pub fn edit_distance_semi_global(
+    seq1: &str,
+    seq2: &str,
+    max_distance: Option<usize>,
+    traceback: bool,
+) -> Result<JsValue, JsValue> {
+    alignment_to_js(alignment::edit_distance_semi_global_internal(
+        seq1,
+        seq2,
+        max_distance,
+        traceback,
+    ))
+}
+
+/// Every named preset with its matrix, gap scores and mode, for display.
+#[wasm_bindgen]
+pub fn list_presets() -> Result<JsValue, JsValue> {
+    to_value(PRESETS)
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize presets: {}", err)))
+}
+
+#[wasm_bindgen]
+pub fn get_preset(name: &str) -> Result<JsValue, JsValue> {
+    alignment_to_js(presets::get_preset(name))
+}
+
+/// Align with the parameters of a named preset (see `list_presets`).
+#[wasm_bindgen]
+pub fn align_with_preset(seq1: &str, seq2: &str, preset: &str) -> Result<JsValue, JsValue> {
+    alignment_to_js(presets::align_with_preset(seq1, seq2, preset))
+}
+
+#[wasm_bindgen]
+pub fn pair_hmm_align(
+    seq1: &str,
+    seq2: &str,
+    match_prob: f64,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let params = PairHmmParams {
+        gap_open,
+        gap_extend,
+        ..PairHmmParams::default()
+    };
+    alignment_to_js(pairhmm::pair_hmm_internal(seq1, seq2, match_prob, &params))
+}
+
+#[wasm_bindgen]
+pub fn pair_hmm_align_blosum62(
+    seq1: &str,
+    seq2: &str,
+    gap_open: f64,
+    gap_extend: f64,
+) -> Result<JsValue, JsValue> {
+    let params = PairHmmParams {
+        gap_open,
+        gap_extend,
+        ..PairHmmParams::default()
+    };
+    alignment_to_js(pairhmm::pair_hmm_blosum62_internal(seq1, seq2, &params))
+}
+
+/// Object-safe view of an `AlignmentTask` whose output can be handed to JS.
+trait JsAlignmentTask {
+    fn total_rows(&self) -> usize;
+    fn rows_done(&self) -> usize;
+    fn step(&mut self, max_rows: usize) -> bool;
+    fn finish(self: Box<Self>) -> Result<JsValue, JsValue>;
+}
+
+impl<T> JsAlignmentTask for T
+where
+    T: AlignmentTask,
+    T::Output: Serialize,
+{
+    fn total_rows(&self) -> usize {
+        AlignmentTask::total_rows(self)
+    }
+
+    fn rows_done(&self) -> usize {
+        AlignmentTask::rows_done(self)
+    }
+
+    fn step(&mut self, max_rows: usize) -> bool {
+        AlignmentTask::step(self, max_rows)
+    }
+
+    fn finish(self: Box<Self>) -> Result<JsValue, JsValue> {
+        alignment_to_js(AlignmentTask::finish(*self))
+    }
+}
+
+/// An alignment that is computed a few DP rows at a time, so that long
+/// alignments can report progress (e.g. from a Web Worker) and be cancelled.
+///
+/// ```js
+/// const job = AlignmentJob.needleman_wunsch_blosum62(a, b, -10, -0.5);
+/// if (job.run(200, (done, total) => postMessage({ done, total }))) {
+///     const result = job.finish();
+/// }
+/// ```
+///
+/// The Smith-Waterman and Needleman-Wunsch factories take an optional last
+/// `column_scores` argument; when true the result carries the per-column
+/// score breakdown in `columns`.
+#[wasm_bindgen]
+pub struct AlignmentJob {
+    task: Option<Box<dyn JsAlignmentTask>>,
+}
+
+impl AlignmentJob {
+    fn new<T>(task: Result<T, AlignmentError>) -> Result<AlignmentJob, JsValue>
+    where
+        T: JsAlignmentTask + 'static,
+    {
+        let task = task.map_err(|err| JsValue::from_str(&err.to_string()))?;
+        Ok(AlignmentJob {
+            task: Some(Box::new(task)),
+        })
+    }
+
+    fn task_mut(&mut self) -> Result<&mut Box<dyn JsAlignmentTask>, JsValue> {
+        self.task
+            .as_mut()
+            .ok_or_else(|| JsValue::from_str("Alignment job was cancelled or already finished"))
+    }
+}
+
+#[wasm_bindgen]
+impl AlignmentJob {
+    pub fn smith_waterman(
+        seq1: &str,
+        seq2: &str,
+        match_score: f64,
+        mismatch_penalty: f64,
+        gap_open: f64,
+        gap_extend: f64,
+        column_scores: Option<bool>,
+    ) -> Result<AlignmentJob, JsValue> {
+        AlignmentJob::new(
+            alignment::smith_waterman_task(
+                seq1,
+                seq2,
+                match_score,
+                mismatch_penalty,
+                gap_open,
+                gap_extend,
+            )
+            .map(|task| task.with_column_scores(column_scores.unwrap_or(false))),
+        )
+    }
+
+    pub fn smith_waterman_blosum62(
+        seq1: &str,
+        seq2: &str,
+        gap_open: f64,
+        gap_extend: f64,
+        column_scores: Option<bool>,
+    ) -> Result<AlignmentJob, JsValue> {
+        AlignmentJob::new(
+            alignment::smith_waterman_blosum62_task(seq1, seq2, gap_open, gap_extend)
+                .map(|task| task.with_column_scores(column_scores.unwrap_or(false))),
+        )
+    }
+
+    pub fn needleman_wunsch(
+        seq1: &str,
+        seq2: &str,
+        match_score: f64,
+        mismatch_penalty: f64,
+        gap_open: f64,
+        gap_extend: f64,
+        column_scores: Option<bool>,
+    ) -> Result<AlignmentJob, JsValue> {
+        AlignmentJob::new(
+            alignment::needleman_wunsch_task(
+                seq1,
+                seq2,
+                match_score,
+                mismatch_penalty,
+                gap_open,
+                gap_extend,
+            )
+            .map(|task| task.with_column_scores(column_scores.unwrap_or(false))),
+        )
+    }
+
+    pub fn needleman_wunsch_blosum62(
+        seq1: &str,
+        seq2: &str,
+        gap_open: f64,
+        gap_extend: f64,
+        column_scores: Option<bool>,
+    ) -> Result<AlignmentJob, JsValue> {
+        AlignmentJob::new(
+            alignment::needleman_wunsch_blosum62_task(seq1, seq2, gap_open, gap_extend)
+                .map(|task| task.with_column_scores(column_scores.unwrap_or(false))),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn smith_waterman_dual_affine(
+        seq1: &str,
+        seq2: &str,
+        match_score: f64,
+        mismatch_penalty: f64,
+        gap_open: f64,
+        gap_extend: f64,
+        long_gap_open: f64,
+        long_gap_extend: f64,
+    ) -> Result<AlignmentJob, JsValue> {
+        let gaps = DualAffineGaps {
+            gap_open,
+            gap_extend,
+            long_gap_open,
+            long_gap_extend,
+        };
+        AlignmentJob::new(alignment::smith_waterman_dual_affine_task(
+            seq1,
+            seq2,
+            match_score,
+            mismatch_penalty,
+            &gaps,
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn needleman_wunsch_dual_affine(
+        seq1: &str,
+        seq2: &str,
+        match_score: f64,
+        mismatch_penalty: f64,
+        gap_open: f64,
+        gap_extend: f64,
+        long_gap_open: f64,
+        long_gap_extend: f64,
+    ) -> Result<AlignmentJob, JsValue> {
+        let gaps = DualAffineGaps {
+            gap_open,
+            gap_extend,
+            long_gap_open,
+            long_gap_extend,
+        };
+        AlignmentJob::new(alignment::needleman_wunsch_dual_affine_task(
+            seq1,
+            seq2,
+            match_score,
+            mismatch_penalty,
+            &gaps,
+        ))
+    }
+
+    pub fn preset(seq1: &str, seq2: &str, preset: &str) -> Result<AlignmentJob, JsValue> {
+        AlignmentJob::new(presets::get_preset(preset).and_then(|preset| preset.task(seq1, seq2)))
+    }
+
+    pub fn pair_hmm(
+        seq1: &str,
+        seq2: &str,
+        match_prob: f64,
+        gap_open: f64,
+        gap_extend: f64,
+    ) -> Result<AlignmentJob, JsValue> {
+        let params = PairHmmParams {
+            gap_open,
+            gap_extend,
+            ..PairHmmParams::default()
+        };
+        AlignmentJob::new(pairhmm::pair_hmm_task(seq1, seq2, match_prob, &params))
+    }
+
+    pub fn pair_hmm_blosum62(
+        seq1: &str,
+        seq2: &str,
+        gap_open: f64,
+        gap_extend: f64,
+    ) -> Result<AlignmentJob, JsValue> {
+        let params = PairHmmParams {
+            gap_open,
+            gap_extend,
+            ..PairHmmParams::default()
+        };
+        AlignmentJob::new(pairhmm::pair_hmm_blosum62_task(seq1, seq2, &params))
+    }
+
+    /// Fill at most `max_rows` more DP rows; returns `true` once the job is
+    /// ready to `finish`.
+    pub fn step(&mut self, max_rows: usize) -> Result<bool, JsValue> {
+        Ok(self.task_mut()?.step(max_rows))
+    }
+
+    /// Fill the DP matrices `chunk_rows` at a time, calling
+    /// `on_progress(rows_done, total_rows)` after each chunk. If the callback
+    /// returns `false` the job is cancelled and `run` returns `false`.
+    pub fn run(&mut self, chunk_rows: usize, on_progress: &js_sys::Function) -> Result<bool, JsValue> {
+        let chunk_rows = chunk_rows.max(1);
+        loop {
+            let task = self.task_mut()?;
+            let done = task.step(chunk_rows);
+            let rows_done = JsValue::from_f64(task.rows_done() as f64);
+            let total_rows = JsValue::from_f64(task.total_rows() as f64);
+            let keep_going = on_progress.call2(&JsValue::NULL, &rows_done, &total_rows)?;
+            if keep_going == JsValue::FALSE {
+                self.cancel();
+                return Ok(false);
+            }
+            if done {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Drop the DP matrices; any further call on this job returns an error.
+    pub fn cancel(&mut self) {
+        self.task = None;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cancelled(&self) -> bool {
+        self.task.is_none()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rows_done(&self) -> usize {
+        self.task.as_ref().map_or(0, |task| task.rows_done())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn total_rows(&self) -> usize {
+        self.task.as_ref().map_or(0, |task| task.total_rows())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn progress(&self) -> f64 {
+        match &self.task {
+            Some(task) if task.total_rows() > 0 => {
+                task.rows_done() as f64 / task.total_rows() as f64
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Fill any remaining rows and return the alignment, consuming the job.
+    pub fn finish(&mut self) -> Result<JsValue, JsValue> {
+        self.task_mut()?;
+        self.task.take().map_or(Ok(JsValue::UNDEFINED), |task| task.finish())
+    }
+}
+
+/// Text of a reader input: either a string or the raw bytes of a file
+/// (`Uint8Array` or `ArrayBuffer`), which are gunzipped when they start with
+/// the gzip magic bytes.
+fn text_from_js(input: &JsValue) -> Result<String, JsValue> {
+    if let Some(text) = input.as_string() {
+        return Ok(text);
+    }
+    if input.is_instance_of::<js_sys::Uint8Array>() || input.is_instance_of::<js_sys::ArrayBuffer>()
+    {
+        let bytes = js_sys::Uint8Array::new(input).to_vec();
+        return gzip::read_text(&bytes).map_err(|err| JsValue::from_str(&err));
+    }
+    Err(JsValue::from_str("Expected a string, Uint8Array or ArrayBuffer"))
+}
+
+/// Decode the bytes of an uploaded file as UTF-8 text, decompressing gzip
+/// and BGZF (`.gz`) files first.
+#[wasm_bindgen]
+pub fn read_text_input(data: &[u8]) -> Result<String, JsValue> {
+    gzip::read_text(data).map_err(|err| JsValue::from_str(&err))
+}
+
+/// Incremental gzip decompression for files read in chunks: `push` each
+/// chunk and collect the returned bytes, then call `finish` to check that
+/// the input was complete. Concatenated members (BGZF) are supported.
+#[wasm_bindgen]
+pub struct GzipStream {
+    decoder: GzipDecoder,
+}
+
+#[wasm_bindgen]
+impl GzipStream {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> GzipStream {
+        GzipStream {
+            decoder: GzipDecoder::new(),
+        }
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let mut out = Vec::new();
+        self.decoder
+            .write(chunk, &mut out)
+            .map_err(|err| JsValue::from_str(&err))?;
+        Ok(out)
+    }
+
+    pub fn finish(&self) -> Result<(), JsValue> {
+        self.decoder.finish().map_err(|err| JsValue::from_str(&err))
+    }
+}
+
+impl Default for GzipStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse FASTA (or raw sequence) text into `{id, description, sequence,
+/// line}` records. Errors carry the offending line number. `input` may also
+/// be the bytes of a (possibly gzipped) file.
+#[wasm_bindgen(js_name = parse_fasta)]
+pub fn parse_fasta_js(input: JsValue) -> Result<JsValue, JsValue> {
+    let text = text_from_js(&input)?;
+    let records = parse_fasta(&text).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    to_value(&records)
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize FASTA records: {}", err)))
+}
+
+fn phred_encoding_from_js(encoding: Option<String>) -> Result<Option<PhredEncoding>, JsValue> {
+    encoding
+        .map(|name| PhredEncoding::from_name(&name))
+        .transpose()
+        .map_err(|err| JsValue::from_str(&err))
+}
+
+fn trim_options_from_js(options: JsValue) -> Result<TrimOptions, JsValue> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(TrimOptions::default());
+    }
+    serde_wasm_bindgen::from_value(options)
+        .map_err(|err| JsValue::from_str(&format!("Invalid trimming options: {}", err)))
+}
+
+/// Parse FASTQ text into `{encoding, records}`, each record with `id`,
+/// `description`, `sequence`, `quality` (Phred scores) and `line`.
+/// `encoding` (`"phred33"` or `"phred64"`) is detected when omitted. `input`
+/// may also be the bytes of a (possibly gzipped) file.
+#[wasm_bindgen]
+pub fn parse_fastq(input: JsValue, encoding: Option<String>) -> Result<JsValue, JsValue> {
+    let text = text_from_js(&input)?;
+    let encoding = phred_encoding_from_js(encoding)?;
+    let reads =
+        fastq::parse_fastq(&text, encoding).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    to_value(&reads)
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize FASTQ records: {}", err)))
+}
+
+/// Quality-trim and length-filter FASTQ reads. `options` is an optional
+/// object with any of `mott_limit`, `sliding_window` (`[size, quality]`),
+/// `min_length` and `max_length`.
+#[wasm_bindgen]
+pub fn trim_fastq(
+    input: JsValue,
+    options: JsValue,
+    encoding: Option<String>,
+) -> Result<JsValue, JsValue> {
+    let text = text_from_js(&input)?;
+    let options = trim_options_from_js(options)?;
+    let encoding = phred_encoding_from_js(encoding)?;
+    let summary = fastq::trim_fastq(&text, encoding, &options)
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    to_value(&summary)
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize FASTQ records: {}", err)))
+}
+
+/// The reads that pass `trim_fastq`, trimmed, as FASTA.
+#[wasm_bindgen]
+pub fn fastq_to_fasta(
+    input: JsValue,
+    options: JsValue,
+    encoding: Option<String>,
+) -> Result<String, JsValue> {
+    let text = text_from_js(&input)?;
+    let options = trim_options_from_js(options)?;
+    let encoding = phred_encoding_from_js(encoding)?;
+    let summary = fastq::trim_fastq(&text, encoding, &options)
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    Ok(fastq::reads_to_fasta(&summary.records))
 }
 
+/// Validate a sequence against an alphabet (`dna`, `rna` or `protein`) and
+/// return it upper case with whitespace removed.
 #[wasm_bindgen]
-pub fn parse_hmm(text: &str) -> Result<JsValue, JsValue> {
-    let hmm = hmm::parse_hmm(text).map_err(|err| JsValue::from_str(&err))?;
+pub fn validate_sequence(seq: &str, alphabet: &str) -> Result<String, JsValue> {
+    Sequence::new(alphabet_from_js(alphabet)?, seq)
+        .map(Sequence::into_string)
+        .map_err(|err| JsValue::from_str(&err.to_string()))
+}
+
+#[wasm_bindgen]
+pub fn guess_alphabet(seq: &str) -> String {
+    Alphabet::guess(seq).name().to_string()
+}
+
+/// Canonical residues followed by the ambiguity codes of an alphabet; a
+/// residue's digital code is its index in this string.
+#[wasm_bindgen]
+pub fn alphabet_symbols(alphabet: &str) -> Result<String, JsValue> {
+    let alphabet = alphabet_from_js(alphabet)?;
+    Ok(String::from_utf8_lossy(alphabet.symbols()).into_owned())
+}
+
+#[wasm_bindgen]
+pub fn encode_sequence(seq: &str, alphabet: &str) -> Result<Vec<u8>, JsValue> {
+    Sequence::new(alphabet_from_js(alphabet)?, seq)
+        .map(|seq| seq.encode())
+        .map_err(|err| JsValue::from_str(&err.to_string()))
+}
+
+#[wasm_bindgen]
+pub fn decode_sequence(codes: &[u8], alphabet: &str) -> Result<String, JsValue> {
+    Sequence::decode(alphabet_from_js(alphabet)?, codes)
+        .map(Sequence::into_string)
+        .map_err(|err| JsValue::from_str(&err))
+}
+
+/// Encode a job spec (`{tool, mode, matrix, params, sequences, text}`) as a
+/// compact token for share links.
+#[wasm_bindgen]
+pub fn encode_job_spec(spec: JsValue) -> Result<String, JsValue> {
+    let spec: JobSpec = serde_wasm_bindgen::from_value(spec)
+        .map_err(|err| JsValue::from_str(&format!("Invalid job spec: {}", err)))?;
+    spec.encode().map_err(|err| JsValue::from_str(&err))
+}
+
+#[wasm_bindgen]
+pub fn decode_job_spec(token: &str) -> Result<JsValue, JsValue> {
+    let spec = JobSpec::decode(token).map_err(|err| JsValue::from_str(&err))?;
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    spec.serialize(&serializer)
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize job spec: {}", err)))
+}
+
+/// Parse a HMMER3 profile from text or from the bytes of a (possibly
+/// gzipped) `.hmm` file.
+#[wasm_bindgen]
+pub fn parse_hmm(input: JsValue) -> Result<JsValue, JsValue> {
+    let text = text_from_js(&input)?;
+    let hmm = hmm::parse_hmm(&text).map_err(|err| JsValue::from_str(&err))?;
     to_value(&hmm).map_err(|err| JsValue::from_str(&format!("Failed to serialize HMM: {}", err)))
 }
 
+/// `genetic_code` is an NCBI translation table ID (default 1, the standard
+/// code); see `list_genetic_codes`. With `initiator_as_methionine`, a first
+/// codon that is a start codon of the code (e.g. GTG in table 11) is
+/// translated as `M`.
 #[wasm_bindgen]
 pub fn translate_dna_frame(
     seq: &str,
     frame: i8,
     stop_at_first_stop: bool,
+    genetic_code: Option<u8>,
+    initiator_as_methionine: Option<bool>,
 ) -> Result<String, JsValue> {
-    translation::translate_frame(seq, frame, stop_at_first_stop)
+    let options = TranslationOptions {
+        stop_at_first_stop,
+        initiator_as_methionine: initiator_as_methionine.unwrap_or(false),
+        ..TranslationOptions::default()
+    };
+    translation::translate_frame_with_options(seq, frame, genetic_code.unwrap_or(1), &options)
+        .map(|translation| translation.amino_acids)
         .map_err(|err| JsValue::from_str(&err))
 }
 
+/// Like `translate_dna_frame` for all six frames; with `report_start_codon`
+/// each frame also carries the `start_codon` it begins with, if any.
 #[wasm_bindgen]
-pub fn translate_dna_all_frames(seq: &str, stop_at_first_stop: bool) -> Result<JsValue, JsValue> {
-    let summary = translation::translate_all_frames(seq, stop_at_first_stop)
-        .map_err(|err| JsValue::from_str(&err))?;
+pub fn translate_dna_all_frames(
+    seq: &str,
+    stop_at_first_stop: bool,
+    genetic_code: Option<u8>,
+    initiator_as_methionine: Option<bool>,
+    report_start_codon: Option<bool>,
+) -> Result<JsValue, JsValue> {
+    let options = TranslationOptions {
+        stop_at_first_stop,
+        initiator_as_methionine: initiator_as_methionine.unwrap_or(false),
+        report_start_codon: report_start_codon.unwrap_or(false),
+        ..TranslationOptions::default()
+    };
+    let summary =
+        translation::translate_all_frames_with_options(seq, genetic_code.unwrap_or(1), &options)
+            .map_err(|err| JsValue::from_str(&err))?;
     to_value(&summary)
         .map_err(|err| JsValue::from_str(&format!("Failed to serialize translations: {}", err)))
 }
+
+/// Translate one `frame` of `seq` (1 to 3 or -1 to -3), or all six frames
+/// when `frame` is `undefined`. `options` is an optional object with the
+/// fields `stop_at_first_stop`, `initiator_as_methionine`,
+/// `report_start_codon`, `selenocysteine` and `pyrrolysine` (translate
+/// internal TGA as `U` and TAG as `O`), `recode_frame` and `recode_positions`
+/// (recode only in that frame, or only the codons covering these 0-based
+/// positions). Recoded codons are reported in `recoded` and `recoded_sites`,
+/// apart from the `stops`.
+#[wasm_bindgen]
+pub fn translate_dna(
+    seq: &str,
+    frame: Option<i8>,
+    genetic_code: Option<u8>,
+    options: JsValue,
+) -> Result<JsValue, JsValue> {
+    let options: TranslationOptions = if options.is_undefined() || options.is_null() {
+        TranslationOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|err| JsValue::from_str(&format!("Invalid translation options: {}", err)))?
+    };
+    let genetic_code = genetic_code.unwrap_or(1);
+    let summary = match frame {
+        Some(frame) => translation::translate_frame_with_options(seq, frame, genetic_code, &options)
+            .map(|translation| TranslationSummary {
+                frames: vec![translation],
+            }),
+        None => translation::translate_all_frames_with_options(seq, genetic_code, &options),
+    }
+    .map_err(|err| JsValue::from_str(&err))?;
+    to_value(&summary)
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize translations: {}", err)))
+}
+
+/// Translate `seq` from a frame, switching frame where a base seems to be
+/// missing or in excess. `options` is an optional `{frame, genetic_code,
+/// shift_penalty, stop_penalty}` object. The result has the corrected
+/// `protein`, with `!` at each frameshift, the `frameshifts`, the
+/// `internal_stops` left and whether the sequence is a likely `pseudogene`.
+#[wasm_bindgen]
+pub fn translate_dna_frameshift(seq: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let options: FrameshiftOptions = if options.is_undefined() || options.is_null() {
+        FrameshiftOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options).map_err(|err| {
+            JsValue::from_str(&format!("Invalid frameshift options: {}", err))
+        })?
+    };
+    let translation =
+        translate_with_frameshifts(seq, &options).map_err(|err| JsValue::from_str(&err))?;
+    to_value(&translation)
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize translation: {}", err)))
+}
+
+/// Translation of FASTA files too large to load at once. Construct with a
+/// `frame` (1 to 3 or -1 to -3, or `undefined` for all six frames), an
+/// optional `genetic_code` and optional `{stop_at_first_stop,
+/// initiator_as_methionine, report_start_codon}` options; then `push` the
+/// bytes of the file (plain or gzipped) chunk by chunk, taking the finished
+/// records with `next_record` or `take_fasta` after each chunk, and call
+/// `finish` at the end of the file before taking the last record.
+#[wasm_bindgen]
+pub struct TranslationStream {
+    translator: StreamingTranslator,
+}
+
+#[wasm_bindgen]
+impl TranslationStream {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        frame: Option<i8>,
+        genetic_code: Option<u8>,
+        options: JsValue,
+    ) -> Result<TranslationStream, JsValue> {
+        let options = if options.is_undefined() || options.is_null() {
+            TranslationOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|err| JsValue::from_str(&format!("Invalid translation options: {}", err)))?
+        };
+        let translator = StreamingTranslator::new(frame, genetic_code.unwrap_or(1), options)
+            .map_err(|err| JsValue::from_str(&err))?;
+        Ok(TranslationStream { translator })
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), JsValue> {
+        self.translator.push(chunk).map_err(|err| JsValue::from_str(&err))
+    }
+
+    pub fn finish(&mut self) -> Result<(), JsValue> {
+        self.translator.finish().map_err(|err| JsValue::from_str(&err))
+    }
+
+    /// The next translated record as `{id, description, frames}`, or
+    /// `undefined` when no complete record is available yet.
+    pub fn next_record(&mut self) -> Result<JsValue, JsValue> {
+        match self.translator.next_record().map_err(|err| JsValue::from_str(&err))? {
+            Some(record) => to_value(&record).map_err(|err| {
+                JsValue::from_str(&format!("Failed to serialize translations: {}", err))
+            }),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    /// All records available so far, translated, as protein FASTA (one
+    /// entry per frame, e.g. `>seq1:+1`).
+    pub fn take_fasta(&mut self) -> Result<String, JsValue> {
+        let mut fasta = String::new();
+        while let Some(record) =
+            self.translator.next_record().map_err(|err| JsValue::from_str(&err))?
+        {
+            fasta.push_str(&record.to_fasta());
+        }
+        Ok(fasta)
+    }
+}
+
+/// A codon usage table given as text (Kazusa or GCG format) or as a
+/// `{codon: weight}` object.
+fn codon_usage_from_js(table: JsValue) -> Result<Option<CodonUsage>, JsValue> {
+    if table.is_undefined() || table.is_null() {
+        return Ok(None);
+    }
+    let usage = match table.as_string() {
+        Some(text) => CodonUsage::parse(&text),
+        None => {
+            let weights: std::collections::HashMap<String, f64> =
+                serde_wasm_bindgen::from_value(table).map_err(|err| {
+                    JsValue::from_str(&format!("Invalid codon usage table: {}", err))
+                })?;
+            CodonUsage::from_weights(weights)
+        }
+    };
+    usage.map(Some).map_err(|err| JsValue::from_str(&err))
+}
+
+/// Back-translate a protein into DNA. `options` is an optional
+/// `{genetic_code, mode, seed}` object, where `mode` is `"degenerate"`
+/// (IUPAC consensus, the default), `"most_frequent"` or `"weighted_random"`.
+/// The last two use `codon_usage`, a table as text (Kazusa or GCG format) or
+/// a `{codon: weight}` object.
+#[wasm_bindgen(js_name = back_translate)]
+pub fn back_translate_js(
+    protein: &str,
+    options: JsValue,
+    codon_usage: JsValue,
+) -> Result<String, JsValue> {
+    let options: BackTranslationOptions = if options.is_undefined() || options.is_null() {
+        BackTranslationOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options).map_err(|err| {
+            JsValue::from_str(&format!("Invalid back-translation options: {}", err))
+        })?
+    };
+    let usage = codon_usage_from_js(codon_usage)?;
+    back_translate(protein, &options, usage.as_ref()).map_err(|err| JsValue::from_str(&err))
+}
+
+/// Codon usage of each coding sequence in `input` (FASTA text or the bytes
+/// of a possibly gzipped file), read in frame 1, and of all of them pooled:
+/// per-codon counts, RSCU, GC3, effective number of codons and, against a
+/// `reference` codon usage table (text or `{codon: weight}` object), the
+/// Codon Adaptation Index. The pooled table is also returned in GCG (`gcg`)
+/// and CUTG (`cutg`) formats.
+#[wasm_bindgen]
+pub fn analyze_codon_usage(
+    input: JsValue,
+    genetic_code: Option<u8>,
+    reference: JsValue,
+) -> Result<JsValue, JsValue> {
+    let text = text_from_js(&input)?;
+    let records = parse_fasta(&text).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    let reference = codon_usage_from_js(reference)?;
+    let report = codon_usage_report(&records, genetic_code.unwrap_or(1), reference.as_ref())
+        .map_err(|err| JsValue::from_str(&err))?;
+    to_value(&report)
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize codon usage: {}", err)))
+}
+
+/// Composition of each nucleotide sequence in `input` (FASTA text or the
+/// bytes of a possibly gzipped file): base and ambiguity counts, GC content,
+/// GC and AT skew, GC1/GC2/GC3 and dinucleotide counts of the whole sequence,
+/// and GC content, skews and cumulative GC skew of sliding windows. `options`
+/// is an optional `{window, step}` object (1000 and 100 bases by default).
+#[wasm_bindgen]
+pub fn sequence_composition(input: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+    let options: CompositionOptions = if options.is_undefined() || options.is_null() {
+        CompositionOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options).map_err(|err| {
+            JsValue::from_str(&format!("Invalid composition options: {}", err))
+        })?
+    };
+    let text = text_from_js(&input)?;
+    let records = parse_fasta(&text).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    let report = composition_report(&records, &options).map_err(|err| JsValue::from_str(&err))?;
+    to_value(&report)
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize composition: {}", err)))
+}
+
+/// All NCBI translation tables as `{id, name, amino_acids}` objects.
+#[wasm_bindgen]
+pub fn list_genetic_codes() -> Result<JsValue, JsValue> {
+    to_value(&fna2faa::GENETIC_CODES[..])
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize genetic codes: {}", err)))
+}
+
+/// Forward-strand `[start, end)` of the codon translated into amino acid
+/// `aa_index` of `frame` (1 to 3 or -1 to -3) in a sequence of `seq_len`
+/// bases, or `undefined` if there is no such codon.
+#[wasm_bindgen]
+pub fn aa_to_nucleotide(seq_len: usize, frame: i8, aa_index: usize) -> Result<JsValue, JsValue> {
+    to_value(&fna2faa::aa_to_nucleotide(seq_len, frame, aa_index))
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize coordinates: {}", err)))
+}
+
+/// `[aa_index, codon_position]` of the codon of `frame` covering
+/// forward-strand `position`, or `undefined` if no codon covers it.
+#[wasm_bindgen]
+pub fn nucleotide_to_aa(seq_len: usize, frame: i8, position: usize) -> Result<JsValue, JsValue> {
+    to_value(&fna2faa::nucleotide_to_aa(seq_len, frame, position))
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize coordinates: {}", err)))
+}
+
+fn orf_options_from_js(options: JsValue) -> Result<OrfOptions, JsValue> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(OrfOptions::default());
+    }
+    serde_wasm_bindgen::from_value(options)
+        .map_err(|err| JsValue::from_str(&format!("Invalid ORF options: {}", err)))
+}
+
+/// Six-frame ORF search. `options` is an optional object with any of
+/// `genetic_code`, `mode` (`"start_to_stop"` or `"stop_to_stop"`),
+/// `min_length` (amino acids), `start_codons`, `include_partial` and
+/// `nested` (`"longest"` or `"all"`).
+#[wasm_bindgen]
+pub fn find_orfs(seq: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let options = orf_options_from_js(options)?;
+    let orfs = orf::find_orfs(seq, &options).map_err(|err| JsValue::from_str(&err))?;
+    to_value(&orfs).map_err(|err| JsValue::from_str(&format!("Failed to serialize ORFs: {}", err)))
+}
+
+/// The ORFs of `find_orfs` as protein FASTA, with headers named after `name`.
+#[wasm_bindgen]
+pub fn find_orfs_fasta(seq: &str, name: &str, options: JsValue) -> Result<String, JsValue> {
+    let options = orf_options_from_js(options)?;
+    let orfs = orf::find_orfs(seq, &options).map_err(|err| JsValue::from_str(&err))?;
+    Ok(orf::orfs_to_fasta(name, &orfs))
+}
+
+#[derive(Serialize)]
+struct GeneCallOutput<'a> {
+    gc_content: f64,
+    gc_frame_bias: [f64; 3],
+    training_genes: usize,
+    genes: &'a [Gene],
+    gff3: String,
+    proteins: String,
+}
+
+/// Prokaryotic gene prediction on an array of `{name, sequence}` contigs,
+/// trained on the contigs themselves. `options` is an optional object with
+/// any of `genetic_code` (11 by default), `min_gene_length`, `max_overlap`
+/// and `closed_ends`. The result carries the genes together with their GFF3
+/// and protein FASTA renderings.
+#[wasm_bindgen]
+pub fn predict_genes(contigs: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+    let contigs: Vec<Contig> = serde_wasm_bindgen::from_value(contigs)
+        .map_err(|err| JsValue::from_str(&format!("Invalid contigs: {}", err)))?;
+    let options: GeneCallOptions = if options.is_undefined() || options.is_null() {
+        GeneCallOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|err| JsValue::from_str(&format!("Invalid gene calling options: {}", err)))?
+    };
+    let prediction =
+        genecall::predict_genes(&contigs, &options).map_err(|err| JsValue::from_str(&err))?;
+    let output = GeneCallOutput {
+        gc_content: prediction.gc_content,
+        gc_frame_bias: prediction.gc_frame_bias,
+        training_genes: prediction.training_genes,
+        genes: &prediction.genes,
+        gff3: genecall::genes_to_gff3(&prediction.genes),
+        proteins: genecall::genes_to_fasta(&prediction.genes),
+    };
+    to_value(&output)
+        .map_err(|err| JsValue::from_str(&format!("Failed to serialize genes: {}", err)))
+}
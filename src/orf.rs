@@ -0,0 +1,417 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fna2faa::{rev_compl_to, CodonEncoder};
+use crate::translation::sanitize_sequence;
+
+/// Where an ORF begins.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrfMode {
+    /// From a start codon to the next in-frame stop codon.
+    #[default]
+    StartToStop,
+    /// From just after a stop codon to the next in-frame stop codon.
+    StopToStop,
+}
+
+impl OrfMode {
+    pub fn from_name(name: &str) -> Result<OrfMode, String> {
+        match name.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "start_to_stop" => Ok(OrfMode::StartToStop),
+            "stop_to_stop" => Ok(OrfMode::StopToStop),
+            _ => Err(format!(
+                "Unknown ORF mode '{}' (expected start_to_stop or stop_to_stop)",
+                name
+            )),
+        }
+    }
+}
+
+/// Which of several in-frame start codons sharing a stop codon are reported
+/// in [`OrfMode::StartToStop`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NestedOrfs {
+    /// Only the most upstream start, i.e. the longest ORF.
+    #[default]
+    Longest,
+    /// One ORF per start codon.
+    All,
+}
+
+impl NestedOrfs {
+    pub fn from_name(name: &str) -> Result<NestedOrfs, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "longest" => Ok(NestedOrfs::Longest),
+            "all" => Ok(NestedOrfs::All),
+            _ => Err(format!(
+                "Unknown nested ORF policy '{}' (expected longest or all)",
+                name
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct OrfOptions {
+    /// NCBI translation table ID.
+    pub genetic_code: u8,
+    pub mode: OrfMode,
+    /// Minimum protein length in amino acids, not counting the stop.
+    pub min_length: usize,
+    /// Codons accepted as starts; `None` uses the start codons of the
+    /// genetic code.
+    pub start_codons: Option<Vec<String>>,
+    /// Also report ORFs that run off either end of the sequence.
+    pub include_partial: bool,
+    pub nested: NestedOrfs,
+}
+
+impl Default for OrfOptions {
+    fn default() -> Self {
+        OrfOptions {
+            genetic_code: 1,
+            mode: OrfMode::StartToStop,
+            min_length: 30,
+            start_codons: None,
+            include_partial: false,
+            nested: NestedOrfs::Longest,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Strand {
+    #[serde(rename = "+")]
+    Forward,
+    #[serde(rename = "-")]
+    Reverse,
+}
+
+impl Strand {
+    pub fn symbol(self) -> char {
+        match self {
+            Strand::Forward => '+',
+            Strand::Reverse => '-',
+        }
+    }
+}
+
+/// An open reading frame. `start` and `end` are 0-based, end-exclusive
+/// coordinates on the forward strand of the input (whitespace and gaps
+/// removed) and include the stop codon when there is one, so on the reverse
+/// strand the ORF reads from `end` down to `start`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Orf {
+    pub strand: Strand,
+    /// Reading frame as in [`crate::FrameTranslation`]: 1 to 3 or -1 to -3.
+    pub frame: i8,
+    pub start: usize,
+    pub end: usize,
+    /// First codon when the ORF begins with a start codon.
+    pub start_codon: Option<String>,
+    /// The ORF runs off the 5' end of the sequence.
+    pub partial_start: bool,
+    /// The ORF runs off the 3' end of the sequence without a stop codon.
+    pub partial_end: bool,
+    /// Translation without the stop codon; a start codon is translated as M.
+    pub protein: String,
+}
+
+fn parse_start_codons(codons: &[String]) -> Result<Vec<[u8; 3]>, String> {
+    codons
+        .iter()
+        .map(|codon| {
+            let bytes: Vec<u8> = codon
+                .trim()
+                .bytes()
+                .map(|b| match b.to_ascii_uppercase() {
+                    b'U' => b'T',
+                    other => other,
+                })
+                .collect();
+            match bytes[..] {
+                [a, b, c] if bytes.iter().all(|b| b"ACGT".contains(b)) => Ok([a, b, c]),
+                _ => Err(format!("Invalid start codon '{}'", codon)),
+            }
+        })
+        .collect()
+}
+
+/// Find the ORFs in all six frames of a nucleotide sequence, sorted by
+/// position.
+pub fn find_orfs(seq: &str, options: &OrfOptions) -> Result<Vec<Orf>, String> {
+    let sanitized = sanitize_sequence(seq)?;
+    let encoder = CodonEncoder::for_table(options.genetic_code)?;
+    let custom_starts = options
+        .start_codons
+        .as_deref()
+        .map(parse_start_codons)
+        .transpose()?;
+    let is_start = |codon: &[u8]| match &custom_starts {
+        Some(starts) => starts.iter().any(|start| start[..] == *codon),
+        None => encoder.is_start_codon(codon),
+    };
+
+    let forward = sanitized.as_bytes();
+    let mut reverse = Vec::new();
+    rev_compl_to(forward, &mut reverse);
+
+    let mut orfs = Vec::new();
+    for (strand, working) in [(Strand::Forward, forward), (Strand::Reverse, &reverse[..])] {
+        for offset in 0..3 {
+            scan_frame(
+                &encoder, &is_start, working, strand, offset, options, &mut orfs,
+            );
+        }
+    }
+    orfs.sort_by_key(|orf| (orf.start, orf.end, orf.strand == Strand::Reverse));
+    Ok(orfs)
+}
+
+/// Report the ORFs of one frame of `working` (the sequence as read on
+/// `strand`).
+fn scan_frame<S>(
+    encoder: &CodonEncoder,
+    is_start: &S,
+    working: &[u8],
+    strand: Strand,
+    offset: usize,
+    options: &OrfOptions,
+    orfs: &mut Vec<Orf>,
+) where
+    S: Fn(&[u8]) -> bool,
+{
+    let len = working.len();
+    let codons: Vec<&[u8]> = working
+        .get(offset..)
+        .unwrap_or_default()
+        .chunks_exact(3)
+        .collect();
+    let amino_acids: Vec<u8> = codons
+        .iter()
+        .map(|codon| encoder.translate_triplet(codon).0)
+        .collect();
+
+    let frame = match strand {
+        Strand::Forward => offset as i8 + 1,
+        Strand::Reverse => -(offset as i8 + 1),
+    };
+    // Codons `from..to` (plus the stop codon at `to`, if any) as an ORF.
+    let mut report = |from: usize, to: usize, with_start: bool, partial_start: bool| {
+        let partial_end = to == codons.len();
+        let mut protein: Vec<u8> = amino_acids[from..to].to_vec();
+        if with_start && !protein.is_empty() {
+            protein[0] = b'M';
+        }
+        if protein.len() < options.min_length
+            || ((partial_start || partial_end) && !options.include_partial)
+        {
+            return;
+        }
+        let begin = offset + 3 * from;
+        let finish = offset + 3 * (to + usize::from(!partial_end));
+        let (start, end) = match strand {
+            Strand::Forward => (begin, finish),
+            Strand::Reverse => (len - finish, len - begin),
+        };
+        orfs.push(Orf {
+            strand,
+            frame,
+            start,
+            end,
+            start_codon: with_start.then(|| String::from_utf8_lossy(codons[from]).into_owned()),
+            partial_start,
+            partial_end,
+            protein: String::from_utf8_lossy(&protein).into_owned(),
+        });
+    };
+
+    // Each stop codon, and the end of the frame, closes a stop-free region.
+    let stops = amino_acids
+        .iter()
+        .enumerate()
+        .filter(|(_, &aa)| aa == b'*')
+        .map(|(idx, _)| idx)
+        .chain([codons.len()]);
+    let mut region_start = 0;
+    for stop in stops {
+        match options.mode {
+            OrfMode::StopToStop => {
+                if stop > region_start {
+                    report(region_start, stop, false, region_start == 0);
+                }
+            }
+            OrfMode::StartToStop => {
+                let mut nested = false;
+                if region_start == 0 && stop > 0 && !is_start(codons[0]) && options.include_partial
+                {
+                    report(0, stop, false, true);
+                    nested = true;
+                }
+                for (from, codon) in codons.iter().enumerate().take(stop).skip(region_start) {
+                    if nested && options.nested == NestedOrfs::Longest {
+                        break;
+                    }
+                    if is_start(codon) {
+                        report(from, stop, true, false);
+                        nested = true;
+                    }
+                }
+            }
+        }
+        region_start = stop + 1;
+    }
+}
+
+/// Format ORFs as protein FASTA. Headers give the ORF number, strand,
+/// frame and 1-based inclusive coordinates, e.g.
+/// `>seq_ORF1 strand=+ frame=1 start=4 end=96 length=30`.
+pub fn orfs_to_fasta(name: &str, orfs: &[Orf]) -> String {
+    let mut fasta = String::new();
+    for (idx, orf) in orfs.iter().enumerate() {
+        fasta.push_str(&format!(
+            ">{}_ORF{} strand={} frame={} start={} end={} length={}",
+            name,
+            idx + 1,
+            orf.strand.symbol(),
+            orf.frame,
+            orf.start + 1,
+            orf.end,
+            orf.protein.len()
+        ));
+        if orf.partial_start || orf.partial_end {
+            let partial = match (orf.partial_start, orf.partial_end) {
+                (true, true) => "both",
+                (true, false) => "5'",
+                _ => "3'",
+            };
+            fasta.push_str(&format!(" partial={}", partial));
+        }
+        fasta.push('\n');
+        for line in orf.protein.as_bytes().chunks(60) {
+            fasta.push_str(&String::from_utf8_lossy(line));
+            fasta.push('\n');
+        }
+    }
+    fasta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(min_length: usize) -> OrfOptions {
+        OrfOptions {
+            min_length,
+            ..OrfOptions::default()
+        }
+    }
+
+    #[test]
+    fn finds_forward_and_reverse_orfs() {
+        // ATG AAA TTT TAA on the forward strand; the reverse complement of
+        // ATG CCC GGG TGA at the end.
+        let seq = "CCATGAAATTTTAAGGTCACCCGGGCATCC";
+        let orfs = find_orfs(seq, &options(2)).unwrap();
+        assert_eq!(orfs.len(), 2);
+
+        let forward = &orfs[0];
+        assert_eq!(forward.strand, Strand::Forward);
+        assert_eq!(forward.frame, 3);
+        assert_eq!((forward.start, forward.end), (2, 14));
+        assert_eq!(forward.protein, "MKF");
+        assert_eq!(forward.start_codon.as_deref(), Some("ATG"));
+
+        let reverse = &orfs[1];
+        assert_eq!(reverse.strand, Strand::Reverse);
+        assert_eq!((reverse.start, reverse.end), (16, 28));
+        assert_eq!(&seq[reverse.start..reverse.end], "TCACCCGGGCAT");
+        assert_eq!(reverse.protein, "MPG");
+
+        assert!(find_orfs(seq, &options(4)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn nested_and_alternative_starts() {
+        let seq = "ATGAAAATGCCCTTGTAA";
+        let longest = find_orfs(seq, &options(1)).unwrap();
+        assert_eq!(longest.len(), 1);
+        assert_eq!(longest[0].protein, "MKMPL");
+
+        let all = OrfOptions {
+            nested: NestedOrfs::All,
+            ..options(1)
+        };
+        let proteins: Vec<_> = find_orfs(seq, &all)
+            .unwrap()
+            .into_iter()
+            .filter(|orf| orf.strand == Strand::Forward)
+            .map(|orf| orf.protein)
+            .collect();
+        // TTG is a start codon of the standard code too.
+        assert_eq!(proteins, ["MKMPL", "MPL", "M"]);
+
+        let atg_only = OrfOptions {
+            nested: NestedOrfs::All,
+            start_codons: Some(vec!["ATG".to_string()]),
+            ..options(1)
+        };
+        assert_eq!(find_orfs(seq, &atg_only).unwrap().len(), 2);
+        let invalid = OrfOptions {
+            start_codons: Some(vec!["AT".to_string()]),
+            ..options(1)
+        };
+        assert!(find_orfs(seq, &invalid).is_err());
+    }
+
+    #[test]
+    fn partial_and_stop_to_stop_orfs() {
+        let seq = "AAACCCTAAGGGATGTTT";
+        assert!(find_orfs(seq, &options(1))
+            .unwrap()
+            .iter()
+            .all(|orf| !orf.partial_start && !orf.partial_end));
+
+        let partial = OrfOptions {
+            include_partial: true,
+            ..options(2)
+        };
+        let orfs: Vec<_> = find_orfs(seq, &partial)
+            .unwrap()
+            .into_iter()
+            .filter(|orf| orf.frame == 1)
+            .collect();
+        assert_eq!(orfs.len(), 2);
+        assert!(orfs[0].partial_start && !orfs[0].partial_end);
+        assert_eq!(
+            (orfs[0].start, orfs[0].end, orfs[0].protein.as_str()),
+            (0, 9, "KP")
+        );
+        assert!(orfs[1].partial_end);
+        assert_eq!(
+            (orfs[1].start, orfs[1].end, orfs[1].protein.as_str()),
+            (12, 18, "MF")
+        );
+
+        let stop_to_stop = OrfOptions {
+            mode: OrfMode::StopToStop,
+            include_partial: true,
+            ..options(2)
+        };
+        let orfs: Vec<_> = find_orfs(seq, &stop_to_stop)
+            .unwrap()
+            .into_iter()
+            .filter(|orf| orf.frame == 1)
+            .collect();
+        assert_eq!(orfs.len(), 2);
+        assert_eq!(orfs[1].protein, "GMF");
+        assert_eq!(orfs[1].start_codon, None);
+
+        let fasta = orfs_to_fasta("seq", &orfs);
+        assert!(
+            fasta.starts_with(">seq_ORF1 strand=+ frame=1 start=1 end=9 length=2 partial=5'\nKP\n")
+        );
+    }
+}
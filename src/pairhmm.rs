@@ -0,0 +1,545 @@
+use serde::{Deserialize, Serialize};
+
+use crate::alignment::{
+    blosum62_score, build_alignment_result, check_blosum62, check_problem, AlignmentError,
+    AlignmentResult, AlignmentTask, ScoreFn, MAX_DP_CELLS,
+};
+
+/// Transition probabilities of the three-state (match, insert X, insert Y)
+/// pair HMM described in Durbin et al., *Biological Sequence Analysis*, ch. 4.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PairHmmParams {
+    /// Probability of opening a gap from the match state (delta).
+    pub gap_open: f64,
+    /// Probability of staying in a gap state (epsilon).
+    pub gap_extend: f64,
+    /// Probability of moving to the end state (tau).
+    pub end: f64,
+}
+
+impl Default for PairHmmParams {
+    fn default() -> Self {
+        PairHmmParams {
+            gap_open: 0.02,
+            gap_extend: 0.4,
+            end: 0.001,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairHmmResult {
+    #[serde(flatten)]
+    pub alignment: AlignmentResult,
+    /// Posterior probability of each alignment column: for a residue pair,
+    /// the probability that the two residues are aligned; for a gap column,
+    /// the probability that the residue is aligned to a gap.
+    pub column_posteriors: Vec<f64>,
+    /// Mean of `column_posteriors` (the expected fraction of correct columns).
+    pub expected_accuracy: f64,
+    /// Natural log of the total probability of the two sequences under the
+    /// pair HMM, relative to the independent random model.
+    pub log_odds: f64,
+}
+
+fn log_add(a: f64, b: f64) -> f64 {
+    if a == f64::NEG_INFINITY {
+        return b;
+    }
+    if b == f64::NEG_INFINITY {
+        return a;
+    }
+    let (hi, lo) = if a > b { (a, b) } else { (b, a) };
+    hi + (lo - hi).exp().ln_1p()
+}
+
+fn check_params(params: &PairHmmParams) -> Result<(), AlignmentError> {
+    let PairHmmParams {
+        gap_open,
+        gap_extend,
+        end,
+    } = *params;
+    if !(gap_open > 0.0 && gap_open < 0.5) {
+        return Err(AlignmentError::InvalidGapParameters(format!(
+            "gap open probability must be between 0 and 0.5. Got {}",
+            gap_open
+        )));
+    }
+    if !(gap_extend > 0.0 && gap_extend < 1.0) {
+        return Err(AlignmentError::InvalidGapParameters(format!(
+            "gap extend probability must be between 0 and 1. Got {}",
+            gap_extend
+        )));
+    }
+    // With no chance of ending, no alignment has a finite probability.
+    if end.is_nan() || end <= 0.0 {
+        return Err(AlignmentError::InvalidGapParameters(format!(
+            "end probability must be larger than 0. Got {}",
+            end
+        )));
+    }
+    if !(1.0 - 2.0 * gap_open - end > 0.0 && 1.0 - gap_extend - end > 0.0) {
+        return Err(AlignmentError::InvalidGapParameters(format!(
+            "end probability {} leaves no probability mass for the match state",
+            end
+        )));
+    }
+    Ok(())
+}
+
+/// Posterior probabilities computed by the forward-backward algorithm.
+#[derive(Default)]
+struct Posteriors {
+    /// `matched[i][j]`: residue `i` of seq1 is aligned to residue `j` of seq2
+    /// (1-based, row and column 0 unused).
+    matched: Vec<Vec<f64>>,
+    /// `gapped1[i]`: residue `i` of seq1 is aligned to a gap.
+    gapped1: Vec<f64>,
+    /// `gapped2[j]`: residue `j` of seq2 is aligned to a gap.
+    gapped2: Vec<f64>,
+    log_odds: f64,
+}
+
+/// Pair HMM alignment filled row by row.
+///
+/// The forward pass fills rows `0..=n` and the backward pass then fills rows
+/// `n..=0`, accumulating the posteriors of each row as soon as its backward
+/// values are known; both run in log space.  The maximum expected accuracy
+/// alignment is computed from the posteriors in [`AlignmentTask::finish`].
+pub struct PairHmmTask<F> {
+    seq1: Vec<u8>,
+    seq2: Vec<u8>,
+    odds_fn: F,
+    ln_open: f64,
+    ln_extend: f64,
+    ln_end: f64,
+    ln_mm: f64,
+    ln_gm: f64,
+    fwd_m: Vec<Vec<f64>>,
+    fwd_x: Vec<Vec<f64>>,
+    fwd_y: Vec<Vec<f64>>,
+    bwd_m: Vec<Vec<f64>>,
+    bwd_x: Vec<Vec<f64>>,
+    bwd_y: Vec<Vec<f64>>,
+    posteriors: Posteriors,
+    rows_done: usize,
+}
+
+impl<F> PairHmmTask<F>
+where
+    F: Fn(u8, u8) -> f64,
+{
+    /// Start a pair HMM alignment.
+    ///
+    /// `odds_fn` gives the emission odds ratio `p(a, b) / (q(a) q(b))` of the
+    /// match state; insert states emit with the background distribution so
+    /// their odds are 1.
+    pub fn new(
+        seq1: &str,
+        seq2: &str,
+        params: &PairHmmParams,
+        odds_fn: F,
+    ) -> Result<Self, AlignmentError> {
+        check_problem(seq1, seq2)?;
+        check_params(params)?;
+        // Forward, backward and posterior tables: about three times the cells
+        // of a Gotoh alignment.
+        let cells = (seq1.len() + 1) * (seq2.len() + 1) * 3;
+        if cells > MAX_DP_CELLS {
+            return Err(AlignmentError::SizeLimitExceeded {
+                cells,
+                limit: MAX_DP_CELLS,
+            });
+        }
+
+        let len1 = seq1.len();
+        let len2 = seq2.len();
+        let neg_inf = vec![vec![f64::NEG_INFINITY; len2 + 1]; len1 + 1];
+        Ok(PairHmmTask {
+            seq1: seq1.as_bytes().to_vec(),
+            seq2: seq2.as_bytes().to_vec(),
+            odds_fn,
+            ln_open: params.gap_open.ln(),
+            ln_extend: params.gap_extend.ln(),
+            ln_end: params.end.ln(),
+            ln_mm: (1.0 - 2.0 * params.gap_open - params.end).ln(),
+            ln_gm: (1.0 - params.gap_extend - params.end).ln(),
+            fwd_m: neg_inf.clone(),
+            fwd_x: neg_inf.clone(),
+            fwd_y: neg_inf.clone(),
+            bwd_m: neg_inf.clone(),
+            bwd_x: neg_inf.clone(),
+            bwd_y: neg_inf,
+            posteriors: Posteriors {
+                matched: vec![vec![0.0; len2 + 1]; len1 + 1],
+                gapped1: vec![0.0; len1 + 1],
+                gapped2: vec![0.0; len2 + 1],
+                log_odds: f64::NEG_INFINITY,
+            },
+            rows_done: 0,
+        })
+    }
+
+    fn emission(&self, i: usize, j: usize) -> f64 {
+        (self.odds_fn)(self.seq1[i - 1], self.seq2[j - 1]).ln()
+    }
+
+    fn forward_row(&mut self, i: usize) {
+        let len1 = self.seq1.len();
+        let len2 = self.seq2.len();
+        if i == 0 {
+            self.fwd_m[0][0] = 0.0;
+        }
+        for j in 0..=len2 {
+            if i > 0 && j > 0 {
+                self.fwd_m[i][j] = self.emission(i, j)
+                    + log_add(
+                        self.ln_mm + self.fwd_m[i - 1][j - 1],
+                        self.ln_gm + log_add(self.fwd_x[i - 1][j - 1], self.fwd_y[i - 1][j - 1]),
+                    );
+            }
+            if i > 0 {
+                self.fwd_x[i][j] = log_add(
+                    self.ln_open + self.fwd_m[i - 1][j],
+                    self.ln_extend + self.fwd_x[i - 1][j],
+                );
+            }
+            if j > 0 {
+                self.fwd_y[i][j] = log_add(
+                    self.ln_open + self.fwd_m[i][j - 1],
+                    self.ln_extend + self.fwd_y[i][j - 1],
+                );
+            }
+        }
+        if i == len1 {
+            self.posteriors.log_odds = self.ln_end
+                + log_add(
+                    self.fwd_m[len1][len2],
+                    log_add(self.fwd_x[len1][len2], self.fwd_y[len1][len2]),
+                );
+        }
+    }
+
+    fn backward_row(&mut self, i: usize) {
+        let len1 = self.seq1.len();
+        let len2 = self.seq2.len();
+        for j in (0..=len2).rev() {
+            if i == len1 && j == len2 {
+                self.bwd_m[i][j] = self.ln_end;
+                self.bwd_x[i][j] = self.ln_end;
+                self.bwd_y[i][j] = self.ln_end;
+                continue;
+            }
+            let next_match = if i < len1 && j < len2 {
+                self.emission(i + 1, j + 1) + self.bwd_m[i + 1][j + 1]
+            } else {
+                f64::NEG_INFINITY
+            };
+            let next_x = if i < len1 {
+                self.bwd_x[i + 1][j]
+            } else {
+                f64::NEG_INFINITY
+            };
+            let next_y = if j < len2 {
+                self.bwd_y[i][j + 1]
+            } else {
+                f64::NEG_INFINITY
+            };
+            self.bwd_m[i][j] = log_add(
+                self.ln_mm + next_match,
+                log_add(self.ln_open + next_x, self.ln_open + next_y),
+            );
+            self.bwd_x[i][j] = log_add(self.ln_gm + next_match, self.ln_extend + next_x);
+            self.bwd_y[i][j] = log_add(self.ln_gm + next_match, self.ln_extend + next_y);
+        }
+
+        let total = self.posteriors.log_odds;
+        let post = &mut self.posteriors;
+        for j in 0..=len2 {
+            if i > 0 && j > 0 {
+                post.matched[i][j] = (self.fwd_m[i][j] + self.bwd_m[i][j] - total).exp();
+            }
+            if i > 0 {
+                post.gapped1[i] += (self.fwd_x[i][j] + self.bwd_x[i][j] - total).exp();
+            }
+            if j > 0 {
+                post.gapped2[j] += (self.fwd_y[i][j] + self.bwd_y[i][j] - total).exp();
+            }
+        }
+    }
+
+    /// Finish both passes and take the posterior probabilities.
+    fn take_posteriors(&mut self) -> Result<Posteriors, AlignmentError> {
+        self.step(usize::MAX);
+        if !self.posteriors.log_odds.is_finite() {
+            return Err(AlignmentError::InvalidScoringParameters(
+                "sequences have zero probability under the pair HMM".into(),
+            ));
+        }
+        Ok(std::mem::take(&mut self.posteriors))
+    }
+}
+
+impl<F> AlignmentTask for PairHmmTask<F>
+where
+    F: Fn(u8, u8) -> f64,
+{
+    type Output = PairHmmResult;
+
+    fn total_rows(&self) -> usize {
+        2 * (self.seq1.len() + 1)
+    }
+
+    fn rows_done(&self) -> usize {
+        self.rows_done
+    }
+
+    fn step(&mut self, max_rows: usize) -> bool {
+        let len1 = self.seq1.len();
+        let total = self.total_rows();
+        let end = self.rows_done.saturating_add(max_rows).min(total);
+        while self.rows_done < end {
+            let row = self.rows_done;
+            if row <= len1 {
+                self.forward_row(row);
+            } else if self.posteriors.log_odds.is_finite() {
+                self.backward_row(total - 1 - row);
+            } else {
+                // Nothing to propagate backwards; finish() reports the error.
+                self.rows_done = total;
+                break;
+            }
+            self.rows_done += 1;
+        }
+        self.rows_done == total
+    }
+
+    fn finish(mut self) -> Result<PairHmmResult, AlignmentError> {
+        let Posteriors {
+            matched: match_post,
+            gapped1: gap_post1,
+            gapped2: gap_post2,
+            log_odds,
+        } = self.take_posteriors()?;
+        let PairHmmTask {
+            seq1,
+            seq2,
+            odds_fn,
+            ..
+        } = self;
+        let len1 = seq1.len();
+        let len2 = seq2.len();
+
+        // Maximum expected accuracy: the alignment maximising the summed
+        // posteriors of its columns.
+        const DIAG: u8 = 0;
+        const UP: u8 = 1;
+        const LEFT: u8 = 2;
+        let mut accuracy = vec![vec![0.0; len2 + 1]; len1 + 1];
+        let mut pointers = vec![vec![DIAG; len2 + 1]; len1 + 1];
+        for i in 1..=len1 {
+            accuracy[i][0] = accuracy[i - 1][0] + gap_post1[i];
+            pointers[i][0] = UP;
+        }
+        for j in 1..=len2 {
+            accuracy[0][j] = accuracy[0][j - 1] + gap_post2[j];
+            pointers[0][j] = LEFT;
+        }
+        for i in 1..=len1 {
+            for j in 1..=len2 {
+                let diag = accuracy[i - 1][j - 1] + match_post[i][j];
+                let up = accuracy[i - 1][j] + gap_post1[i];
+                let left = accuracy[i][j - 1] + gap_post2[j];
+                let (best, pointer) = if diag >= up && diag >= left {
+                    (diag, DIAG)
+                } else if up >= left {
+                    (up, UP)
+                } else {
+                    (left, LEFT)
+                };
+                accuracy[i][j] = best;
+                pointers[i][j] = pointer;
+            }
+        }
+
+        let mut i = len1;
+        let mut j = len2;
+        let mut aligned_seq1 = Vec::new();
+        let mut aligned_seq2 = Vec::new();
+        let mut column_posteriors = Vec::new();
+        while i > 0 || j > 0 {
+            match pointers[i][j] {
+                DIAG => {
+                    aligned_seq1.push(seq1[i - 1]);
+                    aligned_seq2.push(seq2[j - 1]);
+                    column_posteriors.push(match_post[i][j]);
+                    i -= 1;
+                    j -= 1;
+                }
+                UP => {
+                    aligned_seq1.push(seq1[i - 1]);
+                    aligned_seq2.push(b'-');
+                    column_posteriors.push(gap_post1[i]);
+                    i -= 1;
+                }
+                _ => {
+                    aligned_seq1.push(b'-');
+                    aligned_seq2.push(seq2[j - 1]);
+                    column_posteriors.push(gap_post2[j]);
+                    j -= 1;
+                }
+            }
+        }
+        aligned_seq1.reverse();
+        aligned_seq2.reverse();
+        column_posteriors.reverse();
+
+        let score = accuracy[len1][len2];
+        let expected_accuracy = if column_posteriors.is_empty() {
+            0.0
+        } else {
+            score / column_posteriors.len() as f64
+        };
+        let alignment = build_alignment_result(aligned_seq1, aligned_seq2, score, |a, b| {
+            odds_fn(a, b) - 1.0
+        });
+
+        Ok(PairHmmResult {
+            alignment,
+            column_posteriors,
+            expected_accuracy,
+            log_odds,
+        })
+    }
+}
+
+/// Pair HMM alignment for nucleotide-like sequences where two aligned
+/// residues are identical with probability `match_prob` (uniform background
+/// over four bases).
+pub fn pair_hmm_task(
+    seq1: &str,
+    seq2: &str,
+    match_prob: f64,
+    params: &PairHmmParams,
+) -> Result<PairHmmTask<impl Fn(u8, u8) -> f64>, AlignmentError> {
+    if !(match_prob > 0.0 && match_prob < 1.0) {
+        return Err(AlignmentError::InvalidScoringParameters(format!(
+            "match probability must be between 0 and 1, got {}",
+            match_prob
+        )));
+    }
+    let same = 4.0 * match_prob;
+    let different = 4.0 * (1.0 - match_prob) / 3.0;
+    PairHmmTask::new(seq1, seq2, params, move |a: u8, b: u8| {
+        if a.eq_ignore_ascii_case(&b) {
+            same
+        } else {
+            different
+        }
+    })
+}
+
+pub fn pair_hmm_internal(
+    seq1: &str,
+    seq2: &str,
+    match_prob: f64,
+    params: &PairHmmParams,
+) -> Result<PairHmmResult, AlignmentError> {
+    pair_hmm_task(seq1, seq2, match_prob, params)?.finish()
+}
+
+fn blosum62_odds(a: u8, b: u8) -> f64 {
+    2f64.powf(blosum62_score(a, b) / 2.0)
+}
+
+/// Pair HMM alignment for proteins, with match emissions taken from the
+/// BLOSUM62 odds ratios (the matrix is in half-bit units).
+pub fn pair_hmm_blosum62_task(
+    seq1: &str,
+    seq2: &str,
+    params: &PairHmmParams,
+) -> Result<PairHmmTask<ScoreFn>, AlignmentError> {
+    check_blosum62(seq1, seq2)?;
+    PairHmmTask::new(seq1, seq2, params, blosum62_odds)
+}
+
+pub fn pair_hmm_blosum62_internal(
+    seq1: &str,
+    seq2: &str,
+    params: &PairHmmParams,
+) -> Result<PairHmmResult, AlignmentError> {
+    pair_hmm_blosum62_task(seq1, seq2, params)?.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_sequences_are_confident() {
+        let r = pair_hmm_internal(
+            "GATTACAGATTACA",
+            "GATTACAGATTACA",
+            0.9,
+            &PairHmmParams::default(),
+        )
+        .unwrap();
+        assert_eq!(r.alignment.aligned_seq1, "GATTACAGATTACA");
+        assert_eq!(r.alignment.aligned_seq2, "GATTACAGATTACA");
+        assert_eq!(r.column_posteriors.len(), 14);
+        assert!(r.column_posteriors.iter().all(|&p| p > 0.9));
+        assert!((r.alignment.score - r.column_posteriors.iter().sum::<f64>()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn posteriors_are_probabilities() {
+        let r =
+            pair_hmm_blosum62_internal("HEAGAWGHEE", "PAWHEAE", &PairHmmParams::default()).unwrap();
+        assert_eq!(r.column_posteriors.len(), r.alignment.aligned_seq1.len());
+        assert!(r
+            .column_posteriors
+            .iter()
+            .all(|&p| (0.0..=1.0 + 1e-9).contains(&p)));
+        assert!(r.expected_accuracy > 0.0 && r.expected_accuracy <= 1.0);
+        assert_eq!(r.alignment.aligned_seq1.replace('-', ""), "HEAGAWGHEE");
+        assert_eq!(r.alignment.aligned_seq2.replace('-', ""), "PAWHEAE");
+    }
+
+    #[test]
+    fn residue_posteriors_sum_to_one() {
+        // Every residue is either aligned to some residue of the other
+        // sequence or to a gap.
+        let seq1 = "ACGTTGCA";
+        let seq2 = "ACGTGCA";
+        let odds = |a: u8, b: u8| if a == b { 3.6 } else { 0.4 };
+        let post = PairHmmTask::new(seq1, seq2, &PairHmmParams::default(), odds)
+            .and_then(|mut task| task.take_posteriors())
+            .unwrap();
+        for i in 1..=seq1.len() {
+            let total: f64 = post.matched[i].iter().sum::<f64>() + post.gapped1[i];
+            assert!((total - 1.0).abs() < 1e-9, "row {} sums to {}", i, total);
+        }
+        for j in 1..=seq2.len() {
+            let total: f64 = post.matched.iter().map(|row| row[j]).sum::<f64>() + post.gapped2[j];
+            assert!((total - 1.0).abs() < 1e-9, "column {} sums to {}", j, total);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let params = PairHmmParams {
+            gap_open: 0.6,
+            ..PairHmmParams::default()
+        };
+        assert!(pair_hmm_internal("ACGT", "ACGT", 0.9, &params).is_err());
+        assert!(pair_hmm_internal("ACGT", "ACGT", 1.5, &PairHmmParams::default()).is_err());
+        for end in [0.0, -0.1, f64::NAN] {
+            let params = PairHmmParams {
+                end,
+                ..PairHmmParams::default()
+            };
+            let err = pair_hmm_internal("ACGT", "ACGT", 0.9, &params).unwrap_err();
+            assert!(matches!(err, AlignmentError::InvalidGapParameters(_)));
+        }
+    }
+}
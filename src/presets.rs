@@ -0,0 +1,212 @@
+use serde::Serialize;
+
+use crate::alignment::{
+    blosum62_score, check_blosum62, match_mismatch_score, AlignmentError, AlignmentResult,
+    AlignmentTask, BoxedScoreFn, GotohTask,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlignmentMode {
+    Local,
+    Global,
+}
+
+impl AlignmentMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            AlignmentMode::Local => "local",
+            AlignmentMode::Global => "global",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "scoring", rename_all = "snake_case")]
+pub enum PresetScoring {
+    Blosum62,
+    MatchMismatch {
+        match_score: f64,
+        mismatch_penalty: f64,
+    },
+}
+
+/// Parameters reproducing the defaults of a commonly used alignment tool.
+///
+/// Gap scores follow this crate's convention: `gap_open` is the score of the
+/// first gapped residue and `gap_extend` that of each further one, so
+/// BLAST's "existence 5, extension 2" becomes `-7` / `-2`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub mode: AlignmentMode,
+    #[serde(flatten)]
+    pub scoring: PresetScoring,
+    pub gap_open: f64,
+    pub gap_extend: f64,
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "blastn",
+        description: "NCBI blastn (task blastn): reward 2, penalty -3, gap costs 5/2",
+        mode: AlignmentMode::Local,
+        scoring: PresetScoring::MatchMismatch {
+            match_score: 2.0,
+            mismatch_penalty: -3.0,
+        },
+        gap_open: -7.0,
+        gap_extend: -2.0,
+    },
+    Preset {
+        name: "megablast",
+        description: "NCBI megablast: reward 1, penalty -2, linear gap cost 2.5",
+        mode: AlignmentMode::Local,
+        scoring: PresetScoring::MatchMismatch {
+            match_score: 1.0,
+            mismatch_penalty: -2.0,
+        },
+        gap_open: -2.5,
+        gap_extend: -2.5,
+    },
+    Preset {
+        name: "blastp",
+        description: "NCBI blastp: BLOSUM62, gap costs 11/1",
+        mode: AlignmentMode::Local,
+        scoring: PresetScoring::Blosum62,
+        gap_open: -12.0,
+        gap_extend: -1.0,
+    },
+    Preset {
+        name: "water",
+        description: "EMBOSS water (protein): EBLOSUM62, gap open 10, gap extend 0.5",
+        mode: AlignmentMode::Local,
+        scoring: PresetScoring::Blosum62,
+        gap_open: -10.0,
+        gap_extend: -0.5,
+    },
+    Preset {
+        name: "needle",
+        description: "EMBOSS needle (protein): EBLOSUM62, gap open 10, gap extend 0.5",
+        mode: AlignmentMode::Global,
+        scoring: PresetScoring::Blosum62,
+        gap_open: -10.0,
+        gap_extend: -0.5,
+    },
+    Preset {
+        name: "water_dna",
+        description:
+            "EMBOSS water (nucleotide): EDNAFULL on ACGT (5/-4), gap open 10, gap extend 0.5",
+        mode: AlignmentMode::Local,
+        scoring: PresetScoring::MatchMismatch {
+            match_score: 5.0,
+            mismatch_penalty: -4.0,
+        },
+        gap_open: -10.0,
+        gap_extend: -0.5,
+    },
+    Preset {
+        name: "needle_dna",
+        description:
+            "EMBOSS needle (nucleotide): EDNAFULL on ACGT (5/-4), gap open 10, gap extend 0.5",
+        mode: AlignmentMode::Global,
+        scoring: PresetScoring::MatchMismatch {
+            match_score: 5.0,
+            mismatch_penalty: -4.0,
+        },
+        gap_open: -10.0,
+        gap_extend: -0.5,
+    },
+    Preset {
+        name: "biopython",
+        description:
+            "Biopython PairwiseAligner defaults: global, match 1, mismatch 0, no gap penalty",
+        mode: AlignmentMode::Global,
+        scoring: PresetScoring::MatchMismatch {
+            match_score: 1.0,
+            mismatch_penalty: 0.0,
+        },
+        gap_open: 0.0,
+        gap_extend: 0.0,
+    },
+];
+
+/// Look up a preset by name, ignoring case and treating `-` like `_`.
+pub fn get_preset(name: &str) -> Result<&'static Preset, AlignmentError> {
+    let wanted = name.trim().to_ascii_lowercase().replace('-', "_");
+    PRESETS
+        .iter()
+        .find(|preset| preset.name == wanted)
+        .ok_or_else(|| AlignmentError::UnknownPreset(name.to_string()))
+}
+
+impl Preset {
+    pub fn task(&self, seq1: &str, seq2: &str) -> Result<GotohTask<BoxedScoreFn>, AlignmentError> {
+        let score_fn: BoxedScoreFn = match self.scoring {
+            PresetScoring::Blosum62 => {
+                check_blosum62(seq1, seq2)?;
+                Box::new(blosum62_score)
+            }
+            PresetScoring::MatchMismatch {
+                match_score,
+                mismatch_penalty,
+            } => Box::new(match_mismatch_score(match_score, mismatch_penalty)),
+        };
+        match self.mode {
+            AlignmentMode::Local => {
+                GotohTask::smith_waterman(seq1, seq2, self.gap_open, self.gap_extend, score_fn)
+            }
+            AlignmentMode::Global => {
+                GotohTask::needleman_wunsch(seq1, seq2, self.gap_open, self.gap_extend, score_fn)
+            }
+        }
+    }
+
+    pub fn align(&self, seq1: &str, seq2: &str) -> Result<AlignmentResult, AlignmentError> {
+        self.task(seq1, seq2)?.finish()
+    }
+}
+
+pub fn align_with_preset(
+    seq1: &str,
+    seq2: &str,
+    preset: &str,
+) -> Result<AlignmentResult, AlignmentError> {
+    get_preset(preset)?.align(seq1, seq2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::{needleman_wunsch_blosum62_internal, smith_waterman_internal};
+
+    #[test]
+    fn presets_match_explicit_parameters() {
+        let seq1 = "ACGTTGCATGCATTTACG";
+        let seq2 = "ACGTGCATGCAATTACG";
+        let preset = align_with_preset(seq1, seq2, "BLASTN").unwrap();
+        let explicit = smith_waterman_internal(seq1, seq2, 2.0, -3.0, -7.0, -2.0).unwrap();
+        assert_eq!(preset.score, explicit.score);
+        assert_eq!(preset.aligned_seq1, explicit.aligned_seq1);
+
+        let preset = align_with_preset("HEAGAWGHEE", "PAWHEAE", "needle").unwrap();
+        let explicit =
+            needleman_wunsch_blosum62_internal("HEAGAWGHEE", "PAWHEAE", -10.0, -0.5).unwrap();
+        assert_eq!(preset.score, explicit.score);
+    }
+
+    #[test]
+    fn biopython_defaults_count_matches() {
+        // Global alignment with free gaps scores the longest common subsequence.
+        let result = align_with_preset("GAACT", "GAT", "biopython").unwrap();
+        assert_eq!(result.score, 3.0);
+    }
+
+    #[test]
+    fn unknown_preset_is_an_error() {
+        let err = align_with_preset("ACGT", "ACGT", "clustal").unwrap_err();
+        assert!(matches!(err, AlignmentError::UnknownPreset(_)));
+        assert!(get_preset("water-dna").is_ok());
+    }
+}
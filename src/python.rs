@@ -1,6 +1,75 @@
-use crate::AlignmentResult;
+use crate::{
+    AlignmentError, AlignmentResult, ColumnKind, ColumnScore, DpMatrices, DpMatrix, DpMatrixKind,
+    CodonSite, CodonStat, CodonUsageReport, CodonUsageSummary, CompositionProfile,
+    CompositionReport, CompositionStats, CompositionWindow, DinucleotideStat, EditDistanceResult,
+    FastaError, FastaRecord, FastqError, FastqRecord, Frameshift, FrameshiftRecord,
+    FrameTranslation, Gene, InvalidResidue, NestedOrfs, Orf, OrfMode, OrfOptions, PairHmmResult,
+    Preset, PresetScoring, StreamingTranslator, TracebackPointer, TranslatedRecord,
+    TranslationOptions,
+};
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 
+impl From<AlignmentError> for PyErr {
+    fn from(err: AlignmentError) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+impl From<InvalidResidue> for PyErr {
+    fn from(err: InvalidResidue) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+impl From<FastaError> for PyErr {
+    fn from(err: FastaError) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+impl From<FastqError> for PyErr {
+    fn from(err: FastqError) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+fn parse_phred_encoding(encoding: Option<&str>) -> PyResult<Option<crate::PhredEncoding>> {
+    encoding
+        .map(crate::PhredEncoding::from_name)
+        .transpose()
+        .map_err(PyErr::new::<PyValueError, _>)
+}
+
+/// Text of a reader input given as `str`, or as `bytes` that are gunzipped
+/// when they start with the gzip magic bytes.
+fn input_text(input: &Bound<'_, PyAny>) -> PyResult<String> {
+    if let Ok(text) = input.extract::<String>() {
+        return Ok(text);
+    }
+    let bytes = input.extract::<std::borrow::Cow<[u8]>>()?;
+    crate::gzip::read_text(&bytes).map_err(PyErr::new::<PyValueError, _>)
+}
+
+/// A codon usage table given as text (Kazusa or GCG format) or as a
+/// `{codon: weight}` dict.
+fn codon_usage(table: Option<&Bound<'_, PyAny>>) -> PyResult<Option<crate::CodonUsage>> {
+    let Some(table) = table else {
+        return Ok(None);
+    };
+    let usage = match table.extract::<String>() {
+        Ok(text) => crate::CodonUsage::parse(&text),
+        Err(_) => crate::CodonUsage::from_weights(
+            table.extract::<std::collections::HashMap<String, f64>>()?,
+        ),
+    };
+    usage.map(Some).map_err(PyErr::new::<PyValueError, _>)
+}
+
+fn parse_alphabet(name: &str) -> PyResult<crate::Alphabet> {
+    crate::Alphabet::from_name(name).map_err(PyErr::new::<PyValueError, _>)
+}
+
 #[pyclass]
 pub struct PyAlignmentResult {
     #[pyo3(get)]
@@ -15,6 +84,9 @@ pub struct PyAlignmentResult {
     pub score: f64,
     #[pyo3(get)]
     pub alignment_markup: String,
+    /// Per-column score breakdown, `None` unless requested.
+    #[pyo3(get)]
+    pub columns: Option<Vec<PyColumnScore>>,
 }
 
 impl From<AlignmentResult> for PyAlignmentResult {
@@ -26,26 +98,1004 @@ impl From<AlignmentResult> for PyAlignmentResult {
             aligned_identity: r.aligned_identity,
             score: r.score,
             alignment_markup: r.alignment_markup,
+            columns: r
+                .columns
+                .map(|columns| columns.iter().map(PyColumnScore::from).collect()),
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyColumnScore {
+    /// One of `substitution`, `gap_open`, `gap_extend` or `unaligned`.
+    #[pyo3(get)]
+    pub kind: String,
+    #[pyo3(get)]
+    pub score: f64,
+    #[pyo3(get)]
+    pub running_score: f64,
+}
+
+impl From<&ColumnScore> for PyColumnScore {
+    fn from(c: &ColumnScore) -> Self {
+        let kind = match c.kind {
+            ColumnKind::Substitution => "substitution",
+            ColumnKind::GapOpen => "gap_open",
+            ColumnKind::GapExtend => "gap_extend",
+            ColumnKind::Unaligned => "unaligned",
+        };
+        Self {
+            kind: kind.to_string(),
+            score: c.score,
+            running_score: c.running_score,
+        }
+    }
+}
+
+#[pyclass]
+pub struct PyDpMatrices {
+    #[pyo3(get)]
+    pub seq1: String,
+    #[pyo3(get)]
+    pub seq2: String,
+    #[pyo3(get)]
+    pub local: bool,
+    /// Matrix values indexed `[i][j]`; `None` marks unreachable cells.
+    #[pyo3(get)]
+    pub score: Vec<Vec<Option<f64>>>,
+    #[pyo3(get)]
+    pub insert: Vec<Vec<Option<f64>>>,
+    #[pyo3(get)]
+    pub delete: Vec<Vec<Option<f64>>>,
+    /// Pointers: `diagonal`, `insert`, `delete`, `open` or `extend`.
+    #[pyo3(get)]
+    pub score_pointers: Vec<Vec<Option<String>>>,
+    #[pyo3(get)]
+    pub insert_pointers: Vec<Vec<Option<String>>>,
+    #[pyo3(get)]
+    pub delete_pointers: Vec<Vec<Option<String>>>,
+    /// Traceback path as `(matrix, i, j)` from the start of the alignment.
+    #[pyo3(get)]
+    pub path: Vec<(String, usize, usize)>,
+    pub alignment: AlignmentResult,
+}
+
+#[pymethods]
+impl PyDpMatrices {
+    #[getter]
+    fn alignment(&self) -> PyAlignmentResult {
+        self.alignment.clone().into()
+    }
+}
+
+fn pointer_names(matrix: &DpMatrix) -> Vec<Vec<Option<String>>> {
+    matrix
+        .pointers
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|pointer| {
+                    pointer.map(|pointer| {
+                        match pointer {
+                            TracebackPointer::Diagonal => "diagonal",
+                            TracebackPointer::Insert => "insert",
+                            TracebackPointer::Delete => "delete",
+                            TracebackPointer::Open => "open",
+                            TracebackPointer::Extend => "extend",
+                        }
+                        .to_string()
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+impl From<DpMatrices> for PyDpMatrices {
+    fn from(m: DpMatrices) -> Self {
+        let path = m
+            .path
+            .iter()
+            .map(|cell| {
+                let matrix = match cell.matrix {
+                    DpMatrixKind::Score => "score",
+                    DpMatrixKind::Insert => "insert",
+                    DpMatrixKind::Delete => "delete",
+                };
+                (matrix.to_string(), cell.i, cell.j)
+            })
+            .collect();
+        Self {
+            score_pointers: pointer_names(&m.score),
+            insert_pointers: pointer_names(&m.insert),
+            delete_pointers: pointer_names(&m.delete),
+            seq1: m.seq1,
+            seq2: m.seq2,
+            local: m.local,
+            score: m.score.values,
+            insert: m.insert.values,
+            delete: m.delete.values,
+            path,
+            alignment: m.alignment,
+        }
+    }
+}
+
+#[pyclass]
+pub struct PyPairHmmResult {
+    #[pyo3(get)]
+    pub aligned_seq1: String,
+    #[pyo3(get)]
+    pub aligned_seq2: String,
+    #[pyo3(get)]
+    pub aligned_length: usize,
+    #[pyo3(get)]
+    pub aligned_identity: f64,
+    #[pyo3(get)]
+    pub score: f64,
+    #[pyo3(get)]
+    pub alignment_markup: String,
+    #[pyo3(get)]
+    pub column_posteriors: Vec<f64>,
+    #[pyo3(get)]
+    pub expected_accuracy: f64,
+    #[pyo3(get)]
+    pub log_odds: f64,
+}
+
+impl From<PairHmmResult> for PyPairHmmResult {
+    fn from(r: PairHmmResult) -> Self {
+        Self {
+            aligned_seq1: r.alignment.aligned_seq1,
+            aligned_seq2: r.alignment.aligned_seq2,
+            aligned_length: r.alignment.aligned_length,
+            aligned_identity: r.alignment.aligned_identity,
+            score: r.alignment.score,
+            alignment_markup: r.alignment.alignment_markup,
+            column_posteriors: r.column_posteriors,
+            expected_accuracy: r.expected_accuracy,
+            log_odds: r.log_odds,
+        }
+    }
+}
+
+#[pyclass]
+pub struct PyEditDistanceResult {
+    #[pyo3(get)]
+    pub distance: usize,
+    #[pyo3(get)]
+    pub start: usize,
+    #[pyo3(get)]
+    pub end: usize,
+    #[pyo3(get)]
+    pub estimated_identity: f64,
+    pub alignment: Option<AlignmentResult>,
+}
+
+#[pymethods]
+impl PyEditDistanceResult {
+    #[getter]
+    fn alignment(&self) -> Option<PyAlignmentResult> {
+        self.alignment.clone().map(Into::into)
+    }
+}
+
+impl From<EditDistanceResult> for PyEditDistanceResult {
+    fn from(r: EditDistanceResult) -> Self {
+        Self {
+            distance: r.distance,
+            start: r.start,
+            end: r.end,
+            estimated_identity: r.estimated_identity,
+            alignment: r.alignment,
+        }
+    }
+}
+
+#[pyclass]
+pub struct PyFastaRecord {
+    #[pyo3(get)]
+    pub id: String,
+    #[pyo3(get)]
+    pub description: String,
+    #[pyo3(get)]
+    pub sequence: String,
+    #[pyo3(get)]
+    pub line: usize,
+}
+
+#[pymethods]
+impl PyFastaRecord {
+    /// The full header: `id` and `description` separated by a space.
+    #[getter]
+    fn header(&self) -> String {
+        if self.description.is_empty() {
+            self.id.clone()
+        } else {
+            format!("{} {}", self.id, self.description)
+        }
+    }
+}
+
+impl From<FastaRecord> for PyFastaRecord {
+    fn from(record: FastaRecord) -> Self {
+        Self {
+            id: record.id,
+            description: record.description,
+            sequence: record.sequence,
+            line: record.line,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyFastqRecord {
+    #[pyo3(get)]
+    pub id: String,
+    #[pyo3(get)]
+    pub description: String,
+    #[pyo3(get)]
+    pub sequence: String,
+    /// Phred quality of each base.
+    #[pyo3(get)]
+    pub quality: Vec<u8>,
+    #[pyo3(get)]
+    pub line: usize,
+}
+
+impl From<FastqRecord> for PyFastqRecord {
+    fn from(record: FastqRecord) -> Self {
+        Self {
+            id: record.id,
+            description: record.description,
+            sequence: record.sequence,
+            quality: record.quality,
+            line: record.line,
+        }
+    }
+}
+
+#[pyclass]
+pub struct PyTrimSummary {
+    /// `"phred33"` or `"phred64"`.
+    #[pyo3(get)]
+    pub encoding: String,
+    #[pyo3(get)]
+    pub input_reads: usize,
+    #[pyo3(get)]
+    pub input_bases: usize,
+    #[pyo3(get)]
+    pub kept_bases: usize,
+    #[pyo3(get)]
+    pub records: Vec<PyFastqRecord>,
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyCodonSite {
+    #[pyo3(get)]
+    pub aa_index: usize,
+    #[pyo3(get)]
+    pub start: usize,
+    #[pyo3(get)]
+    pub end: usize,
+    #[pyo3(get)]
+    pub codon: String,
+}
+
+impl From<CodonSite> for PyCodonSite {
+    fn from(site: CodonSite) -> Self {
+        Self {
+            aa_index: site.aa_index,
+            start: site.start,
+            end: site.end,
+            codon: site.codon,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyFrameTranslation {
+    #[pyo3(get)]
+    pub frame: i8,
+    #[pyo3(get)]
+    pub amino_acids: String,
+    #[pyo3(get)]
+    pub stops: Vec<usize>,
+    #[pyo3(get)]
+    pub ambiguous: Vec<usize>,
+    #[pyo3(get)]
+    pub stop_sites: Vec<PyCodonSite>,
+    #[pyo3(get)]
+    pub ambiguous_sites: Vec<PyCodonSite>,
+    #[pyo3(get)]
+    pub recoded: Vec<usize>,
+    #[pyo3(get)]
+    pub recoded_sites: Vec<PyCodonSite>,
+    #[pyo3(get)]
+    pub start_codon: Option<String>,
+}
+
+impl From<FrameTranslation> for PyFrameTranslation {
+    fn from(t: FrameTranslation) -> Self {
+        Self {
+            frame: t.frame,
+            amino_acids: t.amino_acids,
+            stops: t.stops,
+            ambiguous: t.ambiguous,
+            stop_sites: t.stop_sites.into_iter().map(Into::into).collect(),
+            ambiguous_sites: t.ambiguous_sites.into_iter().map(Into::into).collect(),
+            recoded: t.recoded,
+            recoded_sites: t.recoded_sites.into_iter().map(Into::into).collect(),
+            start_codon: t.start_codon,
+        }
+    }
+}
+
+#[pyclass]
+pub struct PyTranslatedRecord {
+    #[pyo3(get)]
+    pub id: String,
+    #[pyo3(get)]
+    pub description: String,
+    #[pyo3(get)]
+    pub frames: Vec<PyFrameTranslation>,
+    pub fasta: String,
+}
+
+#[pymethods]
+impl PyTranslatedRecord {
+    /// One protein FASTA entry per frame, e.g. `>seq1:+1`.
+    fn to_fasta(&self) -> String {
+        self.fasta.clone()
+    }
+}
+
+impl From<TranslatedRecord> for PyTranslatedRecord {
+    fn from(record: TranslatedRecord) -> Self {
+        Self {
+            fasta: record.to_fasta(),
+            id: record.id,
+            description: record.description,
+            frames: record.frames.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyFrameshift {
+    #[pyo3(get)]
+    pub protein_index: usize,
+    #[pyo3(get)]
+    pub position: usize,
+    #[pyo3(get)]
+    pub shift: i8,
+    #[pyo3(get)]
+    pub frame: i8,
+}
+
+impl From<Frameshift> for PyFrameshift {
+    fn from(shift: Frameshift) -> Self {
+        Self {
+            protein_index: shift.protein_index,
+            position: shift.position,
+            shift: shift.shift,
+            frame: shift.frame,
+        }
+    }
+}
+
+#[pyclass]
+pub struct PyFrameshiftRecord {
+    #[pyo3(get)]
+    pub id: String,
+    #[pyo3(get)]
+    pub description: String,
+    #[pyo3(get)]
+    pub frame: i8,
+    /// The corrected protein, with `!` at each frameshift.
+    #[pyo3(get)]
+    pub protein: String,
+    #[pyo3(get)]
+    pub frameshifts: Vec<PyFrameshift>,
+    #[pyo3(get)]
+    pub internal_stops: Vec<usize>,
+    #[pyo3(get)]
+    pub pseudogene: bool,
+    pub fasta: String,
+}
+
+#[pymethods]
+impl PyFrameshiftRecord {
+    /// A protein FASTA entry, flagged with `[pseudo ...]` for likely
+    /// pseudogenes.
+    fn to_fasta(&self) -> String {
+        self.fasta.clone()
+    }
+}
+
+impl From<FrameshiftRecord> for PyFrameshiftRecord {
+    fn from(record: FrameshiftRecord) -> Self {
+        let fasta = record.to_fasta();
+        let translation = record.translation;
+        Self {
+            id: record.id,
+            description: record.description,
+            frame: translation.frame,
+            protein: translation.protein,
+            frameshifts: translation.frameshifts.into_iter().map(Into::into).collect(),
+            internal_stops: translation.internal_stops,
+            pseudogene: translation.pseudogene,
+            fasta,
+        }
+    }
+}
+
+/// Translation of FASTA files too large to load at once: `push` chunks of
+/// the file (`str` or `bytes`, plain or gzipped) and iterate over the
+/// records translated so far; call `finish` at the end of the file and
+/// iterate once more for the last record.
+#[pyclass]
+pub struct PyTranslationStream {
+    translator: StreamingTranslator,
+}
+
+#[pymethods]
+impl PyTranslationStream {
+    /// `frame` is 1 to 3 or -1 to -3; `None` translates all six frames.
+    /// See `translate_dna_frame` for recoding stop codons as selenocysteine
+    /// and pyrrolysine.
+    #[new]
+    #[pyo3(signature = (frame=None, genetic_code=1, stop_at_first_stop=false, initiator_as_methionine=false, report_start_codon=false, selenocysteine=false, pyrrolysine=false, recode_frame=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        frame: Option<i8>,
+        genetic_code: u8,
+        stop_at_first_stop: bool,
+        initiator_as_methionine: bool,
+        report_start_codon: bool,
+        selenocysteine: bool,
+        pyrrolysine: bool,
+        recode_frame: Option<i8>,
+    ) -> PyResult<Self> {
+        let options = TranslationOptions {
+            stop_at_first_stop,
+            initiator_as_methionine,
+            report_start_codon,
+            selenocysteine,
+            pyrrolysine,
+            recode_frame,
+            recode_positions: None,
+        };
+        let translator = StreamingTranslator::new(frame, genetic_code, options)
+            .map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Self { translator })
+    }
+
+    fn push(&mut self, chunk: &Bound<'_, PyAny>) -> PyResult<()> {
+        let result = match chunk.extract::<String>() {
+            Ok(text) => self.translator.push(text.as_bytes()),
+            Err(_) => self.translator.push(&chunk.extract::<std::borrow::Cow<[u8]>>()?),
+        };
+        result.map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn finish(&mut self) -> PyResult<()> {
+        self.translator.finish().map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<PyTranslatedRecord>> {
+        self.translator
+            .next_record()
+            .map(|record| record.map(Into::into))
+            .map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyCodonStat {
+    #[pyo3(get)]
+    pub codon: String,
+    #[pyo3(get)]
+    pub amino_acid: char,
+    #[pyo3(get)]
+    pub count: f64,
+    #[pyo3(get)]
+    pub per_thousand: f64,
+    #[pyo3(get)]
+    pub fraction: f64,
+    #[pyo3(get)]
+    pub rscu: f64,
+}
+
+impl From<CodonStat> for PyCodonStat {
+    fn from(stat: CodonStat) -> Self {
+        Self {
+            codon: stat.codon,
+            amino_acid: stat.amino_acid,
+            count: stat.count,
+            per_thousand: stat.per_thousand,
+            fraction: stat.fraction,
+            rscu: stat.rscu,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyCodonUsageSummary {
+    #[pyo3(get)]
+    pub id: String,
+    #[pyo3(get)]
+    pub description: String,
+    #[pyo3(get)]
+    pub codons: usize,
+    #[pyo3(get)]
+    pub gc3: Option<f64>,
+    #[pyo3(get)]
+    pub enc: Option<f64>,
+    #[pyo3(get)]
+    pub cai: Option<f64>,
+    #[pyo3(get)]
+    pub stats: Vec<PyCodonStat>,
+}
+
+impl From<CodonUsageSummary> for PyCodonUsageSummary {
+    fn from(summary: CodonUsageSummary) -> Self {
+        Self {
+            id: summary.id,
+            description: summary.description,
+            codons: summary.codons,
+            gc3: summary.gc3,
+            enc: summary.enc,
+            cai: summary.cai,
+            stats: summary.stats.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[pyclass]
+pub struct PyCodonUsageReport {
+    #[pyo3(get)]
+    pub records: Vec<PyCodonUsageSummary>,
+    #[pyo3(get)]
+    pub total: PyCodonUsageSummary,
+    #[pyo3(get)]
+    pub gcg: String,
+    #[pyo3(get)]
+    pub cutg: String,
+}
+
+impl From<CodonUsageReport> for PyCodonUsageReport {
+    fn from(report: CodonUsageReport) -> Self {
+        Self {
+            records: report.records.into_iter().map(Into::into).collect(),
+            total: report.total.into(),
+            gcg: report.gcg,
+            cutg: report.cutg,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyDinucleotideStat {
+    #[pyo3(get)]
+    pub dinucleotide: String,
+    #[pyo3(get)]
+    pub count: usize,
+    #[pyo3(get)]
+    pub rho: Option<f64>,
+}
+
+impl From<DinucleotideStat> for PyDinucleotideStat {
+    fn from(stat: DinucleotideStat) -> Self {
+        Self {
+            dinucleotide: stat.dinucleotide,
+            count: stat.count,
+            rho: stat.rho,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyCompositionStats {
+    #[pyo3(get)]
+    pub length: usize,
+    #[pyo3(get)]
+    pub a: usize,
+    #[pyo3(get)]
+    pub c: usize,
+    #[pyo3(get)]
+    pub g: usize,
+    #[pyo3(get)]
+    pub t: usize,
+    #[pyo3(get)]
+    pub n: usize,
+    #[pyo3(get)]
+    pub ambiguous: usize,
+    #[pyo3(get)]
+    pub gc: Option<f64>,
+    #[pyo3(get)]
+    pub gc_skew: Option<f64>,
+    #[pyo3(get)]
+    pub at_skew: Option<f64>,
+    #[pyo3(get)]
+    pub gc1: Option<f64>,
+    #[pyo3(get)]
+    pub gc2: Option<f64>,
+    #[pyo3(get)]
+    pub gc3: Option<f64>,
+    #[pyo3(get)]
+    pub dinucleotides: Vec<PyDinucleotideStat>,
+}
+
+impl From<CompositionStats> for PyCompositionStats {
+    fn from(stats: CompositionStats) -> Self {
+        Self {
+            length: stats.length,
+            a: stats.a,
+            c: stats.c,
+            g: stats.g,
+            t: stats.t,
+            n: stats.n,
+            ambiguous: stats.ambiguous,
+            gc: stats.gc,
+            gc_skew: stats.gc_skew,
+            at_skew: stats.at_skew,
+            gc1: stats.gc1,
+            gc2: stats.gc2,
+            gc3: stats.gc3,
+            dinucleotides: stats.dinucleotides.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyCompositionWindow {
+    #[pyo3(get)]
+    pub start: usize,
+    #[pyo3(get)]
+    pub end: usize,
+    #[pyo3(get)]
+    pub gc: Option<f64>,
+    #[pyo3(get)]
+    pub gc_skew: Option<f64>,
+    #[pyo3(get)]
+    pub cumulative_gc_skew: f64,
+    #[pyo3(get)]
+    pub at_skew: Option<f64>,
+    #[pyo3(get)]
+    pub n: usize,
+    #[pyo3(get)]
+    pub ambiguous: usize,
+}
+
+impl From<CompositionWindow> for PyCompositionWindow {
+    fn from(window: CompositionWindow) -> Self {
+        Self {
+            start: window.start,
+            end: window.end,
+            gc: window.gc,
+            gc_skew: window.gc_skew,
+            cumulative_gc_skew: window.cumulative_gc_skew,
+            at_skew: window.at_skew,
+            n: window.n,
+            ambiguous: window.ambiguous,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyCompositionProfile {
+    #[pyo3(get)]
+    pub id: String,
+    #[pyo3(get)]
+    pub description: String,
+    #[pyo3(get)]
+    pub stats: PyCompositionStats,
+    #[pyo3(get)]
+    pub windows: Vec<PyCompositionWindow>,
+}
+
+impl From<CompositionProfile> for PyCompositionProfile {
+    fn from(profile: CompositionProfile) -> Self {
+        Self {
+            id: profile.id,
+            description: profile.description,
+            stats: profile.stats.into(),
+            windows: profile.windows.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[pyclass]
+pub struct PyCompositionReport {
+    #[pyo3(get)]
+    pub records: Vec<PyCompositionProfile>,
+    pub report: CompositionReport,
+}
+
+#[pymethods]
+impl PyCompositionReport {
+    /// Whole-sequence statistics, one row per record, as TSV with a header.
+    fn to_tsv(&self) -> String {
+        self.report.to_tsv()
+    }
+
+    /// Window statistics, one row per window of every record, as TSV with a
+    /// header.
+    fn windows_tsv(&self) -> String {
+        self.report.windows_tsv()
+    }
+}
+
+impl From<CompositionReport> for PyCompositionReport {
+    fn from(report: CompositionReport) -> Self {
+        Self {
+            records: report.records.iter().cloned().map(Into::into).collect(),
+            report,
+        }
+    }
+}
+
+#[pyclass]
+pub struct PyOrf {
+    /// `"+"` or `"-"`.
+    #[pyo3(get)]
+    pub strand: String,
+    #[pyo3(get)]
+    pub frame: i8,
+    #[pyo3(get)]
+    pub start: usize,
+    #[pyo3(get)]
+    pub end: usize,
+    #[pyo3(get)]
+    pub start_codon: Option<String>,
+    #[pyo3(get)]
+    pub partial_start: bool,
+    #[pyo3(get)]
+    pub partial_end: bool,
+    #[pyo3(get)]
+    pub protein: String,
+}
+
+impl From<Orf> for PyOrf {
+    fn from(orf: Orf) -> Self {
+        Self {
+            strand: orf.strand.symbol().to_string(),
+            frame: orf.frame,
+            start: orf.start,
+            end: orf.end,
+            start_codon: orf.start_codon,
+            partial_start: orf.partial_start,
+            partial_end: orf.partial_end,
+            protein: orf.protein,
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PyGene {
+    #[pyo3(get)]
+    pub seqid: String,
+    /// `"+"` or `"-"`.
+    #[pyo3(get)]
+    pub strand: String,
+    #[pyo3(get)]
+    pub start: usize,
+    #[pyo3(get)]
+    pub end: usize,
+    #[pyo3(get)]
+    pub partial_start: bool,
+    #[pyo3(get)]
+    pub partial_end: bool,
+    #[pyo3(get)]
+    pub start_codon: Option<String>,
+    #[pyo3(get)]
+    pub rbs_motif: Option<String>,
+    #[pyo3(get)]
+    pub rbs_spacer: Option<String>,
+    #[pyo3(get)]
+    pub gc_content: f64,
+    #[pyo3(get)]
+    pub coding_score: f64,
+    #[pyo3(get)]
+    pub start_score: f64,
+    #[pyo3(get)]
+    pub score: f64,
+    #[pyo3(get)]
+    pub protein: String,
+}
+
+impl From<Gene> for PyGene {
+    fn from(gene: Gene) -> Self {
+        Self {
+            seqid: gene.seqid,
+            strand: gene.strand.symbol().to_string(),
+            start: gene.start,
+            end: gene.end,
+            partial_start: gene.partial_start,
+            partial_end: gene.partial_end,
+            start_codon: gene.start_codon,
+            rbs_motif: gene.rbs_motif,
+            rbs_spacer: gene.rbs_spacer,
+            gc_content: gene.gc_content,
+            coding_score: gene.coding_score,
+            start_score: gene.start_score,
+            score: gene.score,
+            protein: gene.protein,
+        }
+    }
+}
+
+#[pyclass]
+pub struct PyGenePrediction {
+    #[pyo3(get)]
+    pub gc_content: f64,
+    #[pyo3(get)]
+    pub gc_frame_bias: [f64; 3],
+    #[pyo3(get)]
+    pub training_genes: usize,
+    #[pyo3(get)]
+    pub genes: Vec<PyGene>,
+    #[pyo3(get)]
+    pub gff3: String,
+    #[pyo3(get)]
+    pub proteins: String,
+}
+
+fn orf_options(
+    genetic_code: u8,
+    mode: &str,
+    min_length: usize,
+    start_codons: Option<Vec<String>>,
+    include_partial: bool,
+    nested: &str,
+) -> PyResult<OrfOptions> {
+    Ok(OrfOptions {
+        genetic_code,
+        mode: OrfMode::from_name(mode).map_err(PyErr::new::<PyValueError, _>)?,
+        min_length,
+        start_codons,
+        include_partial,
+        nested: NestedOrfs::from_name(nested).map_err(PyErr::new::<PyValueError, _>)?,
+    })
+}
+
+#[pyclass]
+pub struct PyPreset {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub description: String,
+    #[pyo3(get)]
+    pub mode: String,
+    /// `"blosum62"` or `"match_mismatch"`, as in the JavaScript presets.
+    #[pyo3(get)]
+    pub scoring: String,
+    /// Substitution matrix name, or `None` for match/mismatch scoring.
+    #[pyo3(get)]
+    pub matrix: Option<String>,
+    #[pyo3(get)]
+    pub match_score: Option<f64>,
+    #[pyo3(get)]
+    pub mismatch_penalty: Option<f64>,
+    #[pyo3(get)]
+    pub gap_open: f64,
+    #[pyo3(get)]
+    pub gap_extend: f64,
+}
+
+impl From<&Preset> for PyPreset {
+    fn from(p: &Preset) -> Self {
+        let (scoring, matrix, match_score, mismatch_penalty) = match p.scoring {
+            PresetScoring::Blosum62 => ("blosum62", Some("blosum62".to_string()), None, None),
+            PresetScoring::MatchMismatch {
+                match_score,
+                mismatch_penalty,
+            } => ("match_mismatch", None, Some(match_score), Some(mismatch_penalty)),
+        };
+        Self {
+            name: p.name.to_string(),
+            description: p.description.to_string(),
+            mode: p.mode.name().to_string(),
+            scoring: scoring.to_string(),
+            matrix,
+            match_score,
+            mismatch_penalty,
+            gap_open: p.gap_open,
+            gap_extend: p.gap_extend,
         }
     }
 }
 
-
 #[pymodule]
 mod web_bio_tools {
     use pyo3::prelude::*;
     use pyo3::exceptions::PyValueError;
-    use crate::alignment;
+    use crate::alignment::{self, AlignmentTask, Anchor, DualAffineGaps};
+    use crate::alphabet::{Alphabet, Sequence};
+    use crate::pairhmm::{self, PairHmmParams};
+    use crate::fna2faa::TranslationOptions;
+    use crate::fastq::TrimOptions;
+    use crate::genecall::{Contig, GeneCallOptions};
+    use crate::presets::{self, PRESETS};
 
     #[pymodule_export]
     use super::PyAlignmentResult;
+    #[pymodule_export]
+    use super::PyPairHmmResult;
+    #[pymodule_export]
+    use super::PyEditDistanceResult;
+    #[pymodule_export]
+    use super::PyPreset;
+    #[pymodule_export]
+    use super::PyColumnScore;
+    #[pymodule_export]
+    use super::PyDpMatrices;
+    #[pymodule_export]
+    use super::PyFrameTranslation;
+    #[pymodule_export]
+    use super::PyCodonSite;
+    #[pymodule_export]
+    use super::PyFastaRecord;
+    #[pymodule_export]
+    use super::PyFastqRecord;
+    #[pymodule_export]
+    use super::PyTrimSummary;
+    #[pymodule_export]
+    use super::PyTranslatedRecord;
+    #[pymodule_export]
+    use super::PyTranslationStream;
+    #[pymodule_export]
+    use super::PyCodonStat;
+    #[pymodule_export]
+    use super::PyCodonUsageSummary;
+    #[pymodule_export]
+    use super::PyCodonUsageReport;
+    #[pymodule_export]
+    use super::PyFrameshift;
+    #[pymodule_export]
+    use super::PyFrameshiftRecord;
+    #[pymodule_export]
+    use super::PyDinucleotideStat;
+    #[pymodule_export]
+    use super::PyCompositionStats;
+    #[pymodule_export]
+    use super::PyCompositionWindow;
+    #[pymodule_export]
+    use super::PyCompositionProfile;
+    #[pymodule_export]
+    use super::PyCompositionReport;
+    #[pymodule_export]
+    use super::PyOrf;
+    #[pymodule_export]
+    use super::PyGene;
+    #[pymodule_export]
+    use super::PyGenePrediction;
 
     #[pyfunction]
-    fn smith_waterman(seq1: &str, seq2: &str) -> PyAlignmentResult {
-        alignment::smith_waterman_internal(seq1, seq2, 2.0, -1.0, -1.0, -0.5).into()
+    fn smith_waterman(seq1: &str, seq2: &str) -> PyResult<PyAlignmentResult> {
+        Ok(alignment::smith_waterman_internal(seq1, seq2, 2.0, -1.0, -1.0, -0.5)?.into())
     }
 
     #[pyfunction]
+    #[pyo3(signature = (seq1, seq2, match_score, mismatch_penalty, gap_open, gap_extend, alphabet=None, column_scores=false))]
+    #[allow(clippy::too_many_arguments)]
     fn smith_waterman_custom(
         seq1: &str,
         seq2: &str,
@@ -53,34 +1103,48 @@ mod web_bio_tools {
         mismatch_penalty: f64,
         gap_open: f64,
         gap_extend: f64,
-    ) -> PyAlignmentResult {
-        alignment::smith_waterman_internal(
+        alphabet: Option<&str>,
+        column_scores: bool,
+    ) -> PyResult<PyAlignmentResult> {
+        if let Some(name) = alphabet {
+            alignment::check_alphabet(seq1, seq2, super::parse_alphabet(name)?)?;
+        }
+        Ok(alignment::smith_waterman_task(
             seq1,
             seq2,
             match_score,
             mismatch_penalty,
             gap_open,
             gap_extend,
-        )
-        .into()
+        )?
+        .with_column_scores(column_scores)
+        .finish()?
+        .into())
     }
 
     #[pyfunction]
+    #[pyo3(signature = (seq1, seq2, gap_open, gap_extend, column_scores=false))]
     fn smith_waterman_blosum62(
         seq1: &str,
         seq2: &str,
         gap_open: f64,
         gap_extend: f64,
-    ) -> PyAlignmentResult {
-        alignment::smith_waterman_blosum62_internal(seq1, seq2, gap_open, gap_extend).into()
+        column_scores: bool,
+    ) -> PyResult<PyAlignmentResult> {
+        Ok(alignment::smith_waterman_blosum62_task(seq1, seq2, gap_open, gap_extend)?
+            .with_column_scores(column_scores)
+            .finish()?
+            .into())
     }
 
     #[pyfunction]
-    fn needleman_wunsch(seq1: &str, seq2: &str) -> PyAlignmentResult {
-        alignment::needleman_wunsch_internal(seq1, seq2, 2.0, -1.0, -1.0, -0.5).into()
+    fn needleman_wunsch(seq1: &str, seq2: &str) -> PyResult<PyAlignmentResult> {
+        Ok(alignment::needleman_wunsch_internal(seq1, seq2, 2.0, -1.0, -1.0, -0.5)?.into())
     }
 
     #[pyfunction]
+    #[pyo3(signature = (seq1, seq2, match_score, mismatch_penalty, gap_open, gap_extend, alphabet=None, column_scores=false))]
+    #[allow(clippy::too_many_arguments)]
     fn needleman_wunsch_custom(
         seq1: &str,
         seq2: &str,
@@ -88,33 +1152,690 @@ mod web_bio_tools {
         mismatch_penalty: f64,
         gap_open: f64,
         gap_extend: f64,
-    ) -> PyAlignmentResult {
-        alignment::needleman_wunsch_internal(
+        alphabet: Option<&str>,
+        column_scores: bool,
+    ) -> PyResult<PyAlignmentResult> {
+        if let Some(name) = alphabet {
+            alignment::check_alphabet(seq1, seq2, super::parse_alphabet(name)?)?;
+        }
+        Ok(alignment::needleman_wunsch_task(
             seq1,
             seq2,
             match_score,
             mismatch_penalty,
             gap_open,
             gap_extend,
-        )
-        .into()
+        )?
+        .with_column_scores(column_scores)
+        .finish()?
+        .into())
     }
 
     #[pyfunction]
+    #[pyo3(signature = (seq1, seq2, gap_open, gap_extend, column_scores=false))]
     fn needleman_wunsch_blosum62(
         seq1: &str,
         seq2: &str,
         gap_open: f64,
         gap_extend: f64,
-    ) -> PyAlignmentResult {
-        alignment::needleman_wunsch_blosum62_internal(seq1, seq2, gap_open, gap_extend).into()
+        column_scores: bool,
+    ) -> PyResult<PyAlignmentResult> {
+        Ok(alignment::needleman_wunsch_blosum62_task(seq1, seq2, gap_open, gap_extend)?
+            .with_column_scores(column_scores)
+            .finish()?
+            .into())
     }
 
+    #[pyfunction]
+    fn smith_waterman_matrices(
+        seq1: &str,
+        seq2: &str,
+        match_score: f64,
+        mismatch_penalty: f64,
+        gap_open: f64,
+        gap_extend: f64,
+    ) -> PyResult<PyDpMatrices> {
+        Ok(alignment::smith_waterman_task(
+            seq1,
+            seq2,
+            match_score,
+            mismatch_penalty,
+            gap_open,
+            gap_extend,
+        )?
+        .finish_with_matrices()?
+        .into())
+    }
 
     #[pyfunction]
-    fn translate_dna_frame(seq: &str, frame: i8, stop_at_first_stop: bool) -> PyResult<String> {
-        crate::translation::translate_frame(seq, frame, stop_at_first_stop)
-            .map_err(|err| PyErr::new::<PyValueError, _>(err))
+    fn smith_waterman_blosum62_matrices(
+        seq1: &str,
+        seq2: &str,
+        gap_open: f64,
+        gap_extend: f64,
+    ) -> PyResult<PyDpMatrices> {
+        Ok(alignment::smith_waterman_blosum62_task(seq1, seq2, gap_open, gap_extend)?
+            .finish_with_matrices()?
+            .into())
+    }
+
+    #[pyfunction]
+    fn needleman_wunsch_matrices(
+        seq1: &str,
+        seq2: &str,
+        match_score: f64,
+        mismatch_penalty: f64,
+        gap_open: f64,
+        gap_extend: f64,
+    ) -> PyResult<PyDpMatrices> {
+        Ok(alignment::needleman_wunsch_task(
+            seq1,
+            seq2,
+            match_score,
+            mismatch_penalty,
+            gap_open,
+            gap_extend,
+        )?
+        .finish_with_matrices()?
+        .into())
+    }
+
+    #[pyfunction]
+    fn needleman_wunsch_blosum62_matrices(
+        seq1: &str,
+        seq2: &str,
+        gap_open: f64,
+        gap_extend: f64,
+    ) -> PyResult<PyDpMatrices> {
+        Ok(alignment::needleman_wunsch_blosum62_task(seq1, seq2, gap_open, gap_extend)?
+            .finish_with_matrices()?
+            .into())
+    }
+
+    fn to_anchors(anchors: Vec<(usize, usize, usize, usize)>) -> Vec<Anchor> {
+        anchors
+            .into_iter()
+            .map(|(start1, end1, start2, end2)| Anchor {
+                start1,
+                end1,
+                start2,
+                end2,
+            })
+            .collect()
+    }
+
+    /// `anchors` is a list of `(start1, end1, start2, end2)` tuples (0-based,
+    /// end exclusive) whose regions, of equal length, are forced to align.
+    #[pyfunction]
+    fn needleman_wunsch_anchored(
+        seq1: &str,
+        seq2: &str,
+        anchors: Vec<(usize, usize, usize, usize)>,
+        match_score: f64,
+        mismatch_penalty: f64,
+        gap_open: f64,
+        gap_extend: f64,
+    ) -> PyResult<PyAlignmentResult> {
+        Ok(alignment::needleman_wunsch_anchored_internal(
+            seq1,
+            seq2,
+            &to_anchors(anchors),
+            match_score,
+            mismatch_penalty,
+            gap_open,
+            gap_extend,
+        )?
+        .into())
+    }
+
+    #[pyfunction]
+    fn needleman_wunsch_anchored_blosum62(
+        seq1: &str,
+        seq2: &str,
+        anchors: Vec<(usize, usize, usize, usize)>,
+        gap_open: f64,
+        gap_extend: f64,
+    ) -> PyResult<PyAlignmentResult> {
+        Ok(alignment::needleman_wunsch_anchored_blosum62_internal(
+            seq1,
+            seq2,
+            &to_anchors(anchors),
+            gap_open,
+            gap_extend,
+        )?
+        .into())
+    }
+
+    #[pyfunction]
+    #[allow(clippy::too_many_arguments)]
+    fn smith_waterman_dual_affine(
+        seq1: &str,
+        seq2: &str,
+        match_score: f64,
+        mismatch_penalty: f64,
+        gap_open: f64,
+        gap_extend: f64,
+        long_gap_open: f64,
+        long_gap_extend: f64,
+    ) -> PyResult<PyAlignmentResult> {
+        let gaps = DualAffineGaps {
+            gap_open,
+            gap_extend,
+            long_gap_open,
+            long_gap_extend,
+        };
+        Ok(alignment::smith_waterman_dual_affine_internal(
+            seq1,
+            seq2,
+            match_score,
+            mismatch_penalty,
+            &gaps,
+        )
+        ?.into())
+    }
+
+    #[pyfunction]
+    fn smith_waterman_dual_affine_blosum62(
+        seq1: &str,
+        seq2: &str,
+        gap_open: f64,
+        gap_extend: f64,
+        long_gap_open: f64,
+        long_gap_extend: f64,
+    ) -> PyResult<PyAlignmentResult> {
+        let gaps = DualAffineGaps {
+            gap_open,
+            gap_extend,
+            long_gap_open,
+            long_gap_extend,
+        };
+        Ok(alignment::smith_waterman_dual_affine_blosum62_internal(seq1, seq2, &gaps)?.into())
+    }
+
+    #[pyfunction]
+    #[allow(clippy::too_many_arguments)]
+    fn needleman_wunsch_dual_affine(
+        seq1: &str,
+        seq2: &str,
+        match_score: f64,
+        mismatch_penalty: f64,
+        gap_open: f64,
+        gap_extend: f64,
+        long_gap_open: f64,
+        long_gap_extend: f64,
+    ) -> PyResult<PyAlignmentResult> {
+        let gaps = DualAffineGaps {
+            gap_open,
+            gap_extend,
+            long_gap_open,
+            long_gap_extend,
+        };
+        Ok(alignment::needleman_wunsch_dual_affine_internal(
+            seq1,
+            seq2,
+            match_score,
+            mismatch_penalty,
+            &gaps,
+        )
+        ?.into())
+    }
+
+    #[pyfunction]
+    fn needleman_wunsch_dual_affine_blosum62(
+        seq1: &str,
+        seq2: &str,
+        gap_open: f64,
+        gap_extend: f64,
+        long_gap_open: f64,
+        long_gap_extend: f64,
+    ) -> PyResult<PyAlignmentResult> {
+        let gaps = DualAffineGaps {
+            gap_open,
+            gap_extend,
+            long_gap_open,
+            long_gap_extend,
+        };
+        Ok(alignment::needleman_wunsch_dual_affine_blosum62_internal(seq1, seq2, &gaps)?.into())
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (seq1, seq2, max_distance=None, traceback=false))]
+    fn edit_distance(
+        seq1: &str,
+        seq2: &str,
+        max_distance: Option<usize>,
+        traceback: bool,
+    ) -> PyResult<Option<PyEditDistanceResult>> {
+        Ok(alignment::edit_distance_internal(seq1, seq2, max_distance, traceback)?.map(Into::into))
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (seq1, seq2, max_distance=None, traceback=false))]
+    fn edit_distance_semi_global(
+        seq1: &str,
+        seq2: &str,
+        max_distance: Option<usize>,
+        traceback: bool,
+    ) -> PyResult<Option<PyEditDistanceResult>> {
+        Ok(
+            alignment::edit_distance_semi_global_internal(seq1, seq2, max_distance, traceback)?
+                .map(Into::into),
+        )
+    }
+
+    /// Every named preset with its scoring, gap scores and mode.
+    #[pyfunction]
+    fn list_presets() -> Vec<PyPreset> {
+        PRESETS.iter().map(PyPreset::from).collect()
+    }
+
+    #[pyfunction]
+    fn get_preset(name: &str) -> PyResult<PyPreset> {
+        Ok(presets::get_preset(name)?.into())
+    }
+
+    #[pyfunction]
+    fn align_with_preset(seq1: &str, seq2: &str, preset: &str) -> PyResult<PyAlignmentResult> {
+        Ok(presets::align_with_preset(seq1, seq2, preset)?.into())
+    }
+
+    #[pyfunction]
+    fn pair_hmm_align(
+        seq1: &str,
+        seq2: &str,
+        match_prob: f64,
+        gap_open: f64,
+        gap_extend: f64,
+    ) -> PyResult<PyPairHmmResult> {
+        let params = PairHmmParams {
+            gap_open,
+            gap_extend,
+            ..PairHmmParams::default()
+        };
+        pairhmm::pair_hmm_internal(seq1, seq2, match_prob, &params)
+            .map(Into::into)
+            .map_err(Into::into)
+    }
+
+    #[pyfunction]
+    fn pair_hmm_align_blosum62(
+        seq1: &str,
+        seq2: &str,
+        gap_open: f64,
+        gap_extend: f64,
+    ) -> PyResult<PyPairHmmResult> {
+        let params = PairHmmParams {
+            gap_open,
+            gap_extend,
+            ..PairHmmParams::default()
+        };
+        pairhmm::pair_hmm_blosum62_internal(seq1, seq2, &params)
+            .map(Into::into)
+            .map_err(Into::into)
+    }
+
+    /// With `selenocysteine` (`pyrrolysine`), internal TGA (TAG) stop
+    /// codons are translated as `U` (`O`); `recode_positions` limits this to
+    /// the codons covering the given 0-based forward-strand positions.
+    #[pyfunction]
+    #[pyo3(signature = (seq, frame, stop_at_first_stop, genetic_code=1, initiator_as_methionine=false, selenocysteine=false, pyrrolysine=false, recode_positions=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn translate_dna_frame(
+        seq: &str,
+        frame: i8,
+        stop_at_first_stop: bool,
+        genetic_code: u8,
+        initiator_as_methionine: bool,
+        selenocysteine: bool,
+        pyrrolysine: bool,
+        recode_positions: Option<Vec<usize>>,
+    ) -> PyResult<String> {
+        let options = TranslationOptions {
+            stop_at_first_stop,
+            initiator_as_methionine,
+            selenocysteine,
+            pyrrolysine,
+            recode_positions,
+            ..TranslationOptions::default()
+        };
+        crate::translation::translate_frame_with_options(seq, frame, genetic_code, &options)
+            .map(|translation| translation.amino_acids)
+            .map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// Like `translate_dna_frame` for all six frames; `recode_frame`
+    /// restricts recoding to one frame. Recoded codons are listed in
+    /// `recoded` and `recoded_sites` of each frame, apart from the stops.
+    #[pyfunction]
+    #[pyo3(signature = (seq, stop_at_first_stop, genetic_code=1, initiator_as_methionine=false, report_start_codon=false, selenocysteine=false, pyrrolysine=false, recode_frame=None, recode_positions=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn translate_dna_all_frames(
+        seq: &str,
+        stop_at_first_stop: bool,
+        genetic_code: u8,
+        initiator_as_methionine: bool,
+        report_start_codon: bool,
+        selenocysteine: bool,
+        pyrrolysine: bool,
+        recode_frame: Option<i8>,
+        recode_positions: Option<Vec<usize>>,
+    ) -> PyResult<Vec<PyFrameTranslation>> {
+        let options = TranslationOptions {
+            stop_at_first_stop,
+            initiator_as_methionine,
+            report_start_codon,
+            selenocysteine,
+            pyrrolysine,
+            recode_frame,
+            recode_positions,
+        };
+        let summary =
+            crate::translation::translate_all_frames_with_options(seq, genetic_code, &options)
+                .map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(summary.frames.into_iter().map(Into::into).collect())
+    }
+
+    /// Translate each record of FASTA `text` (or the bytes of a possibly
+    /// gzipped file) from `frame`, switching frame where a base seems to be
+    /// missing or in excess. Records that needed a frameshift or still have
+    /// internal stops are flagged as likely pseudogenes.
+    #[pyfunction]
+    #[pyo3(signature = (text, frame=1, genetic_code=1, shift_penalty=15.0, stop_penalty=10.0))]
+    fn translate_with_frameshifts(
+        text: &Bound<'_, PyAny>,
+        frame: i8,
+        genetic_code: u8,
+        shift_penalty: f64,
+        stop_penalty: f64,
+    ) -> PyResult<Vec<PyFrameshiftRecord>> {
+        let records = crate::fasta::parse_fasta(&super::input_text(text)?)?;
+        let options = crate::FrameshiftOptions {
+            frame,
+            genetic_code,
+            shift_penalty,
+            stop_penalty,
+        };
+        crate::translate_records_with_frameshifts(&records, &options)
+            .map(|records| records.into_iter().map(Into::into).collect())
+            .map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// Back-translate a protein into DNA. `mode` is `"degenerate"` (IUPAC
+    /// consensus), `"most_frequent"` or `"weighted_random"`; the last two use
+    /// `codon_usage`, a table as text (Kazusa or GCG format) or a
+    /// `{codon: weight}` dict.
+    #[pyfunction]
+    #[pyo3(signature = (protein, mode="degenerate", genetic_code=1, seed=0, codon_usage=None))]
+    fn back_translate(
+        protein: &str,
+        mode: &str,
+        genetic_code: u8,
+        seed: u64,
+        codon_usage: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<String> {
+        let options = crate::BackTranslationOptions {
+            genetic_code,
+            mode: crate::BackTranslationMode::from_name(mode)
+                .map_err(PyErr::new::<PyValueError, _>)?,
+            seed,
+        };
+        let usage = super::codon_usage(codon_usage)?;
+        crate::back_translate(protein, &options, usage.as_ref())
+            .map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// Codon usage of each coding sequence in FASTA `text` (or the bytes of
+    /// a possibly gzipped file) and of all of them pooled. With a `reference`
+    /// codon usage table (text or `{codon: weight}` dict), the Codon
+    /// Adaptation Index is computed against it.
+    #[pyfunction]
+    #[pyo3(signature = (text, genetic_code=1, reference=None))]
+    fn analyze_codon_usage(
+        text: &Bound<'_, PyAny>,
+        genetic_code: u8,
+        reference: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyCodonUsageReport> {
+        let records = crate::fasta::parse_fasta(&super::input_text(text)?)?;
+        let reference = super::codon_usage(reference)?;
+        crate::codon_usage_report(&records, genetic_code, reference.as_ref())
+            .map(Into::into)
+            .map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// Composition of each nucleotide sequence in FASTA `text` (or the bytes
+    /// of a possibly gzipped file): base counts, GC content, GC and AT skew,
+    /// GC1/GC2/GC3 and dinucleotide counts, whole and in windows of `window`
+    /// bases every `step` bases. `to_tsv()` and `windows_tsv()` write them as
+    /// tables.
+    #[pyfunction]
+    #[pyo3(signature = (text, window=1000, step=100))]
+    fn sequence_composition(
+        text: &Bound<'_, PyAny>,
+        window: usize,
+        step: usize,
+    ) -> PyResult<PyCompositionReport> {
+        let records = crate::fasta::parse_fasta(&super::input_text(text)?)?;
+        let options = crate::CompositionOptions { window, step };
+        crate::composition_report(&records, &options)
+            .map(Into::into)
+            .map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// Forward-strand `(start, end)` of the codon translated into amino acid
+    /// `aa_index` of `frame` (1 to 3 or -1 to -3).
+    #[pyfunction]
+    fn aa_to_nucleotide(seq_len: usize, frame: i8, aa_index: usize) -> Option<(usize, usize)> {
+        crate::fna2faa::aa_to_nucleotide(seq_len, frame, aa_index)
+    }
+
+    /// `(aa_index, codon_position)` of the codon of `frame` covering
+    /// forward-strand `position`.
+    #[pyfunction]
+    fn nucleotide_to_aa(seq_len: usize, frame: i8, position: usize) -> Option<(usize, usize)> {
+        crate::fna2faa::nucleotide_to_aa(seq_len, frame, position)
+    }
+
+    /// NCBI translation tables as `(id, name)` pairs.
+    #[pyfunction]
+    fn list_genetic_codes() -> Vec<(u8, &'static str)> {
+        crate::fna2faa::GENETIC_CODES
+            .iter()
+            .map(|code| (code.id, code.name))
+            .collect()
+    }
+
+    /// Six-frame ORF search; coordinates are 0-based, end-exclusive on the
+    /// forward strand.
+    #[pyfunction]
+    #[pyo3(signature = (seq, genetic_code=1, mode="start_to_stop", min_length=30, start_codons=None, include_partial=false, nested="longest"))]
+    fn find_orfs(
+        seq: &str,
+        genetic_code: u8,
+        mode: &str,
+        min_length: usize,
+        start_codons: Option<Vec<String>>,
+        include_partial: bool,
+        nested: &str,
+    ) -> PyResult<Vec<PyOrf>> {
+        let options = super::orf_options(
+            genetic_code,
+            mode,
+            min_length,
+            start_codons,
+            include_partial,
+            nested,
+        )?;
+        let orfs = crate::orf::find_orfs(seq, &options).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(orfs.into_iter().map(Into::into).collect())
+    }
+
+    #[pyfunction]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (seq, name="seq", genetic_code=1, mode="start_to_stop", min_length=30, start_codons=None, include_partial=false, nested="longest"))]
+    fn find_orfs_fasta(
+        seq: &str,
+        name: &str,
+        genetic_code: u8,
+        mode: &str,
+        min_length: usize,
+        start_codons: Option<Vec<String>>,
+        include_partial: bool,
+        nested: &str,
+    ) -> PyResult<String> {
+        let options = super::orf_options(
+            genetic_code,
+            mode,
+            min_length,
+            start_codons,
+            include_partial,
+            nested,
+        )?;
+        let orfs = crate::orf::find_orfs(seq, &options).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(crate::orf::orfs_to_fasta(name, &orfs))
+    }
+
+    /// Prokaryotic gene prediction on `(name, sequence)` contigs, trained on
+    /// the contigs themselves.
+    #[pyfunction]
+    #[pyo3(signature = (contigs, genetic_code=11, min_gene_length=90, max_overlap=60, closed_ends=false))]
+    fn predict_genes(
+        contigs: Vec<(String, String)>,
+        genetic_code: u8,
+        min_gene_length: usize,
+        max_overlap: usize,
+        closed_ends: bool,
+    ) -> PyResult<PyGenePrediction> {
+        let contigs: Vec<Contig> = contigs
+            .into_iter()
+            .map(|(name, sequence)| Contig { name, sequence })
+            .collect();
+        let options = GeneCallOptions {
+            genetic_code,
+            min_gene_length,
+            max_overlap,
+            closed_ends,
+        };
+        let prediction = crate::genecall::predict_genes(&contigs, &options)
+            .map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(PyGenePrediction {
+            gc_content: prediction.gc_content,
+            gc_frame_bias: prediction.gc_frame_bias,
+            training_genes: prediction.training_genes,
+            gff3: crate::genecall::genes_to_gff3(&prediction.genes),
+            proteins: crate::genecall::genes_to_fasta(&prediction.genes),
+            genes: prediction.genes.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// Decompress gzip or BGZF (multi-member) data.
+    #[pyfunction]
+    fn decompress_gzip(data: &[u8]) -> PyResult<Vec<u8>> {
+        crate::gzip::decompress_gzip(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// Parse FASTA (or raw sequence) text into records; errors give the
+    /// offending line number. `text` may also be the (possibly gzipped)
+    /// bytes of a file.
+    #[pyfunction]
+    fn parse_fasta(text: &Bound<'_, PyAny>) -> PyResult<Vec<PyFastaRecord>> {
+        let records = crate::fasta::parse_fasta(&super::input_text(text)?)?;
+        Ok(records.into_iter().map(Into::into).collect())
+    }
+
+    /// Parse FASTQ text into reads with Phred qualities; `encoding`
+    /// (`"phred33"` or `"phred64"`) is detected when omitted. `text` may also
+    /// be the (possibly gzipped) bytes of a file.
+    #[pyfunction]
+    #[pyo3(signature = (text, encoding=None))]
+    fn parse_fastq(
+        text: &Bound<'_, PyAny>,
+        encoding: Option<&str>,
+    ) -> PyResult<Vec<PyFastqRecord>> {
+        let text = super::input_text(text)?;
+        let reads = crate::fastq::parse_fastq(&text, super::parse_phred_encoding(encoding)?)?;
+        Ok(reads.records.into_iter().map(Into::into).collect())
+    }
+
+    /// Quality-trim (modified Mott and/or sliding window) and length-filter
+    /// FASTQ reads.
+    #[pyfunction]
+    #[pyo3(signature = (text, mott_limit=None, sliding_window=None, min_length=0, max_length=None, encoding=None))]
+    fn trim_fastq(
+        text: &Bound<'_, PyAny>,
+        mott_limit: Option<f64>,
+        sliding_window: Option<(usize, f64)>,
+        min_length: usize,
+        max_length: Option<usize>,
+        encoding: Option<&str>,
+    ) -> PyResult<PyTrimSummary> {
+        let options = TrimOptions {
+            mott_limit,
+            sliding_window,
+            min_length,
+            max_length,
+        };
+        let text = super::input_text(text)?;
+        let summary =
+            crate::fastq::trim_fastq(&text, super::parse_phred_encoding(encoding)?, &options)?;
+        Ok(PyTrimSummary {
+            encoding: summary.encoding.name().to_string(),
+            input_reads: summary.input_reads,
+            input_bases: summary.input_bases,
+            kept_bases: summary.kept_bases,
+            records: summary.records.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// The reads kept by `trim_fastq`, trimmed, as FASTA.
+    #[pyfunction]
+    #[pyo3(signature = (text, mott_limit=None, sliding_window=None, min_length=0, max_length=None, encoding=None))]
+    fn fastq_to_fasta(
+        text: &Bound<'_, PyAny>,
+        mott_limit: Option<f64>,
+        sliding_window: Option<(usize, f64)>,
+        min_length: usize,
+        max_length: Option<usize>,
+        encoding: Option<&str>,
+    ) -> PyResult<String> {
+        let options = TrimOptions {
+            mott_limit,
+            sliding_window,
+            min_length,
+            max_length,
+        };
+        let text = super::input_text(text)?;
+        let summary =
+            crate::fastq::trim_fastq(&text, super::parse_phred_encoding(encoding)?, &options)?;
+        Ok(crate::fastq::reads_to_fasta(&summary.records))
+    }
+
+    #[pyfunction]
+    fn validate_sequence(seq: &str, alphabet: &str) -> PyResult<String> {
+        Ok(Sequence::new(super::parse_alphabet(alphabet)?, seq)?.into_string())
+    }
+
+    #[pyfunction]
+    fn guess_alphabet(seq: &str) -> &'static str {
+        Alphabet::guess(seq).name()
+    }
+
+    #[pyfunction]
+    fn alphabet_symbols(alphabet: &str) -> PyResult<String> {
+        let alphabet = super::parse_alphabet(alphabet)?;
+        Ok(String::from_utf8_lossy(alphabet.symbols()).into_owned())
+    }
+
+    /// Returns the symbol codes as a list; a `Vec<u8>` would become `bytes`.
+    #[pyfunction]
+    fn encode_sequence(seq: &str, alphabet: &str) -> PyResult<Vec<u32>> {
+        let codes = Sequence::new(super::parse_alphabet(alphabet)?, seq)?.encode();
+        Ok(codes.into_iter().map(u32::from).collect())
     }
-}
 
+    #[pyfunction]
+    fn decode_sequence(codes: Vec<u8>, alphabet: &str) -> PyResult<String> {
+        Sequence::decode(super::parse_alphabet(alphabet)?, &codes)
+            .map(Sequence::into_string)
+            .map_err(PyErr::new::<PyValueError, _>)
+    }
+}
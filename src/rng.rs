@@ -0,0 +1,24 @@
+/// Linear congruential generator (Knuth's MMIX constants): deterministic
+/// pseudo-random numbers from a seed, with no dependency.
+pub(crate) struct Lcg(pub(crate) u64);
+
+impl Lcg {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Roughly uniform in `0..n`, from the high bits of the state.
+    #[cfg(test)]
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() >> 33) as usize) % n
+    }
+}
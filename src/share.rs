@@ -0,0 +1,470 @@
+use std::collections::BTreeMap;
+
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+use serde::{Deserialize, Serialize};
+
+use crate::alphabet::Alphabet;
+
+/// Version written by [`JobSpec::encode`]. Bump it whenever the payload
+/// layout changes, and keep decoding older versions.
+pub const SHARE_FORMAT_VERSION: u8 = 1;
+
+/// Upper bound on the decompressed payload, so a crafted link cannot make
+/// the page allocate without limit.
+const MAX_PAYLOAD_BYTES: usize = 64 * 1024 * 1024;
+
+const NUCLEOTIDES: &[u8] = b"ACGT";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tool {
+    Alignment,
+    Translation,
+    HmmViewer,
+}
+
+impl Tool {
+    fn code(self) -> u8 {
+        match self {
+            Tool::Alignment => 0,
+            Tool::Translation => 1,
+            Tool::HmmViewer => 2,
+        }
+    }
+
+    fn from_code(code: u8) -> Result<Tool, String> {
+        match code {
+            0 => Ok(Tool::Alignment),
+            1 => Ok(Tool::Translation),
+            2 => Ok(Tool::HmmViewer),
+            _ => Err(format!("Unknown tool code {} in share link", code)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedSequence {
+    pub name: String,
+    pub residues: String,
+}
+
+/// Everything needed to reproduce a run of one of the tools.
+///
+/// `mode` and `matrix` are tool specific (e.g. `"sw"` and `"blosum62"` for an
+/// alignment); numeric settings such as gap scores or the translation frame
+/// go in `params`. The HMM viewer keeps the model text in `text`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobSpec {
+    pub tool: Tool,
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub matrix: Option<String>,
+    #[serde(default)]
+    pub params: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub sequences: Vec<SharedSequence>,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+impl JobSpec {
+    /// Check that the spec has what its tool needs.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in &self.params {
+            if !value.is_finite() {
+                return Err(format!("Parameter '{}' must be a finite number", name));
+            }
+        }
+        match self.tool {
+            Tool::Alignment if self.sequences.len() != 2 => Err(format!(
+                "An alignment job needs exactly two sequences, got {}",
+                self.sequences.len()
+            )),
+            Tool::Translation if self.sequences.is_empty() => {
+                Err("A translation job needs at least one sequence".to_string())
+            }
+            Tool::HmmViewer if self.text.is_none() => {
+                Err("An HMM viewer job needs the HMM text".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Serialize to a URL-safe token: base64url (no padding) of a version
+    /// byte followed by the deflate-compressed payload. Sequences made only
+    /// of `ACGT` are packed at 2 bits per base and protein sequences at 5 bits
+    /// per residue; anything else is stored as raw text.
+    pub fn encode(&self) -> Result<String, String> {
+        self.validate()?;
+        let mut payload = Vec::new();
+        payload.push(self.tool.code());
+        write_opt_str(&mut payload, self.mode.as_deref());
+        write_opt_str(&mut payload, self.matrix.as_deref());
+        write_varint(&mut payload, self.params.len());
+        for (name, value) in &self.params {
+            write_str(&mut payload, name);
+            payload.extend_from_slice(&value.to_le_bytes());
+        }
+        write_varint(&mut payload, self.sequences.len());
+        for seq in &self.sequences {
+            write_str(&mut payload, &seq.name);
+            write_residues(&mut payload, seq.residues.as_bytes());
+        }
+        write_opt_str(&mut payload, self.text.as_deref());
+
+        let mut bytes = vec![SHARE_FORMAT_VERSION];
+        bytes.extend(compress_to_vec(&payload, 9));
+        Ok(base64url_encode(&bytes))
+    }
+
+    /// Parse and validate a token produced by [`JobSpec::encode`].
+    pub fn decode(token: &str) -> Result<JobSpec, String> {
+        let bytes = base64url_decode(token.trim())?;
+        let (&version, compressed) = bytes
+            .split_first()
+            .ok_or_else(|| "Share link is empty".to_string())?;
+        if version != SHARE_FORMAT_VERSION {
+            return Err(format!(
+                "Unsupported share link version {} (this version reads {})",
+                version, SHARE_FORMAT_VERSION
+            ));
+        }
+        let payload = decompress_to_vec_with_limit(compressed, MAX_PAYLOAD_BYTES)
+            .map_err(|_| "Share link payload is corrupt or too large".to_string())?;
+
+        let mut reader = Reader {
+            bytes: &payload,
+            pos: 0,
+        };
+        let tool = Tool::from_code(reader.byte()?)?;
+        let mode = reader.opt_str()?;
+        let matrix = reader.opt_str()?;
+        let mut params = BTreeMap::new();
+        for _ in 0..reader.varint()? {
+            let name = reader.str()?;
+            let value = f64::from_le_bytes(reader.take(8)?.try_into().unwrap());
+            params.insert(name, value);
+        }
+        let mut sequences = Vec::new();
+        for _ in 0..reader.varint()? {
+            let name = reader.str()?;
+            let residues = reader.residues()?;
+            sequences.push(SharedSequence { name, residues });
+        }
+        let text = reader.opt_str()?;
+        if reader.pos != payload.len() {
+            return Err("Share link has trailing data".to_string());
+        }
+
+        let spec = JobSpec {
+            tool,
+            mode,
+            matrix,
+            params,
+            sequences,
+            text,
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Packing {
+    Raw = 0,
+    Nucleotide = 1,
+    Protein = 2,
+}
+
+impl Packing {
+    fn for_residues(residues: &[u8]) -> Packing {
+        if residues.iter().all(|b| NUCLEOTIDES.contains(b)) {
+            Packing::Nucleotide
+        } else if residues
+            .iter()
+            .all(|b| b.is_ascii_uppercase() || *b == b'*')
+            && residues.iter().all(|&b| Alphabet::Protein.is_valid(b))
+        {
+            Packing::Protein
+        } else {
+            Packing::Raw
+        }
+    }
+
+    fn symbols(self) -> &'static [u8] {
+        match self {
+            Packing::Raw => &[],
+            Packing::Nucleotide => NUCLEOTIDES,
+            Packing::Protein => Alphabet::Protein.symbols(),
+        }
+    }
+
+    fn bits(self) -> usize {
+        match self {
+            Packing::Raw => 8,
+            Packing::Nucleotide => 2,
+            Packing::Protein => 5,
+        }
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_varint(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_opt_str(out: &mut Vec<u8>, s: Option<&str>) {
+    match s {
+        Some(s) => {
+            out.push(1);
+            write_str(out, s);
+        }
+        None => out.push(0),
+    }
+}
+
+fn write_residues(out: &mut Vec<u8>, residues: &[u8]) {
+    let packing = Packing::for_residues(residues);
+    out.push(packing as u8);
+    write_varint(out, residues.len());
+    if let Packing::Raw = packing {
+        out.extend_from_slice(residues);
+        return;
+    }
+    let symbols = packing.symbols();
+    let bits = packing.bits();
+    let mut acc: u32 = 0;
+    let mut filled = 0;
+    for b in residues {
+        let code = symbols.iter().position(|s| s == b).unwrap() as u32;
+        acc = (acc << bits) | code;
+        filled += bits;
+        while filled >= 8 {
+            filled -= 8;
+            out.push((acc >> filled) as u8);
+        }
+    }
+    if filled > 0 {
+        out.push((acc << (8 - filled)) as u8);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| "Share link payload is truncated".to_string())?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<usize, String> {
+        let mut value = 0usize;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            value |= ((b & 0x7f) as usize)
+                .checked_shl(shift)
+                .ok_or_else(|| "Share link contains an oversized length".to_string())?;
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Share link contains an oversized length".to_string())
+    }
+
+    fn str(&mut self) -> Result<String, String> {
+        let len = self.varint()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| "Share link contains invalid UTF-8 text".to_string())
+    }
+
+    fn opt_str(&mut self) -> Result<Option<String>, String> {
+        match self.byte()? {
+            0 => Ok(None),
+            1 => self.str().map(Some),
+            flag => Err(format!("Invalid option flag {} in share link", flag)),
+        }
+    }
+
+    fn residues(&mut self) -> Result<String, String> {
+        let packing = match self.byte()? {
+            0 => Packing::Raw,
+            1 => Packing::Nucleotide,
+            2 => Packing::Protein,
+            other => return Err(format!("Unknown sequence packing {} in share link", other)),
+        };
+        let count = self.varint()?;
+        if let Packing::Raw = packing {
+            let bytes = self.take(count)?;
+            return String::from_utf8(bytes.to_vec())
+                .map_err(|_| "Share link contains invalid UTF-8 text".to_string());
+        }
+        let symbols = packing.symbols();
+        let bits = packing.bits();
+        let packed_len = count
+            .checked_mul(bits)
+            .map(|total| total.div_ceil(8))
+            .ok_or_else(|| "Share link contains an oversized length".to_string())?;
+        let packed = self.take(packed_len)?;
+        let mask = (1u32 << bits) - 1;
+        let mut residues = String::with_capacity(count);
+        let mut acc: u32 = 0;
+        let mut filled = 0;
+        let mut bytes = packed.iter();
+        for _ in 0..count {
+            while filled < bits {
+                acc = (acc << 8) | *bytes.next().unwrap() as u32;
+                filled += 8;
+            }
+            filled -= bits;
+            let code = ((acc >> filled) & mask) as usize;
+            let symbol = symbols
+                .get(code)
+                .ok_or_else(|| format!("Invalid residue code {} in share link", code))?;
+            residues.push(*symbol as char);
+        }
+        Ok(residues)
+    }
+}
+
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+fn base64url_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(BASE64URL[(n >> (18 - 6 * i)) as usize & 0x3f] as char);
+        }
+    }
+    out
+}
+
+fn base64url_decode(text: &str) -> Result<Vec<u8>, String> {
+    let text = text.trim_end_matches('=');
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut filled = 0;
+    for ch in text.bytes() {
+        let value = BASE64URL
+            .iter()
+            .position(|&c| c == ch)
+            .ok_or_else(|| format!("Invalid character '{}' in share link", ch as char))?;
+        acc = (acc << 6) | value as u32;
+        filled += 6;
+        if filled >= 8 {
+            filled -= 8;
+            out.push((acc >> filled) as u8);
+        }
+    }
+    if filled >= 6 {
+        return Err("Share link has an invalid length".to_string());
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alignment_spec(seq1: &str, seq2: &str) -> JobSpec {
+        let mut params = BTreeMap::new();
+        params.insert("gap_open".to_string(), -10.0);
+        params.insert("gap_extend".to_string(), -0.5);
+        JobSpec {
+            tool: Tool::Alignment,
+            mode: Some("sw".to_string()),
+            matrix: Some("blosum62".to_string()),
+            params,
+            sequences: vec![
+                SharedSequence {
+                    name: "first".to_string(),
+                    residues: seq1.to_string(),
+                },
+                SharedSequence {
+                    name: "second".to_string(),
+                    residues: seq2.to_string(),
+                },
+            ],
+            text: None,
+        }
+    }
+
+    #[test]
+    fn roundtrip_all_packings() {
+        let spec = alignment_spec("MKTAYIAKQRQISFVKSHFSRQ*X", "ACGTTGCAACGTA");
+        let token = spec.encode().unwrap();
+        assert!(token
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+        assert_eq!(JobSpec::decode(&token).unwrap(), spec);
+
+        let raw = alignment_spec("acgt nnn", "GATTACA");
+        assert_eq!(JobSpec::decode(&raw.encode().unwrap()).unwrap(), raw);
+    }
+
+    #[test]
+    fn long_sequences_stay_compact() {
+        let mut seed = 1u64;
+        let dna: String = (0..20_000)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                NUCLEOTIDES[(seed >> 62) as usize] as char
+            })
+            .collect();
+        let spec = alignment_spec(&dna, &dna[..15_000]);
+        let token = spec.encode().unwrap();
+        assert!(token.len() < 20_000, "token is {} bytes", token.len());
+        assert_eq!(JobSpec::decode(&token).unwrap(), spec);
+    }
+
+    #[test]
+    fn rejects_bad_tokens() {
+        let token = alignment_spec("MKV", "MKL").encode().unwrap();
+        assert!(JobSpec::decode(&token[..token.len() - 4]).is_err());
+        assert!(JobSpec::decode("not a token!").is_err());
+
+        let mut bytes = base64url_decode(&token).unwrap();
+        bytes[0] = 99;
+        let err = JobSpec::decode(&base64url_encode(&bytes)).unwrap_err();
+        assert!(err.contains("version"));
+
+        let mut spec = alignment_spec("MKV", "MKL");
+        spec.sequences.pop();
+        assert!(spec.encode().is_err());
+    }
+
+    #[test]
+    fn base64url_roundtrip() {
+        for len in 0..10 {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 37 + 250) as u8).collect();
+            assert_eq!(base64url_decode(&base64url_encode(&bytes)).unwrap(), bytes);
+        }
+    }
+}
@@ -1,10 +1,9 @@
-use crate::fna2faa::{self, CodonEncoder, FrameTranslation, TranslationSummary};
+use crate::alphabet::{Alphabet, Sequence};
+use crate::fasta::FastaReader;
+use crate::fna2faa::{self, CodonEncoder, FrameTranslation, TranslationOptions, TranslationSummary};
+use serde::Serialize;
 
-const VALID_NUCLEOTIDES: &[char] = &[
-    'A', 'C', 'G', 'T', 'U', 'R', 'Y', 'K', 'M', 'S', 'W', 'B', 'D', 'H', 'V', 'N',
-];
-
-fn sanitize_sequence(seq: &str) -> Result<String, String> {
+pub(crate) fn sanitize_sequence(seq: &str) -> Result<String, String> {
     let mut cleaned = String::with_capacity(seq.len());
     for ch in seq.chars() {
         if ch.is_whitespace() {
@@ -21,12 +20,12 @@ fn sanitize_sequence(seq: &str) -> Result<String, String> {
             ));
         }
         if ch.is_ascii_alphabetic() {
-            let upper = ch.to_ascii_uppercase();
-            if VALID_NUCLEOTIDES.contains(&upper) {
-                cleaned.push(match upper {
-                    'U' => 'T',
-                    _ => upper,
-                });
+            let upper = match ch.to_ascii_uppercase() {
+                'U' => 'T',
+                other => other,
+            };
+            if Alphabet::Dna.is_valid(upper as u8) {
+                cleaned.push(upper);
             } else {
                 return Err(format!(
                     "Unsupported nucleotide '{}' encountered in sequence",
@@ -58,58 +57,220 @@ fn translate_frame_with_encoder(
     encoder: &CodonEncoder,
     sanitized: &str,
     frame: i8,
-    stop_at_first_stop: bool,
+    options: &TranslationOptions,
 ) -> Result<FrameTranslation, String> {
     let internal_frame = normalize_frame(frame)?;
-    fna2faa::translate_frame_internal(encoder, sanitized, internal_frame, stop_at_first_stop)
+    fna2faa::translate_frame_internal(encoder, sanitized, internal_frame, options)
 }
 
-pub fn translate_frame(seq: &str, frame: i8, stop_at_first_stop: bool) -> Result<String, String> {
-    let sanitized = sanitize_sequence(seq)?;
-    let encoder = CodonEncoder::mk_encoder();
-    let translation =
-        translate_frame_with_encoder(&encoder, &sanitized, frame, stop_at_first_stop)?;
+fn stop_option(stop_at_first_stop: bool) -> TranslationOptions {
+    TranslationOptions {
+        stop_at_first_stop,
+        ..TranslationOptions::default()
+    }
+}
+
+/// Translate one frame of `seq` with the NCBI translation table
+/// `genetic_code` (1 is the standard code).
+pub fn translate_frame(
+    seq: &str,
+    frame: i8,
+    stop_at_first_stop: bool,
+    genetic_code: u8,
+) -> Result<String, String> {
+    let options = stop_option(stop_at_first_stop);
+    let translation = translate_frame_with_options(seq, frame, genetic_code, &options)?;
     Ok(translation.amino_acids)
 }
 
 pub fn translate_all_frames(
     seq: &str,
     stop_at_first_stop: bool,
+    genetic_code: u8,
+) -> Result<TranslationSummary, String> {
+    translate_all_frames_with_options(seq, genetic_code, &stop_option(stop_at_first_stop))
+}
+
+pub fn translate_frame_with_options(
+    seq: &str,
+    frame: i8,
+    genetic_code: u8,
+    options: &TranslationOptions,
+) -> Result<FrameTranslation, String> {
+    let sanitized = sanitize_sequence(seq)?;
+    let encoder = CodonEncoder::for_table(genetic_code)?;
+    translate_frame_with_encoder(&encoder, &sanitized, frame, options)
+}
+
+pub fn translate_all_frames_with_options(
+    seq: &str,
+    genetic_code: u8,
+    options: &TranslationOptions,
 ) -> Result<TranslationSummary, String> {
     let sanitized = sanitize_sequence(seq)?;
-    let encoder = CodonEncoder::mk_encoder();
+    let encoder = CodonEncoder::for_table(genetic_code)?;
     Ok(fna2faa::translate_all_frames_internal(
         &encoder,
         &sanitized,
-        stop_at_first_stop,
+        options,
+    ))
+}
+
+/// Translate an already validated nucleotide sequence. RNA is read as DNA.
+pub fn translate_sequence_frame(
+    seq: &Sequence,
+    frame: i8,
+    stop_at_first_stop: bool,
+    genetic_code: u8,
+) -> Result<FrameTranslation, String> {
+    let sanitized = sequence_as_dna(seq)?;
+    let encoder = CodonEncoder::for_table(genetic_code)?;
+    translate_frame_with_encoder(&encoder, &sanitized, frame, &stop_option(stop_at_first_stop))
+}
+
+pub fn translate_sequence_all_frames(
+    seq: &Sequence,
+    stop_at_first_stop: bool,
+    genetic_code: u8,
+) -> Result<TranslationSummary, String> {
+    let sanitized = sequence_as_dna(seq)?;
+    let encoder = CodonEncoder::for_table(genetic_code)?;
+    Ok(fna2faa::translate_all_frames_internal(
+        &encoder,
+        &sanitized,
+        &stop_option(stop_at_first_stop),
     ))
 }
 
+fn sequence_as_dna(seq: &Sequence) -> Result<String, String> {
+    match seq.alphabet() {
+        Alphabet::Dna => Ok(seq.as_str().to_string()),
+        Alphabet::Rna => Ok(seq.as_str().replace('U', "T")),
+        Alphabet::Protein => Err("Cannot translate a protein sequence".to_string()),
+    }
+}
+
+/// The translation of one record read by a [`StreamingTranslator`].
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TranslatedRecord {
+    pub id: String,
+    pub description: String,
+    /// The requested frame, or all six frames.
+    pub frames: Vec<FrameTranslation>,
+}
+
+impl TranslatedRecord {
+    /// One FASTA entry per frame, with the frame appended to the ID
+    /// (`seq1:+1`, `seq1:-3`).
+    pub fn to_fasta(&self) -> String {
+        let id = if self.id.is_empty() { "sequence" } else { &self.id };
+        let mut fasta = String::new();
+        for frame in &self.frames {
+            fasta.push_str(&format!(">{}:{:+}", id, frame.frame));
+            if !self.description.is_empty() {
+                fasta.push(' ');
+                fasta.push_str(&self.description);
+            }
+            fasta.push('\n');
+            for line in frame.amino_acids.as_bytes().chunks(60) {
+                fasta.push_str(&String::from_utf8_lossy(line));
+                fasta.push('\n');
+            }
+        }
+        fasta
+    }
+}
+
+/// Translation of FASTA input (optionally gzipped) that arrives in chunks:
+/// `push` the chunks, take the translated records with `next_record` as
+/// they complete, and `finish` at the end of the input. Only one record is
+/// held in memory at a time, so the input may be larger than memory.
+pub struct StreamingTranslator {
+    reader: FastaReader,
+    encoder: CodonEncoder,
+    frame: Option<i8>,
+    options: TranslationOptions,
+}
+
+impl StreamingTranslator {
+    /// `frame` is 1 to 3 or -1 to -3; `None` translates all six frames.
+    pub fn new(
+        frame: Option<i8>,
+        genetic_code: u8,
+        options: TranslationOptions,
+    ) -> Result<Self, String> {
+        if let Some(frame) = frame {
+            normalize_frame(frame)?;
+        }
+        Ok(StreamingTranslator {
+            reader: FastaReader::new(),
+            encoder: CodonEncoder::for_table(genetic_code)?,
+            frame,
+            options,
+        })
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), String> {
+        self.reader.push(chunk).map_err(|err| err.to_string())
+    }
+
+    /// Signal the end of the input. The last record becomes available.
+    pub fn finish(&mut self) -> Result<(), String> {
+        self.reader.finish().map_err(|err| err.to_string())
+    }
+
+    /// Translate the next completed record, if any.
+    pub fn next_record(&mut self) -> Result<Option<TranslatedRecord>, String> {
+        let Some(record) = self.reader.next_record() else {
+            return Ok(None);
+        };
+        let sanitized = sanitize_sequence(&record.sequence)
+            .map_err(|err| format!("line {}: {}", record.line, err))?;
+        let frames = match self.frame {
+            Some(frame) => vec![translate_frame_with_encoder(
+                &self.encoder,
+                &sanitized,
+                frame,
+                &self.options,
+            )?],
+            None => {
+                fna2faa::translate_all_frames_internal(&self.encoder, &sanitized, &self.options)
+                    .frames
+            }
+        };
+        Ok(Some(TranslatedRecord {
+            id: record.id,
+            description: record.description,
+            frames,
+        }))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn translates_simple_sequence() {
-        let result = translate_frame("ATGGCC", 1, false).unwrap();
+        let result = translate_frame("ATGGCC", 1, false, 1).unwrap();
         assert_eq!(result, "MA");
     }
 
     #[test]
     fn respects_frame_offset() {
-        let result = translate_frame("AATGGCC", 2, false).unwrap();
+        let result = translate_frame("AATGGCC", 2, false, 1).unwrap();
         assert_eq!(result, "MA");
     }
 
     #[test]
     fn stops_at_first_stop() {
-        let result = translate_frame("ATGTAAATG", 1, true).unwrap();
+        let result = translate_frame("ATGTAAATG", 1, true, 1).unwrap();
         assert_eq!(result, "M*");
     }
 
     #[test]
     fn handles_all_frames() {
-        let summary = translate_all_frames("ATGGCC", false).unwrap();
+        let summary = translate_all_frames("ATGGCC", false, 1).unwrap();
         assert_eq!(summary.frames.len(), 6);
         assert_eq!(summary.frames[0].frame, 1);
         assert_eq!(summary.frames[0].amino_acids, "MA");
@@ -119,19 +280,105 @@ mod tests {
 
     #[test]
     fn rejects_invalid_characters() {
-        let err = translate_frame("ATG1CC", 1, false).unwrap_err();
+        let err = translate_frame("ATG1CC", 1, false, 1).unwrap_err();
         assert!(err.contains("Invalid character"));
     }
 
     #[test]
     fn rejects_invalid_frame() {
-        let err = translate_frame("ATGGCC", 0, false).unwrap_err();
+        let err = translate_frame("ATGGCC", 0, false, 1).unwrap_err();
         assert!(err.contains("Frame"));
     }
 
+    #[test]
+    fn translates_typed_sequences() {
+        let rna = Sequence::new(Alphabet::Rna, "AUGGCC").unwrap();
+        let result = translate_sequence_frame(&rna, 1, false, 1).unwrap();
+        assert_eq!(result.amino_acids, "MA");
+        let protein = Sequence::new(Alphabet::Protein, "MA").unwrap();
+        assert!(translate_sequence_all_frames(&protein, false, 1).is_err());
+    }
+
+    #[test]
+    fn uses_selected_genetic_code() {
+        assert_eq!(translate_frame("ATGTGAAGA", 1, false, 1).unwrap(), "M*R");
+        assert_eq!(translate_frame("ATGTGAAGA", 1, false, 4).unwrap(), "MWR");
+        assert_eq!(translate_frame("ATGTGAAGA", 1, false, 2).unwrap(), "MW*");
+        let err = translate_all_frames("ATGTGA", false, 8).unwrap_err();
+        assert!(err.contains("Unknown genetic code"));
+    }
+
+    #[test]
+    fn translates_alternative_initiator() {
+        let options = TranslationOptions {
+            initiator_as_methionine: true,
+            report_start_codon: true,
+            ..TranslationOptions::default()
+        };
+        let result = translate_frame_with_options("TTGGCC", 1, 11, &options).unwrap();
+        assert_eq!(result.amino_acids, "MA");
+        assert_eq!(result.start_codon.as_deref(), Some("TTG"));
+        let summary = translate_all_frames_with_options("GGCCAA", 11, &options).unwrap();
+        // Frame -1 reads TTGGCC.
+        assert_eq!(summary.frames[3].amino_acids, "MA");
+        assert_eq!(summary.frames[0].start_codon, None);
+        assert_eq!(translate_frame("TTGGCC", 1, false, 11).unwrap(), "LA");
+    }
+
     #[test]
     fn translates_reverse_frame() {
-        let result = translate_frame("ATGGCC", -1, false).unwrap();
+        let result = translate_frame("ATGGCC", -1, false, 1).unwrap();
         assert_eq!(result, "GH");
     }
+
+    #[test]
+    fn translates_streamed_records() {
+        let text = b">a first\nATGG\nCC\n>b\nATGTAA\n";
+        let options = TranslationOptions::default();
+        let mut translator = StreamingTranslator::new(None, 1, options.clone()).unwrap();
+        let mut records = Vec::new();
+        for chunk in text.chunks(3) {
+            translator.push(chunk).unwrap();
+            while let Some(record) = translator.next_record().unwrap() {
+                records.push(record);
+            }
+        }
+        translator.finish().unwrap();
+        records.extend(translator.next_record().unwrap());
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].frames, translate_all_frames("ATGGCC", false, 1).unwrap().frames);
+        assert_eq!(records[1].frames[0].amino_acids, "M*");
+        assert!(records[0].to_fasta().starts_with(">a:+1 first\nMA\n>a:+2 first\n"));
+
+        let mut translator = StreamingTranslator::new(Some(-1), 1, options.clone()).unwrap();
+        translator.push(b"ATGGCC").unwrap();
+        assert!(translator.next_record().unwrap().is_none());
+        translator.finish().unwrap();
+        let record = translator.next_record().unwrap().unwrap();
+        assert_eq!(record.to_fasta(), ">sequence:-1\nGH\n");
+
+        let mut translator = StreamingTranslator::new(Some(1), 1, options.clone()).unwrap();
+        translator.push(b">a\nATG\n>b\nATJ\n>c\n").unwrap();
+        assert_eq!(translator.next_record().unwrap().unwrap().frames[0].amino_acids, "M");
+        assert!(translator.next_record().unwrap_err().starts_with("line 3:"));
+        assert!(StreamingTranslator::new(Some(0), 1, options).is_err());
+    }
+
+    #[test]
+    fn streams_empty_and_short_records() {
+        let options = TranslationOptions::default();
+        let mut translator = StreamingTranslator::new(None, 1, options).unwrap();
+        translator.push(b">a\nATGGCC\n>empty\n>b\nA\n>c\nAT\n").unwrap();
+        translator.finish().unwrap();
+        let mut records = Vec::new();
+        while let Some(record) = translator.next_record().unwrap() {
+            records.push(record);
+        }
+        let ids: Vec<&str> = records.iter().map(|record| record.id.as_str()).collect();
+        assert_eq!(ids, ["a", "empty", "b", "c"]);
+        for record in &records[1..] {
+            assert_eq!(record.frames.len(), 6);
+            assert!(record.frames.iter().all(|frame| frame.amino_acids.is_empty()));
+        }
+    }
 }
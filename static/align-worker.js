@@ -0,0 +1,58 @@
+// Runs alignments off the main thread, a few DP rows at a time, so the page
+// can show progress and cancel long alignments.
+import init, { AlignmentJob } from '../pkg/web_bio_tools.js';
+
+const ready = init();
+let current = null;
+
+const CHUNK_ROWS = 64;
+
+function createJob({ algorithm, preset, weightOption, seq1, seq2, matchScore, mismatchPenalty, gapOpen, gapExtend }) {
+    if (preset) {
+        return AlignmentJob.preset(seq1, seq2, preset);
+    }
+    if (algorithm === 'nw') {
+        return weightOption === 'blosum62'
+            ? AlignmentJob.needleman_wunsch_blosum62(seq1, seq2, gapOpen, gapExtend)
+            : AlignmentJob.needleman_wunsch(seq1, seq2, matchScore, mismatchPenalty, gapOpen, gapExtend);
+    }
+    return weightOption === 'blosum62'
+        ? AlignmentJob.smith_waterman_blosum62(seq1, seq2, gapOpen, gapExtend)
+        : AlignmentJob.smith_waterman(seq1, seq2, matchScore, mismatchPenalty, gapOpen, gapExtend);
+}
+
+self.onmessage = async (event) => {
+    const message = event.data;
+    if (message.type === 'cancel') {
+        if (current) {
+            current.cancel();
+        }
+        return;
+    }
+    await ready;
+    const id = message.id;
+    let job;
+    try {
+        job = createJob(message);
+        current = job;
+        while (!job.cancelled && !job.step(CHUNK_ROWS)) {
+            self.postMessage({ id, type: 'progress', done: job.rows_done, total: job.total_rows });
+            // Yield so that a 'cancel' message can be handled between chunks.
+            await new Promise(resolve => setTimeout(resolve, 0));
+        }
+        if (job.cancelled) {
+            self.postMessage({ id, type: 'cancelled' });
+            return;
+        }
+        self.postMessage({ id, type: 'result', result: job.finish() });
+    } catch (error) {
+        self.postMessage({ id, type: 'error', message: error instanceof Error ? error.message : String(error) });
+    } finally {
+        if (job) {
+            job.free();
+        }
+        if (current === job) {
+            current = null;
+        }
+    }
+};
@@ -0,0 +1,50 @@
+import itertools
+
+import pytest
+import web_bio_tools
+from Bio.Data import CodonTable
+from Bio.Seq import Seq
+from hypothesis import given, settings
+from hypothesis import strategies as st
+
+proteins = st.text(alphabet="ACDEFGHIKLMNPQRSTVWY*", min_size=1, max_size=60)
+tables = st.sampled_from([1, 2, 4, 11])
+
+IUPAC = {
+    "A": "A", "C": "C", "G": "G", "T": "T", "R": "AG", "Y": "CT", "S": "CG", "W": "AT",
+    "K": "GT", "M": "AC", "B": "CGT", "D": "AGT", "H": "ACT", "V": "ACG", "N": "ACGT",
+}
+
+
+@given(protein=proteins, table=tables, seed=st.integers(min_value=0, max_value=2**32))
+@settings(max_examples=50, deadline=None)
+def test_random_back_translation_roundtrips(protein, table, seed):
+    dna = web_bio_tools.back_translate(protein, mode="weighted_random", genetic_code=table, seed=seed)
+    assert str(Seq(dna).translate(table=table)) == protein
+    again = web_bio_tools.back_translate(protein, mode="weighted_random", genetic_code=table, seed=seed)
+    assert again == dna
+
+
+@given(protein=proteins, table=tables)
+@settings(max_examples=50, deadline=None)
+def test_degenerate_covers_all_synonymous_codons(protein, table):
+    dna = web_bio_tools.back_translate(protein, genetic_code=table)
+    codon_table = CodonTable.unambiguous_dna_by_id[table]
+    for aa, start in zip(protein, range(0, len(dna), 3)):
+        expanded = {"".join(c) for c in itertools.product(*(IUPAC[b] for b in dna[start:start + 3]))}
+        if aa == "*":
+            synonyms = set(codon_table.stop_codons)
+        else:
+            synonyms = {codon for codon, a in codon_table.forward_table.items() if a == aa}
+        assert synonyms <= expanded
+
+
+def test_most_frequent_codon():
+    usage = {"ATG": 1.0, "GCT": 1.0, "GCC": 5.0, "GCA": 2.0, "CTG": 9.0, "TTA": 3.0}
+    assert web_bio_tools.back_translate("MAL", mode="most_frequent", codon_usage=usage) == "ATGGCCCTG"
+    kazusa = "AUG 22.0(1)  GCU 10.0(1)  GCC 28.0(1)  CUG 40.0(1)  UUA 7.0(1)"
+    assert web_bio_tools.back_translate("MAL", mode="most_frequent", codon_usage=kazusa) == "ATGGCCCTG"
+    with pytest.raises(ValueError):
+        web_bio_tools.back_translate("MAL", mode="most_frequent")
+    with pytest.raises(ValueError):
+        web_bio_tools.back_translate("MAW", mode="most_frequent", codon_usage=usage)
@@ -1,3 +1,4 @@
+import pytest
 import web_bio_tools
 from Bio.Align import PairwiseAligner, substitution_matrices
 from hypothesis import given, settings, assume
@@ -34,6 +35,25 @@ def test_nw_globalms():
     assert abs(result.score - ref.score) < 1e-6
 
 
+def test_invalid_inputs_raise_value_error():
+    with pytest.raises(ValueError):
+        web_bio_tools.smith_waterman("", "GATTACA")
+    with pytest.raises(ValueError):
+        web_bio_tools.needleman_wunsch_blosum62("MKV1", "MKV", -10.0, -0.5)
+    with pytest.raises(ValueError):
+        web_bio_tools.needleman_wunsch_custom("GATTACA", "GATTACA", 2, -1, 1.0, -0.5)
+    with pytest.raises(ValueError):
+        web_bio_tools.smith_waterman_custom("GATTACA", "GATEACA", 2, -1, -1.0, -0.5, alphabet="dna")
+
+
+def test_alphabet_encoding_roundtrip():
+    codes = web_bio_tools.encode_sequence("acgt nry", "dna")
+    assert codes == [0, 1, 2, 3, 14, 4, 5]
+    assert web_bio_tools.decode_sequence(codes, "dna") == "ACGTNRY"
+    assert web_bio_tools.guess_alphabet("MKTAYIAKQR") == "protein"
+    assert web_bio_tools.guess_alphabet("ACGUUGCA") == "rna"
+
+
 aa = "ARNDCQEGHILKMFPSTWYV"
 
 
@@ -87,3 +107,147 @@ def test_nw_globalms_hypothesis(seq1, seq2, match_score, mismatch_penalty, gap_o
     aligner.extend_gap_score = gap_extend
     ref = aligner.align(seq1, seq2)
     assert abs(result.score - ref.score) < 1e-6
+
+
+@given(
+    seq1=st.text(alphabet=dna, min_size=5, max_size=60),
+    seq2=st.text(alphabet=dna, min_size=5, max_size=60),
+    gap_open=st.floats(min_value=-6, max_value=-2),
+    gap_extend=st.floats(min_value=-3, max_value=-1),
+    long_gap_open=st.floats(min_value=-20, max_value=-8),
+    long_gap_extend=st.floats(min_value=-0.8, max_value=-0.1),
+)
+@settings(
+    max_examples=20,
+    suppress_health_check=[hp.HealthCheck.data_too_large],
+    deadline=None)
+def test_nw_dual_affine_hypothesis(seq1, seq2, gap_open, gap_extend, long_gap_open, long_gap_extend):
+    result = web_bio_tools.needleman_wunsch_dual_affine(
+        seq1, seq2, 2, -4, gap_open, gap_extend, long_gap_open, long_gap_extend
+    )
+
+    def gap_score(start, length):
+        return max(gap_open + (length - 1) * gap_extend,
+                   long_gap_open + (length - 1) * long_gap_extend)
+
+    aligner = PairwiseAligner()
+    aligner.mode = "global"
+    aligner.match_score = 2
+    aligner.mismatch_score = -4
+    aligner.target_gap_score = gap_score
+    aligner.query_gap_score = gap_score
+    ref = aligner.align(seq1, seq2)
+    assert abs(result.score - ref.score) < 1e-6
+
+
+@given(
+    seq1=st.text(alphabet=dna, min_size=5, max_size=120),
+    seq2=st.text(alphabet=dna, min_size=5, max_size=120),
+)
+@settings(
+    max_examples=20,
+    suppress_health_check=[hp.HealthCheck.data_too_large],
+    deadline=None)
+def test_biopython_preset_matches_default_aligner(seq1, seq2):
+    result = web_bio_tools.align_with_preset(seq1, seq2, "biopython")
+    ref = PairwiseAligner().align(seq1, seq2)
+    assert abs(result.score - ref.score) < 1e-6
+
+
+def test_blastp_preset():
+    preset = web_bio_tools.get_preset("blastp")
+    assert preset.matrix == "blosum62"
+    assert preset.scoring == "blosum62"
+    presets = web_bio_tools.list_presets()
+    assert "blastp" in [p.name for p in presets]
+    assert all(p.scoring in ("blosum62", "match_mismatch") for p in presets)
+    assert preset.mode == "local"
+    seq1 = "HEAGAWGHEE"
+    seq2 = "PAWHEAE"
+    result = web_bio_tools.align_with_preset(seq1, seq2, "blastp")
+    aligner = PairwiseAligner()
+    aligner.mode = "local"
+    aligner.substitution_matrix = substitution_matrices.load("BLOSUM62")
+    aligner.open_gap_score = preset.gap_open
+    aligner.extend_gap_score = preset.gap_extend
+    assert abs(result.score - aligner.score(seq1, seq2)) < 1e-6
+
+
+@given(
+    seq1=st.text(alphabet=dna, min_size=1, max_size=200),
+    seq2=st.text(alphabet=dna, min_size=1, max_size=200),
+)
+@settings(
+    max_examples=20,
+    suppress_health_check=[hp.HealthCheck.data_too_large],
+    deadline=None)
+def test_edit_distance_hypothesis(seq1, seq2):
+    aligner = PairwiseAligner()
+    aligner.mode = "global"
+    aligner.match_score = 0
+    aligner.mismatch_score = -1
+    aligner.gap_score = -1
+    result = web_bio_tools.edit_distance(seq1, seq2, traceback=True)
+    assert result.distance == -aligner.score(seq1, seq2)
+    assert result.alignment.score == -result.distance
+    assert web_bio_tools.edit_distance(seq1, seq2, max_distance=result.distance) is not None
+    if result.distance > 0:
+        assert web_bio_tools.edit_distance(seq1, seq2, max_distance=result.distance - 1) is None
+
+
+@given(
+    seq1=st.text(alphabet=dna, min_size=1, max_size=200),
+    seq2=st.text(alphabet=dna, min_size=1, max_size=200),
+)
+@settings(
+    max_examples=20,
+    suppress_health_check=[hp.HealthCheck.data_too_large],
+    deadline=None)
+def test_column_scores_hypothesis(seq1, seq2):
+    for align in (web_bio_tools.smith_waterman_custom, web_bio_tools.needleman_wunsch_custom):
+        result = align(seq1, seq2, 1.3, -0.7, -3.1, -0.3, column_scores=True)
+        assert len(result.columns) == len(result.aligned_seq1)
+        assert result.columns[-1].running_score == result.score
+        assert align(seq1, seq2, 1.3, -0.7, -3.1, -0.3).columns is None
+
+
+def test_dp_matrices_export():
+    matrices = web_bio_tools.needleman_wunsch_matrices("GATTACA", "GCATGCU", 2.0, -1.0, -3.0, -0.5)
+    result = web_bio_tools.needleman_wunsch_custom("GATTACA", "GCATGCU", 2.0, -1.0, -3.0, -0.5)
+    assert matrices.score[-1][-1] == result.score
+    assert matrices.alignment.aligned_seq1 == result.aligned_seq1
+    assert matrices.path[0] == ("score", 0, 0)
+    assert matrices.path[-1] == ("score", 7, 7)
+    assert matrices.delete_pointers[0][2] == "extend"
+    with pytest.raises(ValueError):
+        web_bio_tools.smith_waterman_matrices("A" * 200, "A" * 200, 2.0, -1.0, -1.0, -0.5)
+
+
+@given(
+    seq1=st.text(alphabet=aa, min_size=1, max_size=100),
+    seq2=st.text(alphabet=aa, min_size=1, max_size=100),
+)
+@settings(
+    max_examples=20,
+    suppress_health_check=[hp.HealthCheck.data_too_large],
+    deadline=None)
+def test_pair_hmm_posteriors_hypothesis(seq1, seq2):
+    result = web_bio_tools.pair_hmm_align_blosum62(seq1, seq2, 0.02, 0.4)
+    assert result.aligned_seq1.replace("-", "") == seq1
+    assert result.aligned_seq2.replace("-", "") == seq2
+    assert len(result.column_posteriors) == len(result.aligned_seq1)
+    assert all(0.0 <= p <= 1.0 + 1e-9 for p in result.column_posteriors)
+    assert result.score == pytest.approx(sum(result.column_posteriors))
+    assert result.expected_accuracy == pytest.approx(result.score / len(result.column_posteriors))
+
+
+def test_pair_hmm_align():
+    result = web_bio_tools.pair_hmm_align("GATTACAGATTACA", "GATTACAGATTACA", 0.9, 0.02, 0.4)
+    assert result.aligned_seq1 == result.aligned_seq2 == "GATTACAGATTACA"
+    assert result.alignment_markup == "|" * 14
+    assert all(p > 0.9 for p in result.column_posteriors)
+    assert result.log_odds > 0
+    with pytest.raises(ValueError):
+        web_bio_tools.pair_hmm_align("GATTACA", "GATTACA", 1.5, 0.02, 0.4)
+    with pytest.raises(ValueError):
+        web_bio_tools.pair_hmm_align_blosum62("MKV", "MKV", 0.6, 0.4)
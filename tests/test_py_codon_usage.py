@@ -0,0 +1,40 @@
+from collections import Counter
+
+import pytest
+import web_bio_tools
+from hypothesis import given, settings
+from hypothesis import strategies as st
+
+codons = st.lists(st.sampled_from(["ATG", "AAA", "AAG", "GCT", "GCC", "GCA", "GCG", "TGG", "TAA"]), min_size=1, max_size=60)
+
+
+@given(genes=st.lists(codons, min_size=1, max_size=4))
+@settings(max_examples=40, deadline=None)
+def test_counts_and_rscu(genes):
+    fasta = "".join(f">g{i}\n{''.join(gene)}\n" for i, gene in enumerate(genes))
+    report = web_bio_tools.analyze_codon_usage(fasta)
+    pooled = Counter(codon for gene in genes for codon in gene)
+    assert report.total.codons == sum(pooled.values())
+    stats = {stat.codon: stat for stat in report.total.stats}
+    assert {codon: stat.count for codon, stat in stats.items() if stat.count} == pooled
+    alanine = [stats[c] for c in ("GCT", "GCC", "GCA", "GCG")]
+    if sum(s.count for s in alanine):
+        assert sum(s.rscu for s in alanine) == pytest.approx(4.0)
+        assert sum(s.fraction for s in alanine) == pytest.approx(1.0)
+    for record, gene in zip(report.records, genes):
+        assert record.codons == len(gene)
+    # The exported tables read back as the same usage.
+    again = web_bio_tools.analyze_codon_usage(fasta, reference=report.gcg)
+    assert again.total.cai == pytest.approx(web_bio_tools.analyze_codon_usage(fasta, reference=report.cutg).total.cai, abs=1e-3)
+
+
+def test_cai_and_enc():
+    reference = {"AAA": 9, "AAG": 1, "GCC": 10, "GCT": 1}
+    report = web_bio_tools.analyze_codon_usage(">a\nAAAGCC\n>b\nAAGGCT\n", reference=reference)
+    assert report.records[0].cai == pytest.approx(1.0)
+    assert report.records[1].cai == pytest.approx((1 / 9 * 1 / 10) ** 0.5)
+    assert report.records[0].enc is None
+    even = web_bio_tools.analyze_codon_usage(">a\n" + "AAAAAG" * 10 + "\n")
+    assert even.total.gc3 == pytest.approx(0.5)
+    with pytest.raises(ValueError):
+        web_bio_tools.analyze_codon_usage(">a\nATG\n", genetic_code=7)
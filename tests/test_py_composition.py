@@ -0,0 +1,55 @@
+import gzip
+
+import pytest
+import web_bio_tools
+from hypothesis import given, settings
+from hypothesis import strategies as st
+
+sequences = st.text(alphabet="ACGTN", min_size=0, max_size=300)
+
+
+@given(seq=sequences, window=st.integers(min_value=1, max_value=50), step=st.integers(min_value=1, max_value=50))
+@settings(max_examples=60, deadline=None)
+def test_matches_python_counts(seq, window, step):
+    report = web_bio_tools.sequence_composition(f">s\n{seq}\n", window=window, step=step)
+    stats = report.records[0].stats
+    assert stats.length == len(seq)
+    assert (stats.a, stats.c, stats.g, stats.t, stats.n) == tuple(seq.count(b) for b in "ACGTN")
+    unambiguous = len(seq) - seq.count("N")
+    if unambiguous:
+        assert stats.gc == pytest.approx((seq.count("G") + seq.count("C")) / unambiguous)
+    else:
+        assert stats.gc is None
+    third = seq[2::3].replace("N", "")
+    if third:
+        assert stats.gc3 == pytest.approx(sum(b in "GC" for b in third) / len(third))
+    pairs = [seq[i:i + 2] for i in range(len(seq) - 1) if "N" not in seq[i:i + 2]]
+    assert {d.dinucleotide: d.count for d in stats.dinucleotides} == {
+        a + b: pairs.count(a + b) for a in "ACGT" for b in "ACGT"
+    }
+
+    windows = report.records[0].windows
+    cumulative = 0.0
+    for window_stats in windows:
+        assert window_stats.start % step == 0
+        part = seq[window_stats.start:window_stats.end]
+        g, c = part.count("G"), part.count("C")
+        assert window_stats.gc_skew == (pytest.approx((g - c) / (g + c)) if g + c else None)
+        cumulative += window_stats.gc_skew or 0.0
+        assert window_stats.cumulative_gc_skew == pytest.approx(cumulative)
+    assert len(report.windows_tsv().splitlines()) == len(windows) + 1
+
+
+def test_tsv_and_inputs():
+    fasta = b">a first\nGGCAu\n>b\nRYNN\n"
+    report = web_bio_tools.sequence_composition(gzip.compress(fasta), window=2, step=2)
+    assert [r.id for r in report.records] == ["a", "b"]
+    header, first, second = report.to_tsv().splitlines()
+    columns = header.split("\t")
+    assert columns[:3] == ["id", "length", "A"]
+    row = dict(zip(columns, first.split("\t")))
+    assert row["gc"] == "0.6000" and row["gc_skew"] == "0.3333" and row["GG"] == "1"
+    row = dict(zip(columns, second.split("\t")))
+    assert (row["N"], row["ambiguous"], row["gc"]) == ("2", "2", "NA")
+    with pytest.raises(ValueError):
+        web_bio_tools.sequence_composition(">a\nACGT\n", step=0)
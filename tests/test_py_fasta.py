@@ -0,0 +1,40 @@
+import io
+
+import pytest
+import web_bio_tools
+from Bio import SeqIO
+from hypothesis import given, settings
+from hypothesis import strategies as st
+
+records = st.lists(
+    st.tuples(
+        st.text(alphabet="abcXYZ_.|0123", min_size=1, max_size=10),
+        st.text(alphabet="ab c", max_size=10),
+        st.text(alphabet="ACGTNacgt-*", max_size=150),
+    ),
+    max_size=5,
+)
+
+
+@given(records=records, width=st.integers(min_value=1, max_value=80), crlf=st.booleans())
+@settings(max_examples=50, deadline=None)
+def test_parse_fasta_matches_biopython(records, width, crlf):
+    lines = []
+    for name, description, seq in records:
+        lines.append(f">{name} {description}".rstrip())
+        lines.extend(seq[i:i + width] for i in range(0, len(seq), width))
+    text = ("\r\n" if crlf else "\n").join(lines) + "\n"
+    ours = web_bio_tools.parse_fasta(text)
+    expected = list(SeqIO.parse(io.StringIO(text.replace("\r\n", "\n")), "fasta"))
+    assert [r.id for r in ours] == [r.id for r in expected]
+    assert [" ".join(r.header.split()) for r in ours] == [" ".join(r.description.split()) for r in expected]
+    assert [r.sequence for r in ours] == [str(r.seq) for r in expected]
+
+
+def test_parse_fasta_errors():
+    with pytest.raises(ValueError, match="line 3"):
+        web_bio_tools.parse_fasta(">a\nACGT\nAC1T\n")
+    raw = web_bio_tools.parse_fasta("ACGT\nTTGA\n")
+    assert len(raw) == 1
+    assert raw[0].id == ""
+    assert raw[0].sequence == "ACGTTTGA"
@@ -0,0 +1,60 @@
+import io
+
+import web_bio_tools
+from Bio import SeqIO
+from hypothesis import given, settings
+from hypothesis import strategies as st
+
+reads = st.lists(
+    st.text(alphabet="ACGTN", min_size=1, max_size=80).flatmap(
+        lambda seq: st.tuples(
+            st.just(seq),
+            st.lists(st.integers(min_value=0, max_value=41), min_size=len(seq), max_size=len(seq)),
+        )
+    ),
+    min_size=1,
+    max_size=5,
+)
+
+
+def to_fastq(reads, offset=33):
+    return "".join(
+        f"@read{i} x\n{seq}\n+\n{''.join(chr(q + offset) for q in quals)}\n"
+        for i, (seq, quals) in enumerate(reads)
+    )
+
+
+@given(reads=reads)
+@settings(max_examples=50, deadline=None)
+def test_parse_fastq_matches_biopython(reads):
+    text = to_fastq(reads)
+    ours = web_bio_tools.parse_fastq(text, "phred33")
+    expected = list(SeqIO.parse(io.StringIO(text), "fastq"))
+    assert [r.sequence for r in ours] == [str(r.seq) for r in expected]
+    assert [list(r.quality) for r in ours] == [r.letter_annotations["phred_quality"] for r in expected]
+    assert [r.id for r in ours] == [r.id for r in expected]
+
+
+@given(reads=reads)
+@settings(max_examples=50, deadline=None)
+def test_mott_trimming(reads):
+    text = to_fastq(reads)
+    summary = web_bio_tools.trim_fastq(text, mott_limit=0.05, encoding="phred33")
+    assert summary.input_reads == len(reads)
+    for record in summary.records:
+        seq, quals = reads[int(record.id[4:])]
+        assert record.sequence in seq
+        kept = sum(0.05 - 10 ** (-q / 10) for q in record.quality)
+        # No stretch of the read scores better than the kept one.
+        for i in range(len(quals)):
+            for j in range(i + 1, len(quals) + 1):
+                assert sum(0.05 - 10 ** (-q / 10) for q in quals[i:j]) <= kept + 1e-9
+
+
+def test_phred64_and_fasta():
+    text = "@r1\nACGTA\n+\nhhhhB\n"
+    summary = web_bio_tools.trim_fastq(text, sliding_window=(1, 20))
+    assert summary.encoding == "phred64"
+    assert [r.sequence for r in summary.records] == ["ACGT"]
+    assert web_bio_tools.fastq_to_fasta(text, min_length=6) == ""
+    assert web_bio_tools.fastq_to_fasta(text) == ">r1\nACGTA\n"
@@ -10,7 +10,7 @@ from hypothesis import strategies as st
 dna = "ACGT"
 
 
-def translate_with_biopython(seq: str, frame: int, stop_at_first_stop: bool) -> str:
+def translate_with_biopython(seq: str, frame: int, stop_at_first_stop: bool, table: int = 1) -> str:
     normalized = seq.replace("u", "t").replace("U", "T").upper()
     seq_obj = Seq(normalized)
     if frame > 0:
@@ -27,7 +27,7 @@ def translate_with_biopython(seq: str, frame: int, stop_at_first_stop: bool) ->
 
     with warnings.catch_warnings():
         warnings.simplefilter("ignore", BiopythonWarning)
-        translated = str(trimmed.translate(table=1, stop_symbol="*"))
+        translated = str(trimmed.translate(table=table, stop_symbol="*"))
 
     if stop_at_first_stop:
         stop_index = translated.find("*")
@@ -57,3 +57,137 @@ def test_fna2faa():
     dna_seq = "ATGCACGGACACTCCCCGGACGTCACGACCACCACGGTGGACGTGGTCGCCCACGCGGGTTACCGCATCGGGGACCGCGTCCTGCGGGCCGCGAAGGTGACCGTGCTGGATCCTGAGAGCTGA"
     expected_aa_seq = "MHGHSPDVTTTTVDVVAHAGYRIGDRVLRAAKVTVLDPES*"
     assert web_bio_tools.translate_dna_frame(dna_seq, 1, True) == expected_aa_seq
+
+
+@given(
+    seq=st.text(alphabet=dna, min_size=3, max_size=120),
+    frame=st.sampled_from([-3, -2, -1, 1, 2, 3]),
+    table=st.sampled_from([1, 2, 3, 4, 5, 6, 9, 11, 12, 13, 14, 16, 21, 22, 23, 24, 25, 26, 29, 30]),
+)
+@settings(
+    max_examples=40,
+    suppress_health_check=[hp.HealthCheck.data_too_large],
+    deadline=None)
+def test_genetic_codes_match_biopython(seq, frame, table):
+    ours = web_bio_tools.translate_dna_frame(seq, frame, False, genetic_code=table)
+    assert ours == translate_with_biopython(seq, frame, False, table)
+
+
+def test_alternative_initiator():
+    assert web_bio_tools.translate_dna_frame("GTGAAATAA", 1, False, genetic_code=11) == "VK*"
+    assert web_bio_tools.translate_dna_frame(
+        "GTGAAATAA", 1, False, genetic_code=11, initiator_as_methionine=True) == "MK*"
+    frames = web_bio_tools.translate_dna_all_frames("GTGAAATAA", False, genetic_code=11, report_start_codon=True)
+    assert frames[0].start_codon == "GTG"
+    assert frames[0].amino_acids == "VK*"
+
+
+@given(seq=st.text(alphabet="ACGT", min_size=0, max_size=300))
+@settings(max_examples=40, deadline=None)
+def test_orfs_match_coordinates(seq):
+    for orf in web_bio_tools.find_orfs(seq, min_length=1, nested="all"):
+        region = Seq(seq[orf.start:orf.end])
+        if orf.strand == "-":
+            region = region.reverse_complement()
+        protein = str(region.translate(to_stop=True))
+        assert orf.protein == "M" + protein[1:]
+        assert orf.start_codon == str(region[:3])
+
+
+def test_find_orfs_fasta():
+    fasta = web_bio_tools.find_orfs_fasta("CCATGAAATTTTAAGG", name="contig", min_length=2)
+    assert fasta == ">contig_ORF1 strand=+ frame=3 start=3 end=14 length=3\nMKF\n"
+
+
+@given(
+    seq=st.text(alphabet="ACGTN", min_size=0, max_size=90),
+    frame=st.sampled_from([-3, -2, -1, 1, 2, 3]),
+)
+@settings(max_examples=40, deadline=None)
+def test_codon_sites_map_to_sequence(seq, frame):
+    translation = next(
+        t for t in web_bio_tools.translate_dna_all_frames(seq, False) if t.frame == frame)
+    assert [site.aa_index for site in translation.stop_sites] == translation.stops
+    assert [site.aa_index for site in translation.ambiguous_sites] == translation.ambiguous
+    for site in translation.stop_sites + translation.ambiguous_sites:
+        codon = Seq(seq[site.start:site.end])
+        if frame < 0:
+            codon = codon.reverse_complement()
+        assert str(codon) == site.codon
+        assert web_bio_tools.aa_to_nucleotide(len(seq), frame, site.aa_index) == (site.start, site.end)
+        assert web_bio_tools.nucleotide_to_aa(len(seq), frame, site.start)[0] == site.aa_index
+
+
+@given(
+    seqs=st.lists(st.text(alphabet=dna, min_size=1, max_size=200), min_size=1, max_size=5),
+    chunk_size=st.integers(min_value=1, max_value=64),
+    frame=st.sampled_from([None, -3, -1, 1, 2]),
+)
+@settings(max_examples=30, deadline=None)
+def test_translation_stream_matches_whole_input(seqs, chunk_size, frame):
+    fasta = "".join(f">seq{i} sample\n{seq}\n" for i, seq in enumerate(seqs)).encode()
+    stream = web_bio_tools.PyTranslationStream(frame=frame)
+
+    def records():
+        for start in range(0, len(fasta), chunk_size):
+            stream.push(fasta[start:start + chunk_size])
+            yield from stream
+        stream.finish()
+        yield from stream
+
+    translated = list(records())
+    assert [r.id for r in translated] == [f"seq{i}" for i in range(len(seqs))]
+    for record, seq in zip(translated, seqs):
+        if frame is None:
+            expected = [t.amino_acids for t in web_bio_tools.translate_dna_all_frames(seq, False)]
+        else:
+            expected = [web_bio_tools.translate_dna_frame(seq, frame, False)]
+        assert [t.amino_acids for t in record.frames] == expected
+        assert record.to_fasta().startswith(f">{record.id}:")
+
+
+@given(
+    seq=st.text(alphabet=dna, min_size=0, max_size=300),
+    frame=st.sampled_from([-3, -2, -1, 1, 2, 3]),
+)
+@settings(max_examples=50, deadline=None)
+def test_recoding_replaces_only_internal_stops(seq, frame):
+    plain = translate_with_biopython(seq, frame, False)
+    if frame > 0:
+        working = seq[frame - 1:]
+    else:
+        working = str(Seq(seq).reverse_complement())[-frame - 1:]
+    codons = [working[i:i + 3] for i in range(0, len(plain) * 3, 3)]
+    # Only a stop codon followed by another full codon is internal.
+    internal = {"TGA": "U", "TAG": "O"}
+    expected = "".join(
+        internal[codon] if codon in internal and index + 1 < len(codons) else aa
+        for index, (codon, aa) in enumerate(zip(codons, plain))
+    )
+    recoded = web_bio_tools.translate_dna_all_frames(
+        seq, False, selenocysteine=True, pyrrolysine=True, recode_frame=frame
+    )
+    (translation,) = [t for t in recoded if t.frame == frame]
+    assert translation.amino_acids == expected
+    assert translation.recoded == [
+        index for index, codon in enumerate(codons[:-1]) if codon in internal
+    ]
+    assert set(translation.recoded).isdisjoint(translation.stops)
+    for other in recoded:
+        if other.frame != frame:
+            assert other.recoded == []
+
+
+def test_recoding_at_positions():
+    seq = "ATGTGATAGAAATGA"
+    assert web_bio_tools.translate_dna_frame(seq, 1, False, selenocysteine=True) == "MU*K*"
+    assert web_bio_tools.translate_dna_frame(seq, 1, False, selenocysteine=True, pyrrolysine=True) == "MUOK*"
+    # The last TGA is recoded when asked for by position.
+    assert web_bio_tools.translate_dna_frame(seq, 1, False, selenocysteine=True, recode_positions=[14]) == "M**KU"
+    (frame,) = [t for t in web_bio_tools.translate_dna_all_frames(seq, False, selenocysteine=True) if t.frame == 1]
+    assert frame.recoded == [1] and frame.stops == [2, 4]
+    assert (frame.recoded_sites[0].start, frame.recoded_sites[0].codon) == (3, "TGA")
+    stream = web_bio_tools.PyTranslationStream(frame=1, selenocysteine=True, pyrrolysine=True)
+    stream.push(f">a\n{seq}\n")
+    stream.finish()
+    assert [r.frames[0].amino_acids for r in stream] == ["MUOK*"]
@@ -0,0 +1,72 @@
+import gzip
+import random
+
+import pytest
+import web_bio_tools
+from hypothesis import given, settings
+from hypothesis import strategies as st
+
+# Codons without stops in any frame when repeated, and a motif reading TAA in
+# both other frames.
+SENSE = ["GCT", "GAA", "AAA", "CTG", "GGC", "ATT", "CGT", "GCG", "GAT", "AGC"]
+MOTIF = "CTAAAAGATAAA"
+
+
+def gene(rng, codons):
+    body = "".join(rng.choice(SENSE) + MOTIF for _ in range(codons))
+    return "ATG" + body + "TAA"
+
+
+@given(seed=st.integers(min_value=0, max_value=10**6), codons=st.integers(min_value=3, max_value=20))
+@settings(max_examples=40, deadline=None)
+def test_intact_genes_match_plain_translation(seed, codons):
+    seq = gene(random.Random(seed), codons)
+    (record,) = web_bio_tools.translate_with_frameshifts(f">g\n{seq}\n")
+    assert record.protein == web_bio_tools.translate_dna_frame(seq, 1, False)
+    assert not record.pseudogene
+    assert record.frameshifts == [] and record.internal_stops == []
+
+
+@given(
+    seed=st.integers(min_value=0, max_value=10**6),
+    codons=st.integers(min_value=4, max_value=20),
+    insert=st.booleans(),
+)
+@settings(max_examples=40, deadline=None)
+def test_single_indels_are_corrected(seed, codons, insert):
+    rng = random.Random(seed)
+    seq = gene(rng, codons)
+    intact = web_bio_tools.translate_dna_frame(seq, 1, False)
+    cut = rng.randrange(6, len(seq) - 30)
+    mutated = seq[:cut] + "G" + seq[cut:] if insert else seq[:cut] + seq[cut + 1:]
+    (record,) = web_bio_tools.translate_with_frameshifts(mutated)
+    assert record.pseudogene
+    assert [shift.shift for shift in record.frameshifts] == [1 if insert else -1]
+    shift = record.frameshifts[0]
+    assert record.protein[shift.protein_index] == "!"
+    assert record.internal_stops == []
+    corrected = record.protein.replace("!", "")
+    assert len(corrected) == len(intact)
+    assert corrected.endswith("*")
+    assert "[pseudo frameshifts=1 internal_stops=0]" in record.to_fasta()
+
+
+def test_options_and_errors():
+    seq = "ATG" + "GCT" * 10 + "TGA" + "GCT" * 10 + "TAA"
+    fasta = gzip.compress(f">a desc\n{seq}\n".encode())
+    (record,) = web_bio_tools.translate_with_frameshifts(fasta)
+    assert record.internal_stops == [11]
+    assert record.to_fasta().startswith(">a desc [pseudo frameshifts=0 internal_stops=1]\n")
+    # Table 4 reads TGA as tryptophan.
+    (record,) = web_bio_tools.translate_with_frameshifts(fasta, genetic_code=4)
+    assert not record.pseudogene
+    with pytest.raises(ValueError):
+        web_bio_tools.translate_with_frameshifts(">a\nACGT\n", frame=0)
+
+
+@pytest.mark.parametrize("penalty", [float("inf"), float("nan"), -1.0])
+def test_rejects_invalid_penalties(penalty):
+    with pytest.raises(ValueError):
+        web_bio_tools.translate_with_frameshifts(">a\nTAAATGGCC\n", stop_penalty=penalty)
+    with pytest.raises(ValueError):
+        web_bio_tools.translate_with_frameshifts(">a\nTAAATGGCC\n", shift_penalty=penalty)
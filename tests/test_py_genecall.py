@@ -0,0 +1,53 @@
+import random
+
+import web_bio_tools
+from Bio.Seq import Seq
+
+CODONS = ["GCC", "GCG", "CTG", "GAC", "GAG", "AAG", "CGC", "GGC",
+          "ACC", "GTG", "CAG", "TTC", "ATC", "AAC", "CCG", "TCG"]
+
+
+def synthetic_contig(n_genes=6):
+    rng = random.Random(13)
+    contig = ""
+    genes = []
+    for idx in range(n_genes):
+        contig += "".join(rng.choice("AATTACGT") for _ in range(150))
+        gene = "ATG" + "".join(rng.choice(CODONS) for _ in range(150 + 10 * idx)) + "TAA"
+        element = "AGGAGGTATCAT" + gene
+        start = len(contig)
+        if idx % 2:
+            contig += str(Seq(element).reverse_complement())
+            genes.append((start, start + len(gene), "-"))
+        else:
+            contig += element
+            genes.append((start + 12, start + len(element), "+"))
+    contig += "".join(rng.choice("AATTACGT") for _ in range(150))
+    return contig, genes
+
+
+def test_predict_genes():
+    contig, expected = synthetic_contig()
+    prediction = web_bio_tools.predict_genes([("contig1 test", contig)])
+    found = {(gene.start, gene.end, gene.strand) for gene in prediction.genes}
+    assert set(expected) <= found
+    for gene in prediction.genes:
+        region = Seq(contig[gene.start:gene.end])
+        if gene.strand == "-":
+            region = region.reverse_complement()
+        protein = str(region.translate(table=11, to_stop=True))
+        if gene.start_codon is not None:
+            protein = "M" + protein[1:]
+        assert gene.protein == protein
+    assert prediction.gff3.startswith("##gff-version 3\n")
+    assert prediction.gff3.count("\tCDS\t") == len(prediction.genes)
+    assert prediction.proteins.count(">contig1_") == len(prediction.genes)
+
+
+def test_predict_genes_invalid():
+    try:
+        web_bio_tools.predict_genes([("c", "ATGAAATAA")], genetic_code=7)
+    except ValueError:
+        pass
+    else:
+        assert False, "expected ValueError"
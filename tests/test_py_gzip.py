@@ -0,0 +1,39 @@
+import gzip
+import zlib
+
+import pytest
+import web_bio_tools
+from hypothesis import given, settings
+from hypothesis import strategies as st
+
+
+@given(
+    chunks=st.lists(st.binary(max_size=2000), min_size=1, max_size=4),
+    level=st.integers(min_value=0, max_value=9),
+)
+@settings(max_examples=50, deadline=None)
+def test_decompress_matches_python_gzip(chunks, level):
+    # Concatenated members, as written by bgzip or `cat a.gz b.gz`.
+    data = b"".join(gzip.compress(chunk, compresslevel=level) for chunk in chunks)
+    assert web_bio_tools.decompress_gzip(data) == b"".join(chunks)
+
+
+def test_readers_accept_gzipped_bytes():
+    fasta = b">seq1 first\nACGT\nTTGA\n>seq2\nMK*\n"
+    records = web_bio_tools.parse_fasta(gzip.compress(fasta))
+    assert [r.id for r in records] == ["seq1", "seq2"]
+    assert records[0].sequence == "ACGTTTGA"
+    assert [r.id for r in web_bio_tools.parse_fasta(fasta)] == ["seq1", "seq2"]
+
+    fastq = b"@r1\nACGT\n+\nIIII\n"
+    reads = web_bio_tools.parse_fastq(gzip.compress(fastq))
+    assert reads[0].sequence == "ACGT"
+    assert list(reads[0].quality) == [40, 40, 40, 40]
+
+
+def test_decompress_errors():
+    data = gzip.compress(b"ACGT" * 100)
+    with pytest.raises(ValueError, match="Truncated"):
+        web_bio_tools.decompress_gzip(data[:-4])
+    with pytest.raises(ValueError):
+        web_bio_tools.decompress_gzip(zlib.compress(b"ACGT"))